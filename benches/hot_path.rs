@@ -0,0 +1,137 @@
+//! Baseline benchmarks for the per-statement hot path: value parsing,
+//! single-value transforms and a whole synthetic dump.
+//!
+//! Run with `cargo bench`; compare against a saved baseline with
+//! `cargo bench -- --save-baseline before` / `--baseline before`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ghostdb::config::{AppConfig, ColumnStrategy, OnError};
+use ghostdb::processor::{process_stream, RunOptions};
+use ghostdb::transformer::Transformer;
+
+const ROWS: usize = 10_000;
+
+fn synthetic_dump(rows: usize) -> String {
+    let mut sql = String::from("-- synthetic dump\n");
+    for i in 0..rows {
+        sql.push_str(&format!(
+            "INSERT INTO users (id, email, first_name, phone, note) VALUES ({}, 'user{}@work.com', 'Name{}', '+1-555-{:04}', 'it''s note, number {}');\n",
+            i, i, i, i % 10_000, i
+        ));
+    }
+    sql
+}
+
+fn bench_parse_values(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_values");
+    let typical = "1, 'alice@work.com', 'Alice', '+1-555-0100', 'it''s a note, really', NULL, 42.5";
+    let long_string = format!("1, '{}'", "x".repeat(64 * 1024));
+    let many_commas = format!("'{}'", ",".repeat(16 * 1024));
+    let many_escapes = format!("'{}'", "\\'".repeat(16 * 1024));
+    let many_values = vec!["'v'"; 2_000].join(", ");
+    for (name, input) in [
+        ("typical", typical.to_string()),
+        ("long_string", long_string),
+        ("quoted_commas", many_commas),
+        ("escaped_quotes", many_escapes),
+        ("many_values", many_values),
+    ] {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &input, |b, input| {
+            b.iter(|| Transformer::parse_values(black_box(input)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_transform(c: &mut Criterion) {
+    let transformer = Transformer::new(42);
+    let mut group = c.benchmark_group("transform");
+    for (name, strategy, value) in [
+        ("keep", ColumnStrategy::Keep, "'alice@work.com'"),
+        ("fixed", ColumnStrategy::Fixed("REDACTED".into()), "'secret'"),
+        ("mask", ColumnStrategy::default_mask(), "'alice@work.com'"),
+        ("email", ColumnStrategy::Email, "'alice@work.com'"),
+        ("first_name", ColumnStrategy::FirstName, "'Alice'"),
+        ("full_name", ColumnStrategy::FullName, "'Alice Smith'"),
+        ("job_title", ColumnStrategy::JobTitle, "'Head of Sales'"),
+        ("phone", ColumnStrategy::Phone, "'+1-555-0100'"),
+        (
+            "date_shift",
+            ColumnStrategy::DateShift {
+                max_days: 30,
+                on_error: OnError::Keep,
+            },
+            "'2024-02-29 12:00:00'",
+        ),
+        ("vin", ColumnStrategy::Vin, "'1M8GDM9AXKP042788'"),
+        ("ssn", ColumnStrategy::Ssn, "'123-45-6789'"),
+        (
+            "json_pii",
+            ColumnStrategy::JsonPii,
+            "'{\"name\": \"Alice Smith\", \"email\": \"a@b.com\", \"tags\": [\"vip\", 3]}'",
+        ),
+        (
+            "template",
+            ColumnStrategy::Template("{firstname:lower}.{lastname:lower}-{rand:4}@corp.test".to_string()),
+            "'alice@work.com'",
+        ),
+        ("hash", ColumnStrategy::Hash, "'sess_8f2b1c9e'"),
+        ("random_int", ColumnStrategy::RandomInt { min: 18, max: 90 }, "42"),
+        (
+            "pseudonym",
+            ColumnStrategy::Pseudonym {
+                namespace: "users".to_string(),
+            },
+            "1048576",
+        ),
+        (
+            "scrub_text",
+            ColumnStrategy::ScrubText,
+            "'Contact: john@x.com, phone (555) 123-4567. Prefers mornings.'",
+        ),
+        (
+            "array_of_email",
+            ColumnStrategy::ArrayOf(Box::new(ColumnStrategy::Email)),
+            "'{a@b.com,c@d.com,e@f.com}'",
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| transformer.transform(black_box(value), &strategy).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_whole_dump(c: &mut Criterion) {
+    let config: AppConfig = serde_yaml::from_str(
+        "tables:\n  users:\n    columns:\n      id: keep\n      email: email\n      first_name: first_name\n      phone: phone\n      note: mask\n",
+    )
+    .unwrap();
+    let sql = synthetic_dump(ROWS);
+    let options = RunOptions::default();
+
+    let mut group = c.benchmark_group("whole_dump");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(sql.len() as u64));
+    group.bench_function(format!("{}_rows", ROWS), |b| {
+        b.iter(|| {
+            let transformer = Transformer::new(42);
+            let mut out = Vec::with_capacity(sql.len());
+            process_stream(&transformer, sql.as_bytes(), &mut out, &config, &options).unwrap();
+            out
+        })
+    });
+    group.bench_function(format!("{}_rows_cached", ROWS), |b| {
+        b.iter(|| {
+            let transformer = Transformer::new(42).with_cache(10_000);
+            let mut out = Vec::with_capacity(sql.len());
+            process_stream(&transformer, sql.as_bytes(), &mut out, &config, &options).unwrap();
+            out
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_values, bench_transform, bench_whole_dump);
+criterion_main!(benches);
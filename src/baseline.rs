@@ -0,0 +1,208 @@
+//! Incremental runs against a previous export. `--mapping-out` records,
+//! for every row of a table with a `key` column, a hash of the key, a
+//! fingerprint of the original row and the anonymized values written for
+//! it. `--baseline` reads such a file back, so rows whose fingerprint is
+//! unchanged get exactly their previous values and only new or changed
+//! rows are anonymized. The file holds no original values: keys and rows
+//! are hashed together with the seed.
+
+use crate::checksum::to_hex;
+use crate::config::AppConfig;
+use crate::failure::Failure;
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// First line of a mapping file: ties its rows to one config and seed.
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    fingerprint: String,
+}
+
+/// One anonymized row.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    table: String,
+    /// Hash of the seed, table and key value.
+    key: String,
+    /// Hash of the seed, table, column names and original values.
+    row: String,
+    values: Vec<String>,
+}
+
+/// Baseline rows by table and key hash: the row hash and the values.
+type Rows = HashMap<(String, String), (String, Vec<String>)>;
+
+/// Hashes the config and seed, so a mapping is only reused by runs that
+/// would anonymize its rows the same way.
+pub fn fingerprint(config: &AppConfig, seed: u64) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.to_le_bytes());
+    // Through `serde_json::Value`, whose maps are sorted, so the hash
+    // doesn't depend on `HashMap` iteration order.
+    hasher.update(serde_json::to_value(config)?.to_string());
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// State of an incremental run: the rows of the baseline, if one was
+/// given and matches the run, and the mapping file being written.
+pub struct Incremental {
+    seed: u64,
+    baseline: Rows,
+    out: Option<Mutex<BufWriter<File>>>,
+    reused: AtomicUsize,
+}
+
+impl Incremental {
+    /// Loads the baseline at `baseline` and creates the mapping file at
+    /// `mapping_out`. A baseline written with another config or seed is
+    /// ignored with a warning: every row is then anonymized afresh.
+    pub fn new(
+        baseline: Option<&Path>,
+        mapping_out: Option<&Path>,
+        config: &AppConfig,
+        seed: u64,
+    ) -> Result<Self> {
+        let fingerprint = fingerprint(config, seed)?;
+        let rows = match baseline {
+            Some(path) => load(path, &fingerprint)?,
+            None => HashMap::new(),
+        };
+        let out = match mapping_out {
+            Some(path) => {
+                let file = File::create(path)
+                    .with_context(|| format!("Failed to create mapping file: {:?}", path))
+                    .context(Failure::Io)?;
+                let mut writer = BufWriter::new(file);
+                serde_json::to_writer(&mut writer, &Header { fingerprint })?;
+                writer
+                    .write_all(b"\n")
+                    .context("Failed to write mapping")
+                    .context(Failure::Io)?;
+                Some(Mutex::new(writer))
+            }
+            None => None,
+        };
+        Ok(Self {
+            seed,
+            baseline: rows,
+            out,
+            reused: AtomicUsize::new(0),
+        })
+    }
+
+    /// The key and row hashes of a row, or `None` if it has no `key`
+    /// column or the key is `NULL`.
+    pub fn row_id(&self, table: &str, columns: &[String], values: &[String], key: &str) -> Option<RowId> {
+        let i = columns.iter().position(|c| c.eq_ignore_ascii_case(key))?;
+        if values[i].eq_ignore_ascii_case("NULL") {
+            return None;
+        }
+        let hash = |parts: &mut dyn Iterator<Item = &str>| {
+            let mut hasher = Sha256::new();
+            hasher.update(self.seed.to_le_bytes());
+            for part in parts {
+                // Length-prefixed, so ("ab", "c") and ("a", "bc") differ.
+                hasher.update((part.len() as u64).to_le_bytes());
+                hasher.update(part);
+            }
+            to_hex(&hasher.finalize())
+        };
+        Some(RowId {
+            key: hash(&mut [table, values[i].as_str()].into_iter()),
+            row: hash(
+                &mut std::iter::once(table)
+                    .chain(columns.iter().map(String::as_str))
+                    .chain(values.iter().map(String::as_str)),
+            ),
+        })
+    }
+
+    /// The values the baseline has for `id`, if the row is unchanged.
+    pub fn reuse(&self, table: &str, id: &RowId, width: usize) -> Option<Vec<String>> {
+        let (row, values) = self.baseline.get(&(table.to_string(), id.key.clone()))?;
+        if *row != id.row || values.len() != width {
+            return None;
+        }
+        self.reused.fetch_add(1, Ordering::Relaxed);
+        Some(values.clone())
+    }
+
+    /// Adds a row to the mapping file, if one is being written.
+    pub fn record(&self, table: &str, id: RowId, values: &[String]) -> Result<()> {
+        let Some(out) = &self.out else {
+            return Ok(());
+        };
+        let record = Record {
+            table: table.to_string(),
+            key: id.key,
+            row: id.row,
+            values: values.to_vec(),
+        };
+        let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+        serde_json::to_writer(&mut *out, &record)?;
+        out.write_all(b"\n")
+            .context("Failed to write mapping")
+            .context(Failure::Io)
+    }
+
+    /// How many rows were taken from the baseline.
+    pub fn reused_rows(&self) -> usize {
+        self.reused.load(Ordering::Relaxed)
+    }
+
+    /// Flushes the mapping file.
+    pub fn finish(&self) -> Result<()> {
+        if let Some(out) = &self.out {
+            out.lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .flush()
+                .context("Failed to write mapping")
+                .context(Failure::Io)?;
+        }
+        Ok(())
+    }
+}
+
+/// Identifies a row across runs.
+pub struct RowId {
+    key: String,
+    row: String,
+}
+
+fn load(path: &Path, fingerprint: &str) -> Result<Rows> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open baseline: {:?}", path))
+        .context(Failure::Io)?;
+    let mut lines = BufReader::new(file).lines();
+    let corrupt = || format!("{:?} is not a mapping written by --mapping-out", path);
+    let header: Header = match lines.next() {
+        Some(line) => serde_json::from_str(&line.context(Failure::Io)?)
+            .with_context(corrupt)
+            .context(Failure::Config)?,
+        None => return Ok(HashMap::new()),
+    };
+    if header.fingerprint != fingerprint {
+        warn!(
+            event = "baseline_mismatch";
+            "Baseline {:?} was written with a different config or seed; anonymizing every row.",
+            path
+        );
+        return Ok(HashMap::new());
+    }
+    let mut rows = HashMap::new();
+    for line in lines {
+        let record: Record = serde_json::from_str(&line.context(Failure::Io)?)
+            .with_context(corrupt)
+            .context(Failure::Config)?;
+        rows.insert((record.table, record.key), (record.row, record.values));
+    }
+    Ok(rows)
+}
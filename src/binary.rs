@@ -0,0 +1,115 @@
+//! Hex and bit literals as written by MySQL dumps (`0x48656C6C6F`,
+//! `X'4865'`, `b'0101'`, `_binary '...'`) and PostgreSQL `bytea` hex
+//! strings (`'\x4865'`).
+
+use rand::Rng;
+
+const HEX_DIGITS: &[u8] = b"0123456789ABCDEF";
+
+/// Whether `value` is a raw SQL token spelling binary data rather than text.
+/// Plain quoted strings are not, even if they hold bytes, except for the
+/// `bytea` hex form `'\x...'`.
+pub fn is_literal(value: &str) -> bool {
+    if strip_introducer(value).is_some() {
+        return true;
+    }
+    hex_digits(value).is_some() || bit_digits(value).is_some() || bytea_digits(value).is_some()
+}
+
+/// Regenerates the bytes of a binary value, keeping its length and the
+/// way it is written. Quoted strings (such as BLOBs dumped without
+/// `--hex-blob`) become a `0x` literal of the same byte length. Returns
+/// `None` for values that aren't binary or quoted, such as `NULL`.
+pub fn randomize<R: Rng>(value: &str, rng: &mut R) -> Option<String> {
+    if let Some((introducer, rest)) = strip_introducer(value) {
+        return randomize(rest, rng).map(|body| format!("{}{}", introducer, body));
+    }
+    if let Some((prefix, digits, suffix)) = hex_digits(value) {
+        return Some(format!("{}{}{}", prefix, random_digits(HEX_DIGITS, digits.len(), rng), suffix));
+    }
+    if let Some((prefix, digits, suffix)) = bit_digits(value) {
+        return Some(format!("{}{}{}", prefix, random_digits(b"01", digits.len(), rng), suffix));
+    }
+    if let Some(digits) = bytea_digits(value) {
+        return Some(format!("'\\x{}'", random_digits(HEX_DIGITS, digits.len(), rng)));
+    }
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        let len = unescaped_len(&value[1..value.len() - 1]);
+        return Some(format!("0x{}", random_digits(HEX_DIGITS, len * 2, rng)));
+    }
+    None
+}
+
+/// Splits off a `_binary` character set introducer, returning it with its
+/// trailing whitespace and the literal that follows.
+fn strip_introducer(value: &str) -> Option<(&str, &str)> {
+    let head = value.get(..7)?;
+    if !head.eq_ignore_ascii_case("_binary") {
+        return None;
+    }
+    let rest = value[7..].trim_start();
+    let split = value.len() - rest.len();
+    let quoted = rest.len() >= 2 && rest.starts_with('\'') && rest.ends_with('\'');
+    if quoted || hex_digits(rest).is_some() || bit_digits(rest).is_some() {
+        Some((&value[..split], rest))
+    } else {
+        None
+    }
+}
+
+/// `0x...` or `X'...'`, split into prefix, digits and suffix.
+fn hex_digits(value: &str) -> Option<(&str, &str, &str)> {
+    radix_digits(value, 'x', |c| c.is_ascii_hexdigit())
+}
+
+/// `0b...` or `b'...'`, split into prefix, digits and suffix.
+fn bit_digits(value: &str) -> Option<(&str, &str, &str)> {
+    radix_digits(value, 'b', |c| c == '0' || c == '1')
+}
+
+fn radix_digits(value: &str, marker: char, is_digit: impl Fn(char) -> bool) -> Option<(&str, &str, &str)> {
+    let lower = value.to_ascii_lowercase();
+    let (prefix, digits, suffix) = if lower.starts_with(&format!("0{}", marker)) {
+        (&value[..2], &value[2..], "")
+    } else if lower.starts_with(&format!("{}'", marker)) && value.len() >= 3 && value.ends_with('\'') {
+        (&value[..2], &value[2..value.len() - 1], "'")
+    } else {
+        return None;
+    };
+    // `0x` alone is not a literal, but `X''` is (an empty string).
+    if (suffix.is_empty() && digits.is_empty()) || !digits.chars().all(is_digit) {
+        return None;
+    }
+    Some((prefix, digits, suffix))
+}
+
+/// Hex digits of a PostgreSQL `bytea` string in hex format, `'\x...'`.
+fn bytea_digits(value: &str) -> Option<&str> {
+    let digits = value.strip_prefix("'\\x")?.strip_suffix('\'')?;
+    if digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(digits)
+    } else {
+        None
+    }
+}
+
+/// Byte length of a quoted string body after `\x` and `''` escapes.
+fn unescaped_len(body: &str) -> usize {
+    let bytes = body.as_bytes();
+    let mut len = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' || (bytes[i] == b'\'' && bytes.get(i + 1) == Some(&b'\'')) {
+            i += 1;
+        }
+        i += 1;
+        len += 1;
+    }
+    len
+}
+
+fn random_digits<R: Rng>(alphabet: &[u8], len: usize, rng: &mut R) -> String {
+    (0..len)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
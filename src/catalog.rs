@@ -0,0 +1,273 @@
+//! The strategies offered to users. The interactive wizard's menu and
+//! `list-strategies` both read from here, so they can't drift apart.
+
+use crate::config::{ColumnStrategy, GeoAxis, OnError};
+use crate::transformer::Transformer;
+use anyhow::Result;
+
+pub struct StrategyInfo {
+    /// Menu entry in the wizard.
+    pub label: &'static str,
+    /// One-line description for `list-strategies`.
+    pub description: &'static str,
+    /// Sample input, shown transformed with seed 42.
+    pub example: &'static str,
+    /// Example output for strategies that need more than the value (the
+    /// schema or an external program) to produce one.
+    pub example_output: Option<&'static str>,
+    /// The strategy with default parameters; the wizard prompts for the rest.
+    pub strategy: ColumnStrategy,
+    /// Whether the wizard offers it.
+    pub in_menu: bool,
+}
+
+impl StrategyInfo {
+    /// The strategy's name in configs, e.g. `zip_code`.
+    pub fn name(&self) -> String {
+        self.strategy.name()
+    }
+
+    /// The example's output, computed with the real transformer.
+    pub fn example_output(&self, transformer: &Transformer) -> Result<String> {
+        match self.example_output {
+            Some(output) => Ok(output.to_string()),
+            None => transformer.transform(self.example, &self.strategy),
+        }
+    }
+}
+
+fn entry(
+    label: &'static str,
+    description: &'static str,
+    example: &'static str,
+    strategy: ColumnStrategy,
+) -> StrategyInfo {
+    StrategyInfo {
+        label,
+        description,
+        example,
+        example_output: None,
+        strategy,
+        in_menu: true,
+    }
+}
+
+/// Every strategy, in menu order.
+pub fn strategies() -> Vec<StrategyInfo> {
+    vec![
+        entry("Keep (Original Value)", "Keeps the original value.", "123", ColumnStrategy::Keep),
+        entry(
+            "Email (fake@example.com)",
+            "Deterministic fake email address.",
+            "'alice@work.com'",
+            ColumnStrategy::Email,
+        ),
+        StrategyInfo {
+            example_output: Some("'jane.doe@example.com'"),
+            ..entry(
+                "Company Email (first.last@example.com)...",
+                "Work address built from the row's anonymized name columns.",
+                "'alice.smith@acme.com'",
+                ColumnStrategy::default_company_email(),
+            )
+        },
+        entry("First Name (Alice)", "Fake first name.", "'Alice'", ColumnStrategy::FirstName),
+        entry(
+            "First Name matching the row's gender (Alice)",
+            "Fake first name matching the row's `gender`/`sex` column.",
+            "'Alice'",
+            ColumnStrategy::GenderedFirstName { gender_column: None },
+        ),
+        entry("Last Name (Smith)", "Fake last name.", "'Smith'", ColumnStrategy::LastName),
+        entry(
+            "Full Name (Alice Smith)",
+            "Fake first and last name.",
+            "'Alice Smith'",
+            ColumnStrategy::FullName,
+        ),
+        entry(
+            "Job Title (Senior Data Engineer)",
+            "Fake job title.",
+            "'Head of Sales'",
+            ColumnStrategy::JobTitle,
+        ),
+        entry(
+            "Name Prefix (Mr, Ms, Dr)",
+            "Seeded honorific, keeping a trailing `.`.",
+            "'Mrs.'",
+            ColumnStrategy::NamePrefix,
+        ),
+        entry("Phone (+1-555...)", "Fake phone number.", "'+1-555-0199'", ColumnStrategy::Phone),
+        entry(
+            "Digit Scramble (555-0142 -> 339-0816)",
+            "Maps every digit through one seeded permutation, keeping the format.",
+            "'+1 555-0142'",
+            ColumnStrategy::DigitScramble,
+        ),
+        entry(
+            "VIN (1M8GDM9AXKP042788)",
+            "Fake vehicle identification number with a valid check digit.",
+            "'1M8GDM9AXKP042788'",
+            ColumnStrategy::Vin,
+        ),
+        entry(
+            "SSN (123-45-6789 -> 912-34-5678)",
+            "Fake Social Security number from a range that is never assigned.",
+            "'123-45-6789'",
+            ColumnStrategy::Ssn,
+        ),
+        entry(
+            "Hostname (db.acme.com -> quia-est.org)",
+            "Fake hostname, keeping `www.` and any port.",
+            "'www.acme.com:8080'",
+            ColumnStrategy::Hostname,
+        ),
+        entry(
+            "URL (https://quia-est.org/...)...",
+            "Fake URL; `keep_path` replaces only the host.",
+            "'https://shop.acme.com/orders?id=7'",
+            ColumnStrategy::Url { keep_path: false },
+        ),
+        entry(
+            "ZIP Code (keep prefix)...",
+            "Keeps the first `keep_prefix` digits of a ZIP code and regenerates the rest.",
+            "'94107-1234'",
+            ColumnStrategy::ZipCode { keep_prefix: 3 },
+        ),
+        entry(
+            "JSON PII (fake emails, phones, names in a document)",
+            "Fakes string values of a JSON column that look like PII, keeping the rest.",
+            "'{\"user\": {\"email\": \"alice@work.com\", \"plan\": \"pro\"}}'",
+            ColumnStrategy::JsonPii,
+        ),
+        entry(
+            "Scrub Text (fake emails, phones, SSNs inside prose)",
+            "Replaces only the PII found inside free text, keeping the prose.",
+            "'Call John at 555-123-4567 or john@x.com.'",
+            ColumnStrategy::ScrubText,
+        ),
+        entry(
+            "Mask (a***@example.com)...",
+            "Reveals a few leading/trailing characters and masks the rest.",
+            "'alice@work.com'",
+            ColumnStrategy::default_mask(),
+        ),
+        StrategyInfo {
+            example_output: Some("'suspended'"),
+            ..entry(
+                "Enum (another member of the column's ENUM)",
+                "Another member of the column's ENUM(...) type from the dump.",
+                "'active'",
+                ColumnStrategy::Enum,
+            )
+        },
+        entry(
+            "Random Bytes (0x48656C6C6F -> 0x9A1F03C2E7)",
+            "Random binary data of the same length and literal form.",
+            "0x48656C6C6F",
+            ColumnStrategy::RandomBytes,
+        ),
+        entry(
+            "Tokenize (value_1, value_2, ...)...",
+            "Numbers the column's distinct values in order of appearance.",
+            "'alice@work.com'",
+            ColumnStrategy::Tokenize {
+                prefix: "value".to_string(),
+            },
+        ),
+        entry(
+            "Template (EMP-{seq:5}, {firstname:lower}@corp.test)...",
+            "Fills a template's placeholders with seeded digits, names or a sequence number.",
+            "'alice@work.com'",
+            ColumnStrategy::Template("{firstname:lower}.{lastname:lower}@corp.test".to_string()),
+        ),
+        entry(
+            "Prefixed Hash (user_a1b2c3d4)...",
+            "Prefix and a truncated seeded SHA-256 of the value; join-safe.",
+            "'alice@work.com'",
+            ColumnStrategy::PrefixedHash {
+                prefix: "user".to_string(),
+                len: 8,
+            },
+        ),
+        entry(
+            "Hash (SHA-256 hex digest)",
+            "A full seeded SHA-256 of the value: one-way, join-safe, no prefix.",
+            "'alice@work.com'",
+            ColumnStrategy::Hash,
+        ),
+        entry(
+            "Pseudonym (shared namespace)...",
+            "Same pseudonym for the same value in every column of a namespace; keeps keys joinable.",
+            "1042",
+            ColumnStrategy::Pseudonym {
+                namespace: "users".to_string(),
+            },
+        ),
+        entry(
+            "Fixed Value...",
+            "Replaces every value with a fixed string.",
+            "'123 Main St'",
+            ColumnStrategy::Fixed("REDACTED".to_string()),
+        ),
+        entry(
+            "Random Integer (range)...",
+            "A seeded integer between `min` and `max`, unquoted for numeric columns.",
+            "42",
+            ColumnStrategy::RandomInt { min: 18, max: 90 },
+        ),
+        entry(
+            "Random Null (sparse NULLs)...",
+            "Replaces a seeded fraction of values with NULL.",
+            "'Alice'",
+            ColumnStrategy::RandomNull { probability: 1.0 },
+        ),
+        StrategyInfo {
+            in_menu: false,
+            ..entry(
+                "Array Of...",
+                "Applies another strategy to each element of a PostgreSQL array.",
+                "'{a@b.com,c@d.com}'",
+                ColumnStrategy::ArrayOf(Box::new(ColumnStrategy::Email)),
+            )
+        },
+        StrategyInfo {
+            in_menu: false,
+            ..entry(
+                "Date Shift...",
+                "Moves dates by a seeded number of days, keeping the format.",
+                "'2023-01-01 10:00:00'",
+                ColumnStrategy::DateShift {
+                    max_days: 30,
+                    on_error: OnError::Keep,
+                },
+            )
+        },
+        StrategyInfo {
+            in_menu: false,
+            ..entry(
+                "Geo Fuzz...",
+                "Moves a latitude or longitude within `radius_m` meters.",
+                "52.520008",
+                ColumnStrategy::GeoFuzz {
+                    radius_m: 500.0,
+                    axis: Some(GeoAxis::Lat),
+                    on_error: OnError::Keep,
+                },
+            )
+        },
+        StrategyInfo {
+            in_menu: false,
+            example_output: Some("'ABC'"),
+            ..entry(
+                "Command...",
+                "Pipes values through an external program (needs --allow-commands).",
+                "'abc'",
+                ColumnStrategy::Command {
+                    program: "tr".to_string(),
+                    args: vec!["a-z".to_string(), "A-Z".to_string()],
+                },
+            )
+        },
+    ]
+}
@@ -0,0 +1,100 @@
+use anyhow::{anyhow, Context, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// A long-running external transform process.
+///
+/// The protocol is line-based: GhostDB writes one value per line to the
+/// program's stdin and reads exactly one line back from its stdout as the
+/// replacement. Backslashes and newlines inside values are escaped as `\\`,
+/// `\n` and `\r` in both directions so that every value fits on one line.
+pub struct CommandProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    program: String,
+}
+
+impl CommandProcess {
+    pub fn spawn(program: &str, args: &[String]) -> Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to spawn transform command: {}", program))?;
+
+        let stdin = child.stdin.take().context("Command stdin unavailable")?;
+        let stdout = child.stdout.take().context("Command stdout unavailable")?;
+
+        Ok(Self {
+            child,
+            stdin: Some(stdin),
+            stdout: BufReader::new(stdout),
+            program: program.to_string(),
+        })
+    }
+
+    pub fn call(&mut self, value: &str) -> Result<String> {
+        let stdin = self.stdin.as_mut().context("Command stdin closed")?;
+        writeln!(stdin, "{}", escape_line(value))
+            .and_then(|_| stdin.flush())
+            .with_context(|| format!("Failed to write to transform command: {}", self.program))?;
+
+        let mut response = String::new();
+        let n = self
+            .stdout
+            .read_line(&mut response)
+            .with_context(|| format!("Failed to read from transform command: {}", self.program))?;
+        if n == 0 {
+            return Err(anyhow!(
+                "Transform command '{}' exited before answering",
+                self.program
+            ));
+        }
+
+        let response = response.strip_suffix('\n').unwrap_or(&response);
+        let response = response.strip_suffix('\r').unwrap_or(response);
+        Ok(unescape_line(response))
+    }
+}
+
+impl Drop for CommandProcess {
+    fn drop(&mut self) {
+        // Closing stdin signals EOF so well-behaved programs exit on their own.
+        self.stdin.take();
+        let _ = self.child.wait();
+    }
+}
+
+fn escape_line(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_line(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
@@ -1,37 +1,819 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::File;
+use serde::de::value::{EnumAccessDeserializer, MapAccessDeserializer};
+use serde::de::{self, EnumAccess, IntoDeserializer, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
 use std::path::Path;
-use anyhow::{Context, Result};
+use std::sync::OnceLock;
+use crate::failure::Failure;
+use crate::template::Template;
+use anyhow::{anyhow, Context, Result};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct AppConfig {
+    #[serde(serialize_with = "sorted")]
     pub tables: HashMap<String, TableConfig>,
+
+    /// Match table and column names from the dump ignoring ASCII case.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub case_insensitive_tables: bool,
+
+    /// Default strategies by declared SQL type (from the dump's `CREATE
+    /// TABLE`s), used for columns without a rule of their own.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "sorted")]
+    pub type_rules: HashMap<String, ColumnStrategy>,
+
+    /// Also apply `rename` / `rename_columns` to `CREATE TABLE` statements.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rename_create_table: bool,
+
+    /// Widen declared types in `CREATE TABLE` statements whose columns get
+    /// values that wouldn't fit them (see [`crate::schema::fitted_type`]).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fit_create_table: bool,
+
+    /// Columns that are always kept, whatever their table or type rules
+    /// say. Each entry is a column name or `table.column`, matched exactly
+    /// or as a regular expression against the whole name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_columns: Vec<String>,
+
+    /// What a quoted `'NULL'` string means. SQL `NULL` is always kept.
+    #[serde(default, skip_serializing_if = "QuotedNull::is_data")]
+    pub quoted_null: QuotedNull,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// How the string `'NULL'` (quoted, any case) is treated, as opposed to SQL
+/// `NULL`, which no strategy ever replaces.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotedNull {
+    /// An ordinary string, anonymized like any other value.
+    #[default]
+    Data,
+    /// A marker for missing data: kept as it is.
+    Keep,
+    /// A marker for missing data: rewritten to SQL `NULL`.
+    Normalize,
+}
+
+impl QuotedNull {
+    fn is_data(&self) -> bool {
+        *self == QuotedNull::Data
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct TableConfig {
+    /// Strategies by column name. A key written `/regex/` or `glob:pattern`
+    /// applies to every column whose whole name matches it and that has no
+    /// exact entry (see [`TableConfig::column`]).
+    #[serde(serialize_with = "sorted")]
     pub columns: HashMap<String, ColumnStrategy>,
+
+    /// Per-column tuning that applies on top of the column's strategy.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "sorted")]
+    pub options: HashMap<String, ColumnOptions>,
+
+    /// Overrides the global seed for this table only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Primary key column, which identifies rows across runs for
+    /// `--baseline` and `--mapping-out`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Pseudonym for the table's own name in the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rename: Option<String>,
+
+    /// Pseudonyms for column names in the output.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "sorted")]
+    pub rename_columns: HashMap<String, String>,
+
+    /// The pattern keys of `columns`, compiled on first use: case-sensitive
+    /// and case-insensitive.
+    #[serde(skip)]
+    column_patterns: [OnceLock<Vec<(String, Regex)>>; 2],
+}
+
+/// Serializes a map with its keys in order, so written configs are stable
+/// from run to run and diff cleanly.
+fn sorted<S: Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct ColumnOptions {
+    /// Upper bound (in characters) for generated names. The seeded faker is
+    /// retried a few times, then the last candidate is truncated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_len: Option<usize>,
+
+    /// Guarantee distinct outputs for distinct inputs (for `UNIQUE` columns).
+    /// Every pseudonym handed out is remembered for the rest of the run.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unique: bool,
+
+    /// Only apply the strategy to rows where this condition holds; other
+    /// rows keep the original value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<Condition>,
+
+    /// Name of the row's company column. The column's pseudonym (an email,
+    /// URL or hostname) gets a fake domain derived from the company's
+    /// anonymized value, so every address of one company shares a domain.
+    /// Rows without the company column keep the independent pseudonym.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_from: Option<String>,
+
+    /// Name of a stable row key column (usually the primary key). The
+    /// column's pseudonym is seeded by the key's original value as well as
+    /// its own, so rows with equal originals get distinct fakes, while each
+    /// row still gets the same fake on every run. Rows without the key
+    /// column, or where it is `NULL`, are seeded by the value alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_from: Option<String>,
+
+    /// Regex every generated value (unquoted) must match as a whole. A
+    /// value that doesn't is generated again with a derived seed, up to a
+    /// bounded number of attempts, after which the run fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub must_match: Option<String>,
 }
 
+/// A row condition for [`ColumnOptions::when`]: `column` must be present in
+/// the statement and its original value, unquoted, must equal `equals`.
+/// SQL `NULL` never matches.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
-#[serde(rename_all = "snake_case")]
+pub struct Condition {
+    pub column: String,
+    pub equals: String,
+}
+
+// `remote = "Self"` derives the externally-tagged (de)serializer as inherent
+// functions, which the hand-written trait impls below wrap.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(remote = "Self", rename_all = "snake_case")]
 pub enum ColumnStrategy {
     FirstName,
     LastName,
     FullName,
+    /// Fake job title, such as `Senior Data Engineer`.
+    JobTitle,
+    /// Seeded honorific (`Mr`, `Mrs`, `Ms`, `Miss`, `Mx`, `Dr`, `Prof`),
+    /// with a trailing `.` if the original had one.
+    NamePrefix,
+    /// Fake first name matching the gender in the same row's
+    /// `gender_column` (by default a column named `gender` or `sex`).
+    /// Rows without one get gender-neutral names.
+    GenderedFirstName {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        gender_column: Option<String>,
+    },
     Email,
+    /// Work address built from the row's anonymized name columns (those
+    /// using `first_name`, `gendered_first_name`, `last_name` or
+    /// `full_name`): `format` with `{first}`, `{last}`, `{f}` and `{l}`
+    /// (initials) filled in, lowercased, then `@domain`. Rows without name
+    /// columns get a seeded fake local part.
+    CompanyEmail {
+        #[serde(default = "default_company_domain")]
+        domain: String,
+        #[serde(default = "default_company_email_format")]
+        format: String,
+    },
     Phone,
-    Mask,
+    /// Reveals the first `reveal_start` and last `reveal_end` characters and
+    /// replaces the rest with three `char`s, so the length is hidden too.
+    /// Emails are masked in the local part only. Values too short to hide
+    /// anything become a single `char` (three for an email's local part).
+    Mask {
+        #[serde(default = "default_mask_char")]
+        char: char,
+        #[serde(default = "default_reveal_start")]
+        reveal_start: usize,
+        #[serde(default)]
+        reveal_end: usize,
+    },
     Fixed(String),
     Keep,
+    /// Fake vehicle identification number with a valid check digit.
+    Vin,
+    /// Fake US Social Security number from a range that is never assigned
+    /// (see [`crate::ssn`]), keeping the original's separators.
+    Ssn,
+    /// Substitutes every digit through a seeded permutation of 0-9, keeping
+    /// all other characters.
+    DigitScramble,
+    /// Fake hostname, keeping a leading `www.` and any `:port`. Equal hosts
+    /// get equal pseudonyms.
+    Hostname,
+    /// Fake URL. With `keep_path`, only the host is replaced and the scheme,
+    /// port, path, query and fragment are kept; otherwise the result is a
+    /// fresh `https://host/word`. Userinfo (`user:pass@`) is dropped.
+    Url {
+        #[serde(default)]
+        keep_path: bool,
+    },
+    /// Regenerates a US ZIP / ZIP+4 code after its first `keep_prefix`
+    /// digits; other postal codes are scrambled keeping their format.
+    ZipCode { keep_prefix: usize },
+    /// Swaps the value for another seeded member of the column's `ENUM(...)`
+    /// type, read from the dump's `CREATE TABLE`. Values outside the enum,
+    /// and columns without a known enum type, pass through unchanged.
+    Enum,
+    /// Regenerates binary data with random bytes of the same length, keeping
+    /// hex (`0x..`, `X'..'`), bit and `_binary` literals in their own form.
+    /// Quoted strings become `0x` literals.
+    RandomBytes,
+    /// Applies the inner strategy to each element of a PostgreSQL array literal.
+    ArrayOf(Box<ColumnStrategy>),
+    /// Replaces a seeded fraction of values with SQL `NULL`, keeping the rest.
+    /// Whether a value is nulled depends only on it and the seed, so runs
+    /// are reproducible. `probability` must be in `[0, 1]`; also accepted
+    /// as `null_out`.
+    #[serde(alias = "null_out")]
+    RandomNull { probability: f64 },
+    /// Seeded integer in `[min, max]`, written unquoted, for numeric columns
+    /// (ages, scores, quantities) that shouldn't keep their values.
+    RandomInt { min: i64, max: i64 },
+    /// Moves dates and timestamps by a seeded number of days in
+    /// `[-max_days, max_days]`, preserving format and timezone offset.
+    DateShift {
+        max_days: i64,
+        #[serde(default, skip_serializing_if = "OnError::is_keep")]
+        on_error: OnError,
+    },
+    /// Displaces a latitude or longitude by a seeded offset within `radius_m` meters.
+    GeoFuzz {
+        radius_m: f64,
+        /// Which coordinate the column holds; guessed from the column name when unset.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        axis: Option<GeoAxis>,
+        #[serde(default, skip_serializing_if = "OnError::is_keep")]
+        on_error: OnError,
+    },
+    /// Replaces each distinct value with `prefix_1`, `prefix_2`, ... in order
+    /// of first appearance in the column, so cardinality and equality are
+    /// kept but nothing of the value is. Every distinct original is
+    /// remembered for the rest of the run, and the numbering depends on
+    /// input order.
+    Tokenize { prefix: String },
+    /// `prefix_` followed by the first `len` hex digits of a SHA-256 of the
+    /// seed and the value, e.g. `user_a1b2c3d4`: a visibly fake, join-safe
+    /// key that needs no state. Distinct values collide only by chance, which
+    /// gets likely once a column has about `16^(len/2)` distinct values.
+    PrefixedHash {
+        prefix: String,
+        #[serde(default = "default_hash_len")]
+        len: usize,
+    },
+    /// All 64 hex digits of a SHA-256 of the seed and the value: one-way
+    /// and join-safe like `prefixed_hash`, for tokens and references that
+    /// need no readable prefix. Collisions are out of practical reach.
+    Hash,
+    /// Maps values through a keyed function of the seed and `namespace`
+    /// (see [`crate::pseudonym`]), so columns in different tables that share
+    /// a namespace, such as a key and the foreign keys referencing it, get
+    /// the same pseudonym for the same value. Integers map to distinct
+    /// integers with as many digits. Table `seed`s and `seed_from` don't
+    /// apply, since the namespace has to agree across tables.
+    Pseudonym { namespace: String },
+    /// Walks a JSON document column and fakes each string value that looks
+    /// like an email address, SSN, phone number or person's name (see
+    /// [`crate::detect`]) with that strategy, keeping keys, other values and
+    /// the document's layout. Values that aren't JSON are kept.
+    JsonPii,
+    /// Finds email addresses, SSNs and phone numbers inside free text (see
+    /// [`crate::detect::find_in_text`]) and replaces just those with fakes
+    /// of the matching strategy, keeping the prose around them.
+    ScrubText,
+    /// Fills a template such as `EMP-{seq:5}` or `{firstname:lower}@corp.test`
+    /// from the value's seed (see [`crate::template`] for the placeholders).
+    /// With `{seq}`, every distinct original is remembered for the rest of
+    /// the run and the numbering depends on input order, as with `tokenize`.
+    Template(String),
+    /// Pipes values through an external program (see `command.rs` for the protocol).
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl Serialize for ColumnStrategy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ColumnStrategy::serialize(self, serializer)
+    }
+}
+
+/// Accepts a strategy as a plain name (`email`), a YAML tag (`!fixed X`) or
+/// a single-key map (`fixed: X`). The map form is what lets YAML configs
+/// nest parameterized strategies, e.g. `array_of: !fixed X`.
+impl<'de> Deserialize<'de> for ColumnStrategy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(StrategyVisitor)
+    }
+}
+
+struct StrategyVisitor;
+
+impl<'de> Visitor<'de> for StrategyVisitor {
+    type Value = ColumnStrategy;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a column strategy")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        match v {
+            // Plain names for variants whose parameters all have defaults.
+            "url" => Ok(ColumnStrategy::Url { keep_path: false }),
+            "mask" => Ok(ColumnStrategy::default_mask()),
+            "gendered_first_name" => Ok(ColumnStrategy::GenderedFirstName { gender_column: None }),
+            "company_email" => Ok(ColumnStrategy::default_company_email()),
+            _ => ColumnStrategy::deserialize(v.into_deserializer()),
+        }
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        ColumnStrategy::deserialize(EnumAccessDeserializer::new(data))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        ColumnStrategy::deserialize(MapAccessDeserializer::new(map))
+    }
+}
+
+/// Parameters that can't work, as messages for [`AppConfig::validate`].
+fn strategy_problems(strategy: &ColumnStrategy) -> Vec<String> {
+    let breaks_sql = |s: &str| s.contains(['\'', '\\']);
+    let mut problems = Vec::new();
+    match strategy {
+        ColumnStrategy::Mask { char: '\'' | '\\', .. } => {
+            problems.push("`mask` `char` is a quote or backslash, which breaks quoted SQL strings".to_string());
+        }
+        ColumnStrategy::ZipCode { keep_prefix } if *keep_prefix > 5 => {
+            problems.push(format!("`keep_prefix` is {}, but a ZIP code has only 5 digits", keep_prefix));
+        }
+        ColumnStrategy::RandomNull { probability } if !(0.0..=1.0).contains(probability) => {
+            problems.push(format!("`probability` is {}, but must be from 0.0 to 1.0", probability));
+        }
+        ColumnStrategy::RandomInt { min, max } if min > max => {
+            problems.push(format!("`random_int` `min` {} is greater than `max` {}", min, max));
+        }
+        ColumnStrategy::DateShift { max_days, on_error } => {
+            if max_days.unsigned_abs() > MAX_DATE_SHIFT_DAYS {
+                problems.push(format!(
+                    "`max_days` is {}; shifts beyond {} days (~10,000 years) leave the supported date range",
+                    max_days, MAX_DATE_SHIFT_DAYS
+                ));
+            }
+            problems.extend(on_error_problems(on_error));
+        }
+        ColumnStrategy::GeoFuzz { radius_m, on_error, .. } => {
+            if !radius_m.is_finite() || *radius_m < 0.0 {
+                problems.push(format!("`radius_m` is {}, but must be a distance in meters", radius_m));
+            }
+            problems.extend(on_error_problems(on_error));
+        }
+        ColumnStrategy::Tokenize { prefix } if breaks_sql(prefix) => {
+            problems.push("`tokenize` `prefix` contains a quote or backslash, which breaks SQL".to_string());
+        }
+        ColumnStrategy::Pseudonym { namespace } if namespace.is_empty() => {
+            problems.push("`pseudonym` `namespace` is empty".to_string());
+        }
+        ColumnStrategy::PrefixedHash { prefix, len } => {
+            if breaks_sql(prefix) {
+                problems.push("`prefixed_hash` `prefix` contains a quote or backslash, which breaks SQL".to_string());
+            }
+            if !(1..=MAX_HASH_LEN).contains(len) {
+                problems.push(format!("`len` is {}, but must be 1 to {} hex digits", len, MAX_HASH_LEN));
+            }
+        }
+        ColumnStrategy::CompanyEmail { domain, format } => {
+            if domain.is_empty() || domain.contains(['@', ' ']) || breaks_sql(domain) {
+                problems.push(format!("`company_email` `domain` {:?} is not a domain name", domain));
+            }
+            if format.contains('@') || breaks_sql(format) {
+                problems.push(format!(
+                    "`company_email` `format` {:?} must not contain `@`, quotes or backslashes",
+                    format
+                ));
+            }
+        }
+        ColumnStrategy::Template(template) => {
+            if let Err(problem) = Template::parse(template) {
+                problems.push(format!("`template` {:?} {}", template, problem));
+            }
+        }
+        ColumnStrategy::ArrayOf(inner) => problems.extend(strategy_problems(inner)),
+        ColumnStrategy::Command { program, .. } if program.trim().is_empty() => {
+            problems.push("`command` has an empty `program`".to_string());
+        }
+        _ => {}
+    }
+    problems
+}
+
+fn on_error_problems(on_error: &OnError) -> Option<String> {
+    match on_error {
+        OnError::Fixed(value) if value.contains(['\'', '\\']) => {
+            Some("`on_error` `fixed` value contains a quote or backslash, which breaks SQL".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// `prefixed_hash`'s largest `len`, and the length of a `hash`: all hex
+/// digits of a SHA-256.
+pub const MAX_HASH_LEN: usize = 64;
+
+/// `date_shift`'s largest accepted `max_days`.
+const MAX_DATE_SHIFT_DAYS: u64 = 3_652_500;
+
+fn default_mask_char() -> char {
+    '*'
+}
+
+fn default_reveal_start() -> usize {
+    1
+}
+
+fn default_hash_len() -> usize {
+    8
+}
+
+fn default_company_domain() -> String {
+    "example.com".to_string()
+}
+
+fn default_company_email_format() -> String {
+    "{first}.{last}".to_string()
+}
+
+impl ColumnStrategy {
+    /// `mask` with its default parameters: `a***` for `alice`.
+    pub fn default_mask() -> Self {
+        ColumnStrategy::Mask {
+            char: default_mask_char(),
+            reveal_start: default_reveal_start(),
+            reveal_end: 0,
+        }
+    }
+
+    /// The strategy's name in configs, e.g. `zip_code`.
+    pub fn name(&self) -> String {
+        match serde_yaml::to_value(self) {
+            Ok(serde_yaml::Value::String(name)) => name,
+            Ok(serde_yaml::Value::Tagged(tagged)) => {
+                tagged.tag.to_string().trim_start_matches('!').to_string()
+            }
+            _ => format!("{:?}", self),
+        }
+    }
+
+    /// Whether the output depends on the seed, so that changing the seed
+    /// (rotating it) gives values new pseudonyms. `mask`, `fixed`, `keep`,
+    /// `tokenize` and `command` are seed-independent by design.
+    pub fn is_seeded(&self) -> bool {
+        match self {
+            ColumnStrategy::Keep
+            | ColumnStrategy::Fixed(_)
+            | ColumnStrategy::Mask { .. }
+            | ColumnStrategy::Tokenize { .. }
+            | ColumnStrategy::Command { .. } => false,
+            ColumnStrategy::ArrayOf(inner) => inner.is_seeded(),
+            _ => true,
+        }
+    }
+
+    /// Whether the strategy runs an external program, itself or for the
+    /// elements of an array.
+    pub fn contains_command(&self) -> bool {
+        match self {
+            ColumnStrategy::Command { .. } => true,
+            ColumnStrategy::ArrayOf(inner) => inner.contains_command(),
+            _ => false,
+        }
+    }
+
+    /// `company_email` with its default parameters: `jane.doe@example.com`.
+    pub fn default_company_email() -> Self {
+        ColumnStrategy::CompanyEmail {
+            domain: default_company_domain(),
+            format: default_company_email_format(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GeoAxis {
+    Lat,
+    Lon,
+}
+
+/// What `date_shift` and `geo_fuzz` write in place of a value they can't
+/// parse. `NULL`s are always kept.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnError {
+    /// The original value, unchanged.
+    #[default]
+    Keep,
+    /// SQL `NULL`.
+    Null,
+    /// A fixed value, quoted if the original was.
+    Fixed(String),
+}
+
+impl OnError {
+    fn is_keep(&self) -> bool {
+        *self == OnError::Keep
+    }
+}
+
+/// The file formats a config can be written in, told apart by the file's
+/// extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// The format of the config at `path`: `.yaml`/`.yml`, `.json` or
+    /// `.toml`, in any case.
+    pub fn of(path: &Path) -> Result<Self> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match extension.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "json" => Ok(ConfigFormat::Json),
+            "toml" => Ok(ConfigFormat::Toml),
+            _ => Err(anyhow!(
+                "Unknown configuration format {:?}: use a .yaml, .yml, .json or .toml file",
+                path
+            ))
+            .context(Failure::Config),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+        }
+    }
 }
 
 impl AppConfig {
+    /// Loads and validates the config at `path`, in the format its
+    /// extension names.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = File::open(path).context("Failed to open configuration file")?;
-        let config: AppConfig = serde_yaml::from_reader(file)
-            .context("Failed to parse YAML configuration")?;
+        let path = path.as_ref();
+        let format = ConfigFormat::of(path)?;
+        let text = fs::read_to_string(path)
+            .context("Failed to open configuration file")
+            .context(Failure::Config)?;
+        let config: AppConfig = match format {
+            ConfigFormat::Yaml => serde_yaml::from_str(&text).map_err(anyhow::Error::from),
+            ConfigFormat::Json => serde_json::from_str(&text).map_err(anyhow::Error::from),
+            ConfigFormat::Toml => toml::from_str(&text).map_err(anyhow::Error::from),
+        }
+        .with_context(|| format!("Failed to parse {} configuration", format.name()))
+        .context(Failure::Config)?;
+        config.validate()?;
         Ok(config)
     }
+
+    /// The config written out in `format`.
+    pub fn to_string_as(&self, format: ConfigFormat) -> Result<String> {
+        Ok(match format {
+            ConfigFormat::Yaml => serde_yaml::to_string(self)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(self)? + "\n",
+            ConfigFormat::Toml => toml::to_string(self)?,
+        })
+    }
+
+    /// Checks every strategy's parameters and column option, so mistakes
+    /// surface before a run rather than partway through (or as broken SQL
+    /// in the output). The error lists every problem by `table.column`.
+    /// [`lint_config`](crate::lint::lint_config) covers what is merely
+    /// suspicious.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        for (table_name, table) in &self.tables {
+            for (column, strategy) in &table.columns {
+                for problem in strategy_problems(strategy) {
+                    problems.push(format!("{}.{}: {}", table_name, column, problem));
+                }
+                if let Some(Err(e)) = column_pattern(column, false) {
+                    problems.push(format!("{}.{}: not a valid column pattern: {}", table_name, column, e));
+                }
+            }
+            for (column, options) in &table.options {
+                if options.max_len == Some(0) {
+                    problems.push(format!("{}.{}: `max_len` must be at least 1", table_name, column));
+                }
+                if let Some(pattern) = &options.must_match {
+                    if let Err(e) = Regex::new(pattern) {
+                        problems.push(format!(
+                            "{}.{}: `must_match` {:?} is not a valid regex: {}",
+                            table_name, column, pattern, e
+                        ));
+                    }
+                }
+            }
+        }
+        for (sql_type, strategy) in &self.type_rules {
+            for problem in strategy_problems(strategy) {
+                problems.push(format!("type_rules.{}: {}", sql_type, problem));
+            }
+        }
+        if let Err(e) = self.protected_patterns() {
+            problems.push(format!("{:#}", e));
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        problems.sort();
+        Err(anyhow!("Invalid configuration:\n  {}", problems.join("\n  ")).context(Failure::Config))
+    }
+
+    pub fn has_command_strategies(&self) -> bool {
+        self.tables
+            .values()
+            .flat_map(|t| t.columns.values())
+            .chain(self.type_rules.values())
+            .any(ColumnStrategy::contains_command)
+    }
+
+    /// Refuses a config that runs external programs, unless they were
+    /// allowed (`--allow-commands`).
+    pub fn ensure_commands_allowed(&self, allow_commands: bool) -> Result<()> {
+        if self.has_command_strategies() && !allow_commands {
+            return Err(anyhow!(
+                "Config uses `command` strategies, which run external programs. Re-run with --allow-commands to permit this."
+            )
+            .context(Failure::Config));
+        }
+        Ok(())
+    }
+
+    /// Whether any column asks for `unique` outputs, is tokenized or uses a
+    /// template with `{seq}`, which makes results depend on the order values
+    /// are seen in.
+    pub fn has_order_dependent_columns(&self) -> bool {
+        self.tables.values().any(|t| t.options.values().any(|o| o.unique))
+            || self
+                .tables
+                .values()
+                .flat_map(|t| t.columns.values())
+                .chain(self.type_rules.values())
+                .any(|s| match s {
+                    ColumnStrategy::Tokenize { .. } => true,
+                    ColumnStrategy::Template(t) => Template::parse(t).is_ok_and(|t| t.uses_seq()),
+                    _ => false,
+                })
+    }
+
+    /// Compiles `protected_columns` into anchored patterns, honoring
+    /// `case_insensitive_tables`.
+    pub fn protected_patterns(&self) -> Result<Vec<Regex>> {
+        let flags = if self.case_insensitive_tables { "(?i)" } else { "" };
+        self.protected_columns
+            .iter()
+            .map(|entry| {
+                Regex::new(&format!("{}^(?:{})$", flags, entry))
+                    .with_context(|| format!("Invalid protected_columns entry: {:?}", entry))
+                    .context(Failure::Config)
+            })
+            .collect()
+    }
+
+    /// Resolves a table name from the dump to its config entry, trying the
+    /// fully-qualified name first and then the unqualified table name.
+    pub fn find_table(&self, name: &str) -> Option<&TableConfig> {
+        self.lookup_table(name).or_else(|| {
+            name.split('.')
+                .next_back()
+                .filter(|short| *short != name)
+                .and_then(|short| self.lookup_table(short))
+        })
+    }
+
+    /// The table configured as `name`, or with `case_insensitive_tables`
+    /// the first key in sorted order that equals it ignoring case, so keys
+    /// that differ only by case resolve the same way on every run.
+    fn lookup_table(&self, name: &str) -> Option<&TableConfig> {
+        if let Some(table) = self.tables.get(name) {
+            return Some(table);
+        }
+        if self.case_insensitive_tables {
+            return self
+                .tables
+                .iter()
+                .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+                .min_by_key(|(key, _)| *key)
+                .map(|(_, table)| table);
+        }
+        None
+    }
+}
+
+impl TableConfig {
+    /// Looks up the strategy configured for a column: its exact entry, or
+    /// else the first pattern key, in sorted order, that matches its name.
+    pub fn column(&self, name: &str, case_insensitive: bool) -> Option<&ColumnStrategy> {
+        lookup_column(&self.columns, name, case_insensitive).or_else(|| {
+            self.column_patterns(case_insensitive)
+                .iter()
+                .find(|(_, pattern)| pattern.is_match(name))
+                .and_then(|(key, _)| self.columns.get(key))
+        })
+    }
+
+    /// The pattern keys of `columns` with their regexes, in the sorted order
+    /// `column` tries them. Keys that don't compile are left out.
+    pub fn column_patterns(&self, case_insensitive: bool) -> &[(String, Regex)] {
+        self.column_patterns[case_insensitive as usize].get_or_init(|| {
+            let mut patterns: Vec<(String, Regex)> = self
+                .columns
+                .keys()
+                .filter_map(|key| Some((key.clone(), column_pattern(key, case_insensitive)?.ok()?)))
+                .collect();
+            patterns.sort_by(|a, b| a.0.cmp(&b.0));
+            patterns
+        })
+    }
+
+    /// Looks up the output name configured for a column.
+    pub fn renamed_column(&self, name: &str, case_insensitive: bool) -> Option<&String> {
+        lookup_column(&self.rename_columns, name, case_insensitive)
+    }
+
+    /// Whether the table or any of its columns gets a new name.
+    pub fn renames_identifiers(&self) -> bool {
+        self.rename.is_some() || !self.rename_columns.is_empty()
+    }
+
+    /// Looks up the extra options configured for a column.
+    pub fn column_options(&self, name: &str, case_insensitive: bool) -> Option<&ColumnOptions> {
+        lookup_column(&self.options, name, case_insensitive)
+    }
+}
+
+/// Whether a `columns` key is a `/regex/` or `glob:pattern` rather than a
+/// column name.
+pub fn is_column_pattern(key: &str) -> bool {
+    column_pattern(key, false).is_some()
+}
+
+/// The regex for a `columns` key written `/regex/` or `glob:pattern` (`*`
+/// for any run of characters, `?` for one), anchored to the whole name, or
+/// `None` for a plain column name.
+fn column_pattern(key: &str, case_insensitive: bool) -> Option<Result<Regex, regex::Error>> {
+    let pattern = if let Some(glob) = key.strip_prefix("glob:") {
+        glob.chars()
+            .map(|c| match c {
+                '*' => ".*".to_string(),
+                '?' => ".".to_string(),
+                c => regex::escape(&c.to_string()),
+            })
+            .collect()
+    } else {
+        key.strip_prefix('/')?.strip_suffix('/').filter(|p| !p.is_empty())?.to_string()
+    };
+    let flags = if case_insensitive { "(?i)" } else { "" };
+    Some(Regex::new(&format!("{}^(?:{})$", flags, pattern)))
+}
+
+/// The entry for `name`, or when `case_insensitive` the first key in sorted
+/// order that equals it ignoring case (see [`AppConfig::lookup_table`]).
+fn lookup_column<'a, V>(
+    map: &'a HashMap<String, V>,
+    name: &str,
+    case_insensitive: bool,
+) -> Option<&'a V> {
+    if let Some(value) = map.get(name) {
+        return Some(value);
+    }
+    if case_insensitive {
+        return map
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .min_by_key(|(key, _)| *key)
+            .map(|(_, value)| value);
+    }
+    None
 }
@@ -0,0 +1,173 @@
+//! PostgreSQL `COPY ... FROM stdin` blocks, as `pg_dump` writes its data by
+//! default: a `COPY table (columns) FROM stdin;` header, then one line per
+//! row with the fields separated by tabs, up to a line holding only `\.`.
+//!
+//! Fields use `COPY`'s text format: `\N` is `NULL`, and backslash escapes
+//! stand for tabs, newlines and other control characters. Fields are handed
+//! to the strategies as SQL literals (numbers bare, everything else quoted)
+//! and written back in the same format.
+
+use crate::ident::split_column_list;
+use crate::sql::TABLE_NAME;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// The line that ends a block's data.
+pub const END_OF_DATA: &str = "\\.";
+
+/// The header of a `COPY ... FROM stdin` block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyHeader {
+    /// The table as written, quotes and schema included.
+    pub table: String,
+    /// The unquoted column names; empty if the header has no column list,
+    /// in which case the columns are those of the table's `CREATE TABLE`.
+    pub columns: Vec<String>,
+    /// Where the table name and the column list (inside its parentheses)
+    /// are in the statement.
+    pub table_range: Range<usize>,
+    pub columns_range: Option<Range<usize>>,
+}
+
+/// Parses a `COPY` statement (without its `;`) whose data follows inline.
+/// `COPY ... FROM` a file or program, and `COPY ... TO`, give `None`.
+pub fn parse_header(statement: &str) -> Option<CopyHeader> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    let regex = HEADER.get_or_init(|| {
+        Regex::new(&format!(r"(?is)^COPY\s+({})\s*(?:\((.*?)\))?\s*FROM\s+STDIN\b", TABLE_NAME))
+            .expect("Invalid regex pattern")
+    });
+    let caps = regex.captures(statement)?;
+    let table = caps.get(1)?;
+    let columns = caps.get(2);
+    Some(CopyHeader {
+        table: table.as_str().to_string(),
+        columns: columns.map(|m| split_column_list(m.as_str())).unwrap_or_default(),
+        table_range: table.range(),
+        columns_range: columns.map(|m| m.range()),
+    })
+}
+
+/// The fields of a data row, `None` for `\N`.
+pub fn split_row(line: &str) -> Vec<Option<String>> {
+    line.split('\t')
+        .map(|field| (field != "\\N").then(|| decode(field)))
+        .collect()
+}
+
+/// A field in `COPY`'s text format, `\N` for `None`.
+pub fn encode_field(field: Option<&str>) -> String {
+    match field {
+        Some(text) => encode(text),
+        None => "\\N".to_string(),
+    }
+}
+
+/// A field as a SQL literal: `NULL`, a bare number, or a quoted string.
+pub fn to_literal(field: Option<&str>) -> String {
+    match field {
+        None => "NULL".to_string(),
+        Some(text) if is_number(text) => text.to_string(),
+        Some(text) => format!("'{}'", text.replace('\'', "''")),
+    }
+}
+
+/// A strategy's output as a field: quotes are removed, and bare `NULL`
+/// becomes `\N`. Other unquoted outputs (numbers, `0x...` literals) are
+/// kept as they are.
+pub fn from_literal(value: &str) -> Option<String> {
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        Some(value[1..value.len() - 1].replace("''", "'"))
+    } else if value.eq_ignore_ascii_case("NULL") {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn is_number(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    !digits.is_empty()
+        && !digits.starts_with('.')
+        && !digits.ends_with('.')
+        && digits.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+        && digits.bytes().filter(|&b| b == b'.').count() <= 1
+}
+
+/// Resolves backslash escapes: `\b`, `\f`, `\n`, `\r`, `\t`, `\v`, octal
+/// `\ooo` and hex `\xhh` byte values; any other escaped character stands
+/// for itself.
+fn decode(field: &str) -> String {
+    if !field.contains('\\') {
+        return field.to_string();
+    }
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut chars = field.chars().peekable();
+    let push = |bytes: &mut Vec<u8>, c: char| {
+        let mut buf = [0u8; 4];
+        bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    };
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            push(&mut bytes, c);
+            continue;
+        }
+        let Some(escaped) = chars.next() else {
+            push(&mut bytes, '\\');
+            break;
+        };
+        match escaped {
+            'b' => bytes.push(0x08),
+            'f' => bytes.push(0x0c),
+            'n' => bytes.push(b'\n'),
+            'r' => bytes.push(b'\r'),
+            't' => bytes.push(b'\t'),
+            'v' => bytes.push(0x0b),
+            '0'..='7' => {
+                let mut value = escaped.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            value = value * 8 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            'x' if chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) => {
+                let mut value = 0;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(16)) {
+                        Some(digit) => {
+                            value = value * 16 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            other => push(&mut bytes, other),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Escapes what would end a field or row early: backslashes, tabs,
+/// newlines and carriage returns.
+fn encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,28 @@
+use chrono::{Duration, NaiveDate};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Matches `YYYY-MM-DD`, optionally followed by a `T` or space separated
+/// time (`HH:MM[:SS[.fff]]`) and a timezone (`Z`, `+HH`, `+HH:MM`, `+HHMM`).
+fn date_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^(\d{4}-\d{2}-\d{2})((?:[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?)?(?:\s*(?:Z|[+-]\d{2}(?::?\d{2})?))?)$",
+        )
+        .expect("Invalid regex pattern")
+    })
+}
+
+/// Shifts an ISO 8601 date or timestamp literal by whole days.
+///
+/// Only the calendar date moves: the time of day, fractional seconds and
+/// timezone offset are re-emitted exactly as written, so a `timestamptz`
+/// keeps its offset and the instant moves by `days * 24h`. Returns `None`
+/// for values that don't parse.
+pub fn shift_days(value: &str, days: i64) -> Option<String> {
+    let caps = date_regex().captures(value)?;
+    let date = NaiveDate::parse_from_str(&caps[1], "%Y-%m-%d").ok()?;
+    let shifted = date.checked_add_signed(Duration::try_days(days)?)?;
+    Some(format!("{}{}", shifted.format("%Y-%m-%d"), &caps[2]))
+}
@@ -0,0 +1,129 @@
+//! Anonymization of CSV/TSV exports: a header row naming the columns of one
+//! configured table, then one row per record.
+
+use crate::config::AppConfig;
+use crate::failure::Failure;
+use crate::processor::{new_report, RunOptions, StatementProcessor};
+use crate::report::RunReport;
+use crate::transformer::Transformer;
+use anyhow::{Context, Result};
+use log::warn;
+use std::io::{Read, Write};
+
+/// Anonymizes delimited data from `reader` into `writer` with the column
+/// rules of `table`. Cells are handed to the strategies as quoted SQL
+/// strings and unquoted again on the way out; empty cells stay empty.
+/// Records with the wrong number of fields are copied unchanged and
+/// counted as skipped.
+pub fn process_delimited<R: Read, W: Write>(
+    transformer: &Transformer,
+    reader: R,
+    writer: W,
+    config: &AppConfig,
+    options: &RunOptions,
+    table: &str,
+    delimiter: u8,
+) -> Result<RunReport> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(reader);
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(writer);
+    let mut report = new_report(options);
+
+    let columns: Vec<String> = reader
+        .headers()
+        .context("Failed to read the header row")
+        .context(Failure::Io)?
+        .iter()
+        .map(|c| c.trim().to_string())
+        .collect();
+    report.processed_lines += 1;
+
+    let processor = StatementProcessor::new(transformer, config, options)?;
+    let Some(table_config) = processor.table_config(table, None) else {
+        warn!(event = "unconfigured_table", table = table; "Table {} is not in the config; copying the file unchanged", table);
+        for column in &columns {
+            report.unconfigured_columns.insert(format!("{}.{}", table, column));
+        }
+        writer.write_record(reader.headers()?)?;
+        for record in reader.records() {
+            let record = record.context("Failed to read record").context(Failure::Io)?;
+            writer.write_record(&record)?;
+            report.processed_lines += 1;
+        }
+        writer.flush().context(Failure::Io)?;
+        return Ok(report);
+    };
+
+    let ci = config.case_insensitive_tables;
+    let header: Vec<&str> = columns
+        .iter()
+        .map(|c| table_config.renamed_column(c, ci).map_or(c.as_str(), |r| r.as_str()))
+        .collect();
+    writer.write_record(&header)?;
+
+    let rules = processor.resolve_rules(
+        table_config,
+        None,
+        table,
+        &columns,
+        &mut report.unconfigured_columns,
+    );
+    for record in reader.records() {
+        let record = record.context("Failed to read record").context(Failure::Io)?;
+        report.processed_lines += 1;
+        let line = record.position().map_or(0, |p| p.line());
+        if record.len() != columns.len() {
+            warn!(event = "skipped_statement", line = line, table = table; "Field count mismatch. Skipping line {}", line);
+            report.warn(format!("line {}: field count mismatch in {}", line, table));
+            report.skipped_statements += 1;
+            writer.write_record(&record)?;
+            continue;
+        }
+
+        let values: Vec<String> = record.iter().map(to_literal).collect();
+        let mut row_rules = rules.clone();
+        processor.apply_guards(&mut row_rules, &columns, &values);
+        processor.apply_quoted_null(&mut row_rules, &values, true);
+        for (rule, cell) in row_rules.iter_mut().zip(record.iter()) {
+            if cell.is_empty() {
+                rule.strategy = &crate::processor::KEEP;
+            }
+        }
+        let new_values = transformer.transform_row(
+            table,
+            &columns,
+            &values,
+            &row_rules,
+            table_config.seed,
+            table_config.key.as_deref(),
+        )?;
+        report.record_row(table, &columns, &row_rules, &values, &new_values);
+        report.record_statement(table);
+        let cells: Vec<String> = new_values.iter().map(|v| from_literal(v)).collect();
+        writer.write_record(&cells)?;
+    }
+    writer.flush().context("Failed to write output").context(Failure::Io)?;
+    Ok(report)
+}
+
+/// A cell as a quoted SQL string.
+fn to_literal(cell: &str) -> String {
+    format!("'{}'", cell.replace('\'', "''"))
+}
+
+/// A strategy's output as a cell: quotes are removed, `NULL` becomes empty
+/// and unquoted outputs (like `0x...` literals) are kept as they are.
+fn from_literal(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].replace("''", "'")
+    } else if value.eq_ignore_ascii_case("NULL") {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
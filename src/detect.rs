@@ -0,0 +1,110 @@
+//! Content detectors: which strategy a value calls for, judged by what it
+//! looks like rather than by its column's name. `scan` uses them on
+//! sampled values of columns whose names say nothing, `json_pii` on the
+//! string leaves of JSON documents and `scrub_text` inside free text.
+
+use crate::config::ColumnStrategy;
+use crate::json;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+struct Detector {
+    /// Matches a whole value.
+    whole: Regex,
+    /// Matches occurrences inside free text; `None` for detectors too loose
+    /// for prose (names).
+    within: Option<Regex>,
+    strategy: ColumnStrategy,
+}
+
+/// Tried in order; the first match wins.
+fn detectors() -> &'static [Detector] {
+    static DETECTORS: OnceLock<Vec<Detector>> = OnceLock::new();
+    DETECTORS.get_or_init(|| {
+        let detector = |pattern: &str, in_text: bool, strategy| Detector {
+            whole: Regex::new(&format!("^(?:{})$", pattern)).expect("Invalid regex pattern"),
+            within: in_text.then(|| Regex::new(pattern).expect("Invalid regex pattern")),
+            strategy,
+        };
+        vec![
+            detector(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}", true, ColumnStrategy::Email),
+            detector(r"\d{3}-\d{2}-\d{4}", true, ColumnStrategy::Ssn),
+            // North American numbers with separators, or any number written
+            // with a country code. Dates (`2024-01-15`) don't fit either.
+            detector(r"(?:\+1[ .-]?)?(?:\(\d{3}\) ?|\d{3}[ .-])\d{3}[ .-]\d{4}", true, ColumnStrategy::Phone),
+            detector(r"\+\d{1,3}[ .-]?\d[\d ().-]{5,}\d", true, ColumnStrategy::Phone),
+            // Two or three capitalized words. Title-case phrases such as
+            // place names match too.
+            detector(
+                r"\p{Lu}\p{Ll}+(?:[-'.]\p{Lu}\p{Ll}+)?(?: \p{Lu}\p{Ll}+(?:[-']\p{Lu}\p{Ll}+)?){1,2}",
+                false,
+                ColumnStrategy::FullName,
+            ),
+        ]
+    })
+}
+
+/// The strategy for a value that looks like an email address, SSN, phone
+/// number or person's name. `value` is the plain text, without SQL quotes.
+pub fn detect(value: &str) -> Option<&'static ColumnStrategy> {
+    let value = value.trim();
+    detectors()
+        .iter()
+        .find(|d| d.whole.is_match(value))
+        .map(|d| &d.strategy)
+}
+
+/// A strategy for a column whose sampled values (SQL literals, `NULL`s
+/// excluded) all look alike: the detected strategy if every sample gets the
+/// same one, or `json_pii` if every sample is a JSON document with at least
+/// one PII-looking string in it. `None` without samples or agreement.
+pub fn guess_from_samples(samples: &[String]) -> Option<ColumnStrategy> {
+    let texts: Vec<String> = samples
+        .iter()
+        .map(|s| match s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+            Some(body) => json::decode_literal(body).0,
+            None => s.clone(),
+        })
+        .collect();
+    let (first, rest) = texts.split_first()?;
+    if let Some(strategy) = detect(first) {
+        if rest.iter().all(|t| detect(t) == Some(strategy)) {
+            return Some(strategy.clone());
+        }
+    }
+    texts
+        .iter()
+        .all(|t| json::has_pii(t))
+        .then_some(ColumnStrategy::JsonPii)
+}
+
+/// The email addresses, SSNs and phone numbers inside free text, with the
+/// strategy for each, in order and without overlaps (where two overlap,
+/// the earlier detector wins). A match must not continue a word or number
+/// on either side.
+pub fn find_in_text(text: &str) -> Vec<(Range<usize>, &'static ColumnStrategy)> {
+    let standalone = |range: &Range<usize>| {
+        let before = text[..range.start].chars().next_back();
+        let after = text[range.end..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric() || matches!(c, '@' | '+' | '-' | '_' | '.'))
+            && !after.is_some_and(|c| c.is_alphanumeric() || matches!(c, '@' | '-' | '_'))
+    };
+    let mut found: Vec<(Range<usize>, &ColumnStrategy)> = Vec::new();
+    for detector in detectors() {
+        let Some(within) = &detector.within else {
+            continue;
+        };
+        for m in within.find_iter(text) {
+            let range = m.range();
+            let overlaps = found
+                .iter()
+                .any(|(r, _)| r.start < range.end && range.start < r.end);
+            if standalone(&range) && !overlaps {
+                found.push((range, &detector.strategy));
+            }
+        }
+    }
+    found.sort_by_key(|(r, _)| r.start);
+    found
+}
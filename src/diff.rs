@@ -0,0 +1,69 @@
+//! Unified diff of the statements a run changed, for reviewing what was
+//! anonymized without comparing whole dumps.
+
+use crate::sql::Chunk;
+use std::io::{self, Write};
+
+/// Writes `diff -U0`-style hunks: one per rewritten statement, with no
+/// context lines. Unchanged statements only advance the line counters.
+pub struct UnifiedDiff<W> {
+    out: W,
+    /// Lines written to the anonymized file so far.
+    new_lines: usize,
+}
+
+impl<W: Write> UnifiedDiff<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, new_lines: 0 }
+    }
+
+    /// Starts the section for one input/output pair. Several files can be
+    /// written to the same diff.
+    pub fn begin_file(&mut self, old_label: &str, new_label: &str) -> io::Result<()> {
+        self.new_lines = 0;
+        writeln!(self.out, "--- {}", old_label)?;
+        writeln!(self.out, "+++ {}", new_label)
+    }
+
+    /// Records that `chunk` was written out as `output` (its lines, each
+    /// terminated by `\n`), emitting a hunk if the two differ.
+    pub fn chunk(&mut self, chunk: &Chunk, output: &[u8]) -> io::Result<()> {
+        let new_start = self.new_lines + 1;
+        let new_text = String::from_utf8_lossy(output);
+        let new: Vec<&str> = new_text.lines().collect();
+        self.new_lines += new.len();
+
+        let changed = new_text.strip_suffix('\n') != Some(chunk.text.as_str());
+        if !changed {
+            return Ok(());
+        }
+        let old: Vec<&str> = chunk.text.split('\n').collect();
+        writeln!(
+            self.out,
+            "@@ -{} +{} @@",
+            range(chunk.first_line, old.len()),
+            range(new_start, new.len())
+        )?;
+        for line in old {
+            writeln!(self.out, "-{}", line)?;
+        }
+        for line in new {
+            writeln!(self.out, "+{}", line)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// A hunk range: `start` alone for one line, `start,count` otherwise. An
+/// empty range names the line before it, as `diff` does.
+fn range(start: usize, count: usize) -> String {
+    match count {
+        1 => start.to_string(),
+        0 => format!("{},0", start - 1),
+        _ => format!("{},{}", start, count),
+    }
+}
@@ -0,0 +1,63 @@
+//! Failure categories that map to distinct process exit codes, so scripts
+//! and CI can tell a bad config from a missing file from an incomplete run.
+
+use std::fmt;
+
+/// Attached to an error with `anyhow::Context::context`, where it decides
+/// the exit code. Errors without a category exit with
+/// [`GENERIC_EXIT_CODE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// The config file is missing, unparseable or invalid.
+    Config,
+    /// An input could not be read or an output could not be written.
+    Io,
+    /// A check the run was asked to enforce failed (`--fail-on-unconfigured`,
+    /// `lint-config`).
+    Validation,
+    /// The run finished, but some statements of configured tables could
+    /// not be parsed and were copied through unchanged.
+    Incomplete,
+    /// The state kept by stateful strategies outgrew `--max-memory`.
+    MemoryLimit,
+}
+
+/// Exit code for errors that carry no [`Failure`] category.
+pub const GENERIC_EXIT_CODE: u8 = 1;
+
+impl Failure {
+    /// `2` is left to clap's usage errors.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Failure::Config => 3,
+            Failure::Io => 4,
+            Failure::Validation => 5,
+            Failure::Incomplete => 6,
+            Failure::MemoryLimit => 7,
+        }
+    }
+
+    /// The category attached to `error`, if any.
+    pub fn of(error: &anyhow::Error) -> Option<Failure> {
+        error.downcast_ref::<Failure>().copied()
+    }
+
+    /// The exit code for `error`: that of its category, if it has one.
+    pub fn exit_code_of(error: &anyhow::Error) -> u8 {
+        Failure::of(error).map_or(GENERIC_EXIT_CODE, Failure::exit_code)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Failure::Config => "configuration error",
+            Failure::Io => "input/output error",
+            Failure::Validation => "validation failed",
+            Failure::Incomplete => "run incomplete",
+            Failure::MemoryLimit => "memory limit exceeded",
+        })
+    }
+}
+
+impl std::error::Error for Failure {}
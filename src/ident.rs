@@ -0,0 +1,104 @@
+//! Quoted SQL identifiers: `"col"` (standard SQL, PostgreSQL, SQLite,
+//! Oracle), `` `col` `` (MySQL) and `[col]` (SQL Server, also accepted by
+//! SQLite). In identifier position the three styles can't be mistaken for
+//! each other or for string literals, so the same helpers serve every
+//! dialect, and renamed identifiers keep the quotes they were written with.
+
+use std::borrow::Cow;
+
+/// The closing quote for an identifier opened with `open`, if `open` is
+/// an identifier quote.
+fn closing(open: char) -> Option<char> {
+    match open {
+        '"' => Some('"'),
+        '`' => Some('`'),
+        '[' => Some(']'),
+        _ => None,
+    }
+}
+
+/// The name an identifier stands for: quotes removed and doubled closing
+/// quotes inside (`"a""b"`, `` `a``b` ``, `[a]]b]`) undone. Bare names are
+/// returned as written, trimmed of whitespace.
+pub fn unquote_identifier(token: &str) -> Cow<'_, str> {
+    let token = token.trim();
+    let mut chars = token.chars();
+    let (Some(open), Some(last)) = (chars.next(), chars.next_back()) else {
+        return Cow::Borrowed(token);
+    };
+    match closing(open) {
+        Some(close) if last == close => {
+            let inner = &token[open.len_utf8()..token.len() - close.len_utf8()];
+            let doubled = format!("{}{}", close, close);
+            if inner.contains(&doubled) {
+                Cow::Owned(inner.replace(&doubled, &close.to_string()))
+            } else {
+                Cow::Borrowed(inner)
+            }
+        }
+        _ => Cow::Borrowed(token),
+    }
+}
+
+/// A possibly schema-qualified name with each dot-separated segment
+/// unquoted: `"main"."users"` -> `main.users`. Dots inside quotes don't
+/// separate segments.
+pub fn unquote_qualified(token: &str) -> String {
+    split_outside_quotes(token, '.')
+        .into_iter()
+        .map(unquote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The unquoted names of an `INSERT` column list (the text between its
+/// parentheses). Commas inside quoted names don't separate them.
+pub fn split_column_list(cols_part: &str) -> Vec<String> {
+    split_outside_quotes(cols_part, ',')
+        .into_iter()
+        .map(|name| unquote_identifier(name).into_owned())
+        .collect()
+}
+
+/// Wraps `name` in the same quotes as `token` (backticks, double quotes or
+/// brackets), or leaves it bare if `token` was bare.
+pub fn requote(token: &str, name: &str) -> String {
+    match token.trim_start().chars().next() {
+        Some(open @ ('`' | '"' | '[')) => quote_with(open, name),
+        _ => name.to_string(),
+    }
+}
+
+/// `name` quoted with `open` and its closing counterpart, doubling any
+/// closing quote inside.
+fn quote_with(open: char, name: &str) -> String {
+    let close = closing(open).unwrap_or(open);
+    let escaped = name.replace(close, &format!("{}{}", close, close));
+    format!("{}{}{}", open, escaped, close)
+}
+
+/// Splits `text` at each `separator` outside identifier quotes and string
+/// literals.
+pub(crate) fn split_outside_quotes(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut close: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match close {
+            // A doubled closing quote is part of the name.
+            Some(q) if c == q && chars.peek().is_some_and(|&(_, next)| next == q) => {
+                chars.next();
+            }
+            Some(q) if c == q => close = None,
+            Some(_) => {}
+            None if c == separator => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            None => close = closing(c).or((c == '\'').then_some('\'')),
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
@@ -0,0 +1,138 @@
+//! JSON documents stored in SQL string literals, for `json_pii`: decoding
+//! the literal, finding the string leaves of the document and splicing
+//! fakes back in without disturbing anything else (key order, whitespace,
+//! numbers and escapes of untouched strings stay as they were).
+
+use crate::config::ColumnStrategy;
+use crate::detect::detect;
+use anyhow::Result;
+use serde::de::IgnoredAny;
+use std::ops::Range;
+
+/// How the body of a quoted SQL literal escapes its contents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Escaping {
+    /// Only `'` is escaped, as `''` (standard SQL, PostgreSQL, SQLite).
+    Standard,
+    /// Backslash escapes as written by `mysqldump`: `\'`, `\"`, `\\`,
+    /// `\n`, `\r`, `\0` and `\Z`.
+    Backslash,
+}
+
+/// The text of a SQL literal's body (without its quotes) and the escaping
+/// it uses. A body is taken to be backslash-escaped when its first `"` is
+/// (a JSON document's quotes all are under `mysqldump`), or, without
+/// double quotes, when it contains `\'` or `\\`.
+pub fn decode_literal(body: &str) -> (String, Escaping) {
+    let backslash = match body.find('"') {
+        Some(i) => body[..i].ends_with('\\'),
+        None => body.contains("\\'") || body.contains("\\\\"),
+    };
+    if !backslash {
+        return (body.replace("''", "'"), Escaping::Standard);
+    }
+    let mut text = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => text.push('\n'),
+                Some('r') => text.push('\r'),
+                Some('t') => text.push('\t'),
+                Some('0') => text.push('\0'),
+                Some('Z') => text.push('\x1a'),
+                Some(other) => text.push(other),
+                None => text.push('\\'),
+            },
+            '\'' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                text.push('\'');
+            }
+            c => text.push(c),
+        }
+    }
+    (text, Escaping::Backslash)
+}
+
+/// The inverse of [`decode_literal`]: `text` escaped for the body of a
+/// quoted SQL literal.
+pub fn encode_literal(text: &str, escaping: Escaping) -> String {
+    if escaping == Escaping::Standard {
+        return text.replace('\'', "''");
+    }
+    let mut body = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => body.push_str("\\\\"),
+            '\'' => body.push_str("\\'"),
+            '"' => body.push_str("\\\""),
+            '\n' => body.push_str("\\n"),
+            '\r' => body.push_str("\\r"),
+            '\0' => body.push_str("\\0"),
+            '\x1a' => body.push_str("\\Z"),
+            c => body.push(c),
+        }
+    }
+    body
+}
+
+/// Replaces every string leaf of `document` that looks like PII (see
+/// [`detect`]) with `fake(leaf, strategy)`. Object keys are never touched.
+/// `None` if `document` isn't valid JSON.
+pub fn refake(
+    document: &str,
+    mut fake: impl FnMut(&str, &ColumnStrategy) -> Result<String>,
+) -> Result<Option<String>> {
+    if serde_json::from_str::<IgnoredAny>(document).is_err() {
+        return Ok(None);
+    }
+    let mut out = String::with_capacity(document.len());
+    let mut copied = 0;
+    for range in leaves(document) {
+        let leaf: String = serde_json::from_str(&document[range.clone()])?;
+        let Some(strategy) = detect(&leaf) else {
+            continue;
+        };
+        out.push_str(&document[copied..range.start]);
+        out.push_str(&serde_json::to_string(&fake(&leaf, strategy)?)?);
+        copied = range.end;
+    }
+    out.push_str(&document[copied..]);
+    Ok(Some(out))
+}
+
+/// Whether `text` is a JSON object or array with a PII-looking string leaf.
+pub fn has_pii(text: &str) -> bool {
+    let text = text.trim();
+    (text.starts_with('{') || text.starts_with('['))
+        && serde_json::from_str::<IgnoredAny>(text).is_ok()
+        && leaves(text).into_iter().any(|range| {
+            serde_json::from_str::<String>(&text[range]).is_ok_and(|leaf| detect(&leaf).is_some())
+        })
+}
+
+/// Byte ranges (quotes included) of the strings in valid JSON `document`
+/// that are values rather than object keys.
+fn leaves(document: &str) -> Vec<Range<usize>> {
+    let bytes = document.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'"' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i] != b'"' {
+            i += if bytes[i] == b'\\' { 2 } else { 1 };
+        }
+        i += 1;
+        let end = i.min(bytes.len());
+        let next = bytes[end..].iter().find(|b| !b.is_ascii_whitespace());
+        if next != Some(&b':') {
+            ranges.push(start..end);
+        }
+    }
+    ranges
+}
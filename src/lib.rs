@@ -0,0 +1,39 @@
+//! GhostDB: streaming anonymization of SQL database dumps.
+//!
+//! The binary is a thin CLI over this library; the modules are public so
+//! the processing pipeline can be driven from tests and benchmarks.
+
+pub mod baseline;
+pub mod binary;
+pub mod cache;
+pub mod catalog;
+pub mod checksum;
+pub mod command;
+pub mod config;
+pub mod copy;
+pub mod dates;
+pub mod delimited;
+pub mod detect;
+pub mod diff;
+pub mod failure;
+pub mod ident;
+pub mod json;
+pub mod lint;
+pub mod logging;
+pub mod names;
+pub mod pgarray;
+pub mod predicate;
+pub mod preview;
+pub mod processor;
+pub mod pseudonym;
+pub mod rename;
+pub mod report;
+pub mod resume;
+pub mod rotation;
+pub mod scanner;
+pub mod schema;
+pub mod sql;
+pub mod ssn;
+pub mod template;
+pub mod transformer;
+pub mod vin;
@@ -0,0 +1,347 @@
+use crate::config::{is_column_pattern, AppConfig, ColumnOptions, ColumnStrategy, OnError, TableConfig};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A config entry that is dead, contradictory or unlikely to do what the
+/// author meant. `column` is `None` for table-level problems.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LintIssue {
+    pub table: String,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "{}.{}: {}", self.table, column, self.message),
+            None => write!(f, "{}: {}", self.table, self.message),
+        }
+    }
+}
+
+/// Checks a config for rules that can never fire, rules that conflict with
+/// each other, and strategies that don't fit the column they're mapped to.
+/// Issues are sorted by table and column.
+pub fn lint_config(config: &AppConfig) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+
+    if config.case_insensitive_tables {
+        for (a, b) in case_collisions(config.tables.keys()) {
+            issues.push(LintIssue {
+                table: a.to_string(),
+                column: None,
+                message: format!(
+                    "differs from table `{}` only by case; with case_insensitive_tables, names matching neither exactly use `{}`",
+                    b, b
+                ),
+            });
+        }
+    }
+
+    for (table_name, table) in &config.tables {
+        lint_table(config, table_name, table, &mut issues);
+    }
+
+    issues.sort();
+    issues
+}
+
+fn lint_table(
+    config: &AppConfig,
+    table_name: &str,
+    table: &TableConfig,
+    issues: &mut Vec<LintIssue>,
+) {
+    let mut push = |column: &str, message: String| {
+        issues.push(LintIssue {
+            table: table_name.to_string(),
+            column: Some(column.to_string()),
+            message,
+        })
+    };
+
+    if config.case_insensitive_tables {
+        for (a, b) in case_collisions(table.columns.keys()) {
+            push(
+                a,
+                format!("differs from column `{}` only by case; names matching neither exactly use `{}`", b, b),
+            );
+        }
+    }
+
+    for column in table.options.keys() {
+        if table.column(column, config.case_insensitive_tables).is_none() {
+            push(column, "has options but no strategy, so the options are never used".to_string());
+        }
+    }
+
+    let patterns = table.column_patterns(config.case_insensitive_tables);
+    for (i, (key, _)) in patterns.iter().enumerate() {
+        if let Some(message) = shadowed_pattern(table, key, &patterns[..i], config.case_insensitive_tables) {
+            push(key, message);
+        }
+    }
+
+    for (column, strategy) in &table.columns {
+        if let Some(options) = table.column_options(column, config.case_insensitive_tables) {
+            for message in lint_options(strategy, options) {
+                push(column, message);
+            }
+        }
+        // A pattern key says nothing about the names it will meet.
+        let name = (!is_column_pattern(column)).then_some(column.as_str());
+        for message in lint_strategy(name, strategy) {
+            push(column, message);
+        }
+        if matches!(strategy, ColumnStrategy::CompanyEmail { .. })
+            && !table.columns.values().any(is_name_strategy)
+        {
+            push(
+                column,
+                "`company_email` in a table without name columns; every address gets a random local part"
+                    .to_string(),
+            );
+        }
+    }
+}
+
+/// Whether `strategy` writes fake names, the only strategies `max_len`
+/// applies to.
+pub fn is_name_strategy(strategy: &ColumnStrategy) -> bool {
+    matches!(
+        strategy,
+        ColumnStrategy::FirstName
+            | ColumnStrategy::LastName
+            | ColumnStrategy::FullName
+            | ColumnStrategy::GenderedFirstName { .. }
+    )
+}
+
+fn lint_options(strategy: &ColumnStrategy, options: &ColumnOptions) -> Vec<String> {
+    let mut messages = Vec::new();
+    if options.max_len.is_some() && !is_name_strategy(strategy) {
+        messages.push("`max_len` only applies to name strategies and is ignored here".to_string());
+    }
+    if options.when.is_some() && matches!(strategy, ColumnStrategy::Keep) {
+        messages.push("`when` has no effect on `keep`".to_string());
+    }
+    if options.must_match.is_some() {
+        match strategy {
+            ColumnStrategy::Keep => messages.push("`must_match` has no effect on `keep`".to_string()),
+            ColumnStrategy::Tokenize { .. } | ColumnStrategy::CompanyEmail { .. } => {
+                messages.push("`must_match` is not checked for this strategy".to_string())
+            }
+            strategy if !strategy.is_seeded() => messages.push(
+                "`must_match` can't retry a strategy that ignores the seed; a value that doesn't match fails the run"
+                    .to_string(),
+            ),
+            _ => {}
+        }
+    }
+    if options.domain_from.is_some() && matches!(strategy, ColumnStrategy::Keep) {
+        messages.push("`domain_from` has no effect on `keep`".to_string());
+    }
+    if options.seed_from.is_some()
+        && (!strategy.is_seeded()
+            || matches!(
+                strategy,
+                ColumnStrategy::GeoFuzz { .. } | ColumnStrategy::CompanyEmail { .. } | ColumnStrategy::Pseudonym { .. }
+            ))
+    {
+        messages.push("`seed_from` has no effect on this strategy".to_string());
+    }
+    if options.unique {
+        match strategy {
+            ColumnStrategy::Keep | ColumnStrategy::RandomNull { .. } => messages.push(
+                "`unique` has no effect on a strategy that keeps the original values".to_string(),
+            ),
+            ColumnStrategy::Fixed(_) => messages.push(
+                "`unique` cannot be satisfied by `fixed`; the run fails on the second distinct value"
+                    .to_string(),
+            ),
+            ColumnStrategy::Pseudonym { .. } => messages.push(
+                "`unique` has no effect on `pseudonym`, which must agree across its namespace; integers stay distinct anyway"
+                    .to_string(),
+            ),
+            _ => {}
+        }
+    }
+    messages
+}
+
+/// Checks a strategy against its column's name, or only against itself for
+/// a pattern key (`column` is `None`), whose names aren't known.
+fn lint_strategy(column: Option<&str>, strategy: &ColumnStrategy) -> Vec<String> {
+    let lower = column.map(str::to_lowercase);
+    let looks_like_key = lower.as_deref().is_some_and(|l| l == "id" || l.ends_with("_id"));
+    let looks_like_date = lower.as_deref().is_none_or(|l| {
+        l.contains("date") || l.contains("time") || l.ends_with("_at") || l.contains("birth") || l == "dob"
+    });
+    let looks_like_coordinate = lower.as_deref().is_none_or(|l| ["lat", "lon", "lng"].iter().any(|s| l.contains(s)));
+
+    let mut messages = Vec::new();
+    let fakes_value = !matches!(
+        strategy,
+        ColumnStrategy::Keep
+            | ColumnStrategy::RandomNull { .. }
+            | ColumnStrategy::Command { .. }
+            | ColumnStrategy::Pseudonym { .. }
+    );
+    if looks_like_key && fakes_value {
+        messages.push(
+            "looks like a key column; replacing it breaks joins to other tables (`pseudonym` keeps them)".to_string(),
+        );
+    }
+
+    match strategy {
+        ColumnStrategy::ArrayOf(inner) if matches!(**inner, ColumnStrategy::ArrayOf(_)) => {
+            messages.push(
+                "nested `array_of` is redundant; multi-dimensional arrays are already walked"
+                    .to_string(),
+            );
+        }
+        ColumnStrategy::RandomNull { probability } if *probability == 0.0 => {
+            messages.push("`probability` is 0, so no value is ever nulled".to_string());
+        }
+        ColumnStrategy::RandomInt { min, max } if min == max => {
+            messages.push(format!("`min` equals `max`, so every value becomes {}; `fixed` says so plainly", min));
+        }
+        ColumnStrategy::DateShift { max_days, on_error } => {
+            if *max_days == 0 {
+                messages.push("`max_days` is 0, so dates are never shifted".to_string());
+            }
+            if !looks_like_date && *on_error == OnError::Keep {
+                messages.push(
+                    "`date_shift` on a column that doesn't look like a date; unparseable values pass through unchanged"
+                        .to_string(),
+                );
+            }
+        }
+        ColumnStrategy::GeoFuzz { radius_m, axis, .. } => {
+            if *radius_m <= 0.0 {
+                messages.push("`radius_m` is not positive, so coordinates never move".to_string());
+            }
+            if axis.is_none() && !looks_like_coordinate {
+                messages.push(
+                    "`geo_fuzz` can't tell lat from lon by this name and will assume latitude; set `axis`"
+                        .to_string(),
+                );
+            }
+        }
+        ColumnStrategy::PrefixedHash { len, .. } if *len < 8 => {
+            messages.push(format!(
+                "`prefixed_hash` `len` {} leaves only {} bits; distinct values are likely to collide past ~{} of them (set `unique: true` to rule it out)",
+                len,
+                len * 4,
+                1u64 << (len * 2)
+            ));
+        }
+        ColumnStrategy::CompanyEmail { format, .. }
+            if !["{first}", "{last}", "{f}", "{l}"].iter().any(|p| format.contains(p)) =>
+        {
+            messages.push("`company_email` `format` has no name placeholder, so every row gets the same address".to_string());
+        }
+        _ => {}
+    }
+    messages
+}
+
+/// Why the pattern `key` can never apply, if every column it can match has
+/// an exact key or goes to one of the `earlier` patterns first. Only
+/// patterns whose matches can be worked out are checked: a glob without
+/// wildcards, literal alternatives like `/email|mail/`, and globs covered by
+/// an earlier glob.
+fn shadowed_pattern(
+    table: &TableConfig,
+    key: &str,
+    earlier: &[(String, Regex)],
+    case_insensitive: bool,
+) -> Option<String> {
+    if let Some(names) = literal_names(key) {
+        let mut exact = false;
+        let mut shadows: Vec<String> = Vec::new();
+        for name in &names {
+            let has_entry = table.columns.keys().any(|k| {
+                (k == name || (case_insensitive && k.eq_ignore_ascii_case(name))) && !is_column_pattern(k)
+            });
+            if has_entry {
+                exact = true;
+                continue;
+            }
+            let (shadow, _) = earlier.iter().find(|(_, pattern)| pattern.is_match(name))?;
+            let shadow = format!("`{}`", shadow);
+            if !shadows.contains(&shadow) {
+                shadows.push(shadow);
+            }
+        }
+        let shadows = shadows.join(", ");
+        return Some(if shadows.is_empty() {
+            "every column this pattern matches has its own entry, so it never applies".to_string()
+        } else if exact {
+            format!(
+                "every column this pattern matches has its own entry or goes to {} first, so it never applies",
+                shadows
+            )
+        } else {
+            format!("every column this pattern matches goes to {} first, so it never applies", shadows)
+        });
+    }
+
+    let glob: Vec<char> = key.strip_prefix("glob:")?.chars().collect();
+    earlier.iter().find_map(|(shadow, _)| {
+        let outer: Vec<char> = shadow.strip_prefix("glob:")?.chars().collect();
+        glob_covers(&outer, &glob, case_insensitive).then(|| {
+            format!("`{}` is tried first and matches every column this pattern does, so it never applies", shadow)
+        })
+    })
+}
+
+/// The names a pattern key spells out, if it can match nothing else: a glob
+/// without wildcards or a regex made only of literal alternatives.
+fn literal_names(key: &str) -> Option<Vec<String>> {
+    if let Some(glob) = key.strip_prefix("glob:") {
+        return (!glob.contains(['*', '?'])).then(|| vec![glob.to_string()]);
+    }
+    let regex = key.strip_prefix('/')?.strip_suffix('/')?;
+    regex
+        .split('|')
+        .map(|alt| (!alt.is_empty() && regex::escape(alt) == alt).then(|| alt.to_string()))
+        .collect()
+}
+
+/// Whether the glob `outer` matches every name the glob `inner` does.
+fn glob_covers(outer: &[char], inner: &[char], case_insensitive: bool) -> bool {
+    match (outer.first(), inner.first()) {
+        (None, _) => inner.is_empty(),
+        (Some('*'), _) => {
+            glob_covers(&outer[1..], inner, case_insensitive)
+                || (!inner.is_empty() && glob_covers(outer, &inner[1..], case_insensitive))
+        }
+        (Some(_), None) | (Some(_), Some('*')) => false,
+        (Some('?'), Some(_)) => glob_covers(&outer[1..], &inner[1..], case_insensitive),
+        (Some(a), Some(b)) => {
+            (a == b || (case_insensitive && a.eq_ignore_ascii_case(b)))
+                && glob_covers(&outer[1..], &inner[1..], case_insensitive)
+        }
+    }
+}
+
+/// Pairs of names that are distinct but equal ignoring ASCII case, each
+/// with the first of its group in sorted order, which lookups that match
+/// neither exactly resolve to.
+fn case_collisions<'a>(names: impl Iterator<Item = &'a String>) -> Vec<(&'a str, &'a str)> {
+    let mut seen: HashMap<String, &'a str> = HashMap::new();
+    let mut names: Vec<&String> = names.collect();
+    names.sort();
+    let mut collisions = Vec::new();
+    for name in names {
+        if let Some(first) = seen.get(&name.to_lowercase()) {
+            collisions.push((name.as_str(), *first));
+        } else {
+            seen.insert(name.to_lowercase(), name);
+        }
+    }
+    collisions
+}
@@ -0,0 +1,51 @@
+//! `--log-format json`: log records as one JSON object per line, for
+//! feeding scheduled runs into log pipelines. Structured fields attached to
+//! a log call (`event`, `line`, `table`, counts) become keys of the object.
+
+use log::kv::{self, Key, VisitSource};
+use log::Record;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+/// Installs an `env_logger` (filtered by `RUST_LOG` as usual) that writes
+/// every record as a JSON line to stderr.
+pub fn init_json() {
+    env_logger::Builder::from_default_env()
+        .format(|buf, record| {
+            let mut object = record_json(record);
+            object.insert("timestamp".to_string(), buf.timestamp_millis().to_string().into());
+            writeln!(buf, "{}", Value::Object(object))
+        })
+        .init();
+}
+
+/// The fields of `record`: `level`, `target`, `message` and its key-value
+/// pairs, with numbers and booleans kept as JSON numbers and booleans.
+pub fn record_json(record: &Record) -> Map<String, Value> {
+    let mut object = Map::new();
+    object.insert("level".to_string(), record.level().as_str().into());
+    object.insert("target".to_string(), record.target().into());
+    object.insert("message".to_string(), record.args().to_string().into());
+    let _ = record.key_values().visit(&mut Fields(&mut object));
+    object
+}
+
+struct Fields<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let json = if let Some(n) = value.to_u64() {
+            n.into()
+        } else if let Some(n) = value.to_i64() {
+            n.into()
+        } else if let Some(b) = value.to_bool() {
+            b.into()
+        } else if let Some(f) = value.to_f64() {
+            f.into()
+        } else {
+            value.to_string().into()
+        };
+        self.0.insert(key.as_str().to_string(), json);
+        Ok(())
+    }
+}
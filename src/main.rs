@@ -1,17 +1,42 @@
-mod config;
-mod transformer;
-
 use anyhow::{anyhow, Context, Result};
 use clap::{Parser, Subcommand};
-use config::{AppConfig, ColumnStrategy, TableConfig};
-use dialoguer::{theme::ColorfulTheme, Input, Select};
-use log::{debug, info, warn};
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use console::style;
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+use dialoguer::{Confirm, Input, Select};
+use ghostdb::catalog::{self, StrategyInfo};
+use ghostdb::config::{AppConfig, ColumnOptions, ColumnStrategy, ConfigFormat, TableConfig, MAX_HASH_LEN};
+use ghostdb::failure::Failure;
+use ghostdb::lint::{is_name_strategy, lint_config};
+use ghostdb::processor::{
+    default_threads_io, is_stdio, run_batch, run_processing, run_stream, InputFormat, RunOptions, SqlDialect, KEPT_SAMPLE_TAG, STDIO,
+};
+use ghostdb::preview::dry_run;
+use ghostdb::report::RunReport;
+use ghostdb::rotation;
+use ghostdb::scanner::{coverage, filter_scan, merge_scan, scan_file_with_samples, Coverage, Samples};
+use ghostdb::template::Template;
+use ghostdb::transformer::Transformer;
+use log::info;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use transformer::Transformer;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by `--quiet`; silences `status!` messages.
+static QUIET: AtomicBool = AtomicBool::new(false);
+/// Set by `--no-color` or `NO_COLOR`; selects the plain prompt theme.
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Prints a human-facing status line to stderr unless `--quiet` is set, so
+/// stdout only ever carries machine-readable output.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        }
+    };
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -24,27 +49,241 @@ struct Cli {
 
     #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// Print the proposed plan as JSON instead of the human-readable listing
+    #[arg(long, default_value_t = false)]
+    plan_json: bool,
+
+    /// Suppress informational messages (errors are still reported)
+    #[arg(short, long, global = true, default_value_t = false)]
+    quiet: bool,
+
+    /// Disable colored output and styled prompts (also enabled by `NO_COLOR`)
+    #[arg(long, global = true, default_value_t = false)]
+    no_color: bool,
+
+    /// Log format; `json` writes one JSON object per line for log pipelines
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Human)]
+    log_format: LogFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum LogFormat {
+    Human,
+    Json,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    Run(RunArgs),
+    Run(Box<RunArgs>),
     Scan(ScanArgs),
+    /// Report unreachable, conflicting or ill-fitting rules in a config
+    LintConfig(LintArgs),
+    /// List the available column strategies with an example of each
+    ListStrategies,
+    /// Compare a dump's pseudonyms under an old and a new seed
+    RotateSeed(RotateSeedArgs),
+    /// Anonymize SQL from stdin to stdout as it arrives, for live pipelines
+    Stream(StreamArgs),
 }
 
 #[derive(clap::Args, Debug)]
 struct RunArgs {
+    /// Input dump(s); repeat the flag or pass a glob like `dumps/*.sql`. `-` (the default) reads stdin
+    #[arg(short, long, num_args = 1.., default_value = STDIO)]
+    input: Vec<PathBuf>,
+
+    /// Output file, or a directory when several inputs are given. `-` (the default) writes to stdout
+    #[arg(short, long, default_value = STDIO)]
+    output: PathBuf,
+
     #[arg(short, long)]
-    input: PathBuf,
+    config: PathBuf,
+
+    #[arg(short, long, default_value_t = 42)]
+    seed: u64,
+
+    /// Fail if the dump contains tables or columns missing from the config
+    #[arg(long, default_value_t = false)]
+    fail_on_unconfigured: bool,
+
+    /// Print SHA-256 checksums of the input and output files
+    #[arg(long, default_value_t = false)]
+    checksum: bool,
+
+    /// Also write the checksums to a `<output>.sha256` sidecar (implies --checksum)
+    #[arg(long, default_value_t = false)]
+    checksum_sidecar: bool,
+
+    /// Splice new values into the original statement text instead of rebuilding it
+    #[arg(long, default_value_t = false)]
+    preserve_formatting: bool,
+
+    /// Like --preserve-formatting, but also keep unchanged values exactly as written
+    #[arg(long, default_value_t = false)]
+    no_header_rebuild: bool,
+
+    /// Write a JSON summary of tables, columns, strategies and warnings
+    #[arg(long, visible_alias = "report", value_name = "PATH")]
+    summary_json: Option<PathBuf>,
+
+    /// Read, transform and write on separate threads, with N transform workers (default: one per CPU; 1 runs serially)
+    #[arg(long, visible_alias = "threads", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    threads_io: Option<u64>,
+
+    /// Write a unified diff of the changed statements to this file
+    #[arg(long, value_name = "PATH")]
+    diff: Option<PathBuf>,
+
+    /// Input format; `csv` and `tsv` read a header row and anonymize one table
+    #[arg(long, value_enum, default_value_t = Format::Sql)]
+    format: Format,
+
+    /// Config table for CSV/TSV input (default: the input file's name without extension)
+    #[arg(long, value_name = "NAME")]
+    table: Option<String>,
+
+    /// Remember fake values for this many recent originals; speeds up low-cardinality columns
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    cache_size: usize,
+
+    /// Abort once `unique`/`tokenize` state would exceed this size (e.g. 512M, 2G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_memory: Option<usize>,
+
+    /// Allow `command` strategies to execute external programs named in the config
+    #[arg(long, default_value_t = false)]
+    allow_commands: bool,
+
+    /// DEBUG ONLY: leave this fraction (0.0 - 1.0) of rows with their original values
+    #[arg(long, value_name = "FRACTION", value_parser = parse_fraction)]
+    unsafe_keep_sample: Option<f64>,
+
+    /// Continue an interrupted run from its last checkpoint, appending to the existing output
+    #[arg(long, default_value_t = false)]
+    resume: bool,
+
+    /// Also anonymize values in UPDATE/DELETE WHERE clauses (changes which rows they match)
+    #[arg(long, default_value_t = false)]
+    scrub_where: bool,
 
+    /// Database that wrote the dump; `oracle` matches its uppercase identifiers to the config
+    #[arg(long, value_enum, default_value_t = Dialect::Generic)]
+    dialect: Dialect,
+
+    /// CREATE TABLE statements for a data-only dump, giving INSERTs and COPY blocks without a column list their columns
+    #[arg(long, value_name = "PATH")]
+    schema: Option<PathBuf>,
+
+    /// Mapping from an earlier --mapping-out: unchanged rows (by table `key`) keep their previous values
+    #[arg(long, value_name = "PATH")]
+    baseline: Option<PathBuf>,
+
+    /// Record the anonymized rows of tables with a `key`, for a later --baseline
+    #[arg(long, value_name = "PATH")]
+    mapping_out: Option<PathBuf>,
+
+    /// Overwrite output files that already exist
+    #[arg(long, default_value_t = false)]
+    force: bool,
+
+    /// Write nothing; print the first changed values of each column and how many statements would change
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Changed values to show per column with --dry-run
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    dry_run_values: usize,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Sql,
+    Csv,
+    Tsv,
+}
+
+impl From<Format> for InputFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Sql => InputFormat::Sql,
+            Format::Csv => InputFormat::Csv,
+            Format::Tsv => InputFormat::Tsv,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Dialect {
+    Generic,
+    Oracle,
+}
+
+impl From<Dialect> for SqlDialect {
+    fn from(dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::Generic => SqlDialect::Generic,
+            Dialect::Oracle => SqlDialect::Oracle,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct LintArgs {
     #[arg(short, long)]
-    output: PathBuf,
+    config: PathBuf,
+}
 
+#[derive(clap::Args, Debug)]
+struct StreamArgs {
     #[arg(short, long)]
     config: PathBuf,
 
     #[arg(short, long, default_value_t = 42)]
     seed: u64,
+
+    /// Flush stdout after every N statements; higher values trade latency for throughput
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    flush_every: u64,
+
+    /// Remember fake values for this many recent originals; speeds up low-cardinality columns
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    cache_size: usize,
+
+    /// Abort once `unique`/`tokenize` state would exceed this size (e.g. 512M, 2G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_memory: Option<usize>,
+
+    /// Allow `command` strategies to execute external programs named in the config
+    #[arg(long, default_value_t = false)]
+    allow_commands: bool,
+
+    /// Database that wrote the statements; `oracle` matches its uppercase identifiers to the config
+    #[arg(long, value_enum, default_value_t = Dialect::Generic)]
+    dialect: Dialect,
+
+    /// CREATE TABLE statements giving INSERTs without a column list their columns
+    #[arg(long, value_name = "PATH")]
+    schema: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct RotateSeedArgs {
+    #[arg(short, long)]
+    config: PathBuf,
+
+    #[arg(short, long)]
+    input: PathBuf,
+
+    #[arg(long)]
+    old_seed: u64,
+
+    #[arg(long)]
+    new_seed: u64,
+
+    /// Write old -> new pseudonyms as CSV, to re-key data anonymized with the old seed
+    #[arg(long, value_name = "PATH")]
+    remapping: Option<PathBuf>,
 }
 
 #[derive(clap::Args, Debug)]
@@ -54,18 +293,143 @@ struct ScanArgs {
 
     #[arg(short = 'I', long, default_value_t = false)]
     interactive: bool,
+
+    /// Only scan this table (repeatable); also limits the wizard's table list
+    #[arg(long = "table", value_name = "NAME")]
+    tables: Vec<String>,
+
+    /// Only output columns whose guessed strategy changes values
+    #[arg(long, default_value_t = false)]
+    non_keep_only: bool,
+
+    /// Add newly found tables and columns to this existing config file,
+    /// leaving its entries untouched, instead of printing a new config
+    #[arg(long, value_name = "CONFIG")]
+    merge_into: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", describe_error(&err));
+            ExitCode::from(Failure::exit_code_of(&err))
+        }
+    }
+}
+
+/// Formats an error like anyhow's `Debug` output, leaving out the
+/// [`Failure`] marker that only selects the exit code.
+fn describe_error(err: &anyhow::Error) -> String {
+    let marker = Failure::of(err).map(|f| f.to_string());
+    let mut messages = err
+        .chain()
+        .map(|e| e.to_string())
+        .filter(|m| Some(m) != marker.as_ref());
+    let mut text = messages.next().unwrap_or_else(|| err.to_string());
+    let causes: Vec<String> = messages.collect();
+    if !causes.is_empty() {
+        text.push_str("\n\nCaused by:");
+        for cause in causes {
+            text.push_str(&format!("\n    {}", cause));
+        }
+    }
+    text
 }
 
-fn main() -> Result<()> {
-    env_logger::init();
+fn run() -> Result<()> {
     let cli = Cli::parse();
+    match cli.log_format {
+        LogFormat::Human => env_logger::init(),
+        LogFormat::Json => ghostdb::logging::init_json(),
+    }
+    QUIET.store(cli.quiet, Ordering::Relaxed);
+    let no_color = cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if no_color {
+        PLAIN.store(true, Ordering::Relaxed);
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
 
     match cli.command {
         Some(Commands::Run(args)) => {
             let config = AppConfig::load(&args.config)?;
-            run_processing(&args.input, &args.output, &config, args.seed)
+            config.ensure_commands_allowed(args.allow_commands)?;
+            let mut options = RunOptions {
+                fail_on_unconfigured: args.fail_on_unconfigured,
+                checksum: args.checksum || args.checksum_sidecar,
+                checksum_sidecar: args.checksum_sidecar,
+                preserve_formatting: args.preserve_formatting,
+                no_header_rebuild: args.no_header_rebuild,
+                summary_json: args.summary_json,
+                threads_io: None,
+                diff: args.diff,
+                cache_size: args.cache_size,
+                max_memory: args.max_memory,
+                format: args.format.into(),
+                table: args.table,
+                unsafe_keep_sample: args.unsafe_keep_sample,
+                resume: args.resume,
+                scrub_where: args.scrub_where,
+                collect_pseudonyms: false,
+                preview_values: None,
+                dialect: args.dialect.into(),
+                schema: args.schema,
+                baseline: args.baseline,
+                mapping_out: args.mapping_out,
+                overwrite: args.force,
+                flush_every: None,
+            };
+            // `--threads 1` is the serial loop.
+            options.threads_io = match args.threads_io {
+                Some(1) => None,
+                Some(workers) => Some(workers as usize),
+                None => default_threads_io(&config, &options),
+            };
+            if let Some(fraction) = options.unsafe_keep_sample {
+                // Deliberately not silenced by --quiet.
+                eprintln!(
+                    "WARNING: --unsafe-keep-sample copies about {:.1}% of rows with their ORIGINAL, \
+                     un-anonymized values (tagged `{}`). Never use it on real personal data.",
+                    fraction * 100.0,
+                    KEPT_SAMPLE_TAG
+                );
+            }
+            if options.scrub_where {
+                // Deliberately not silenced by --quiet.
+                eprintln!(
+                    "WARNING: --scrub-where rewrites the values in UPDATE/DELETE WHERE clauses. \
+                     Unless every compared column is anonymized consistently in the same dump, \
+                     those statements will match different rows than they did originally."
+                );
+            }
+            if args.dry_run {
+                let inputs = expand_inputs(&args.input)?;
+                let report = dry_run(&inputs, &config, args.seed, &options, args.dry_run_values)?;
+                print_dry_run(&report);
+                return Ok(());
+            }
+            let jobs = plan_run_jobs(&args.input, &args.output)?;
+            run_batch(&jobs, &config, args.seed, &options)
         }
         Some(Commands::Scan(args)) => process_scan(args),
+        Some(Commands::ListStrategies) => list_strategies(),
+        Some(Commands::RotateSeed(args)) => rotate_seed(args),
+        Some(Commands::Stream(args)) => stream(args),
+        Some(Commands::LintConfig(args)) => {
+            let config = AppConfig::load(&args.config)?;
+            let issues = lint_config(&config);
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            if issues.is_empty() {
+                status!("No problems found.");
+                Ok(())
+            } else {
+                Err(anyhow!("{} problem(s) found in {:?}", issues.len(), args.config)
+                    .context(Failure::Validation))
+            }
+        }
         None => {
             if let Some(input) = cli.input {
                 let output = cli.output.unwrap_or_else(|| {
@@ -73,7 +437,7 @@ fn main() -> Result<()> {
                     name.push("_anonymized.sql");
                     PathBuf::from(name)
                 });
-                process_smart_run(input, output)
+                process_smart_run(input, output, cli.plan_json)
             } else {
                 Err(anyhow!("No input file provided. Use --input or a subcommand."))
             }
@@ -81,288 +445,447 @@ fn main() -> Result<()> {
     }
 }
 
-fn process_scan(args: ScanArgs) -> Result<()> {
-    info!("Scanning file: {:?}", args.input);
-    let mut config = scan_file(&args.input)?;
+fn stream(args: StreamArgs) -> Result<()> {
+    let config = AppConfig::load(&args.config)?;
+    config.ensure_commands_allowed(args.allow_commands)?;
+    let options = RunOptions {
+        cache_size: args.cache_size,
+        max_memory: args.max_memory,
+        dialect: args.dialect.into(),
+        schema: args.schema,
+        flush_every: Some(args.flush_every as usize),
+        ..Default::default()
+    };
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::BufWriter::new(std::io::stdout().lock());
+    run_stream(stdin.lock(), &mut stdout, &config, args.seed, &options)
+}
 
-    if args.interactive {
-        run_interactive_wizard(&mut config)?;
-    } else {
-        let yaml = serde_yaml::to_string(&config)?;
-        println!("{}", yaml);
+/// Prints what a dry run found: per table, the statements that would be
+/// rewritten and, per column, its strategy and sample replacements.
+fn print_dry_run(report: &RunReport) {
+    let samples = report.pseudonyms.as_ref();
+    for (table, table_report) in &report.tables {
+        println!("{}: {} statement(s) would be rewritten", table, table_report.statements);
+        for (column, column_report) in &table_report.columns {
+            if column_report.transformed == 0 {
+                continue;
+            }
+            println!(
+                "  {} ({}): {} value(s) would change",
+                column,
+                column_report.strategy.name(),
+                column_report.transformed
+            );
+            let column_samples = samples.and_then(|s| s.get(&format!("{}.{}", table, column)));
+            for (original, replacement) in column_samples.into_iter().flatten() {
+                println!("    {} -> {}", original, replacement);
+            }
+        }
+    }
+    println!("{} statement(s) would be rewritten in total", report.anonymized_statements);
+    if !report.unconfigured_columns.is_empty() {
+        println!("{} column(s) have no config entry and would be kept", report.unconfigured_columns.len());
     }
-    Ok(())
 }
 
-fn process_smart_run(input: PathBuf, output: PathBuf) -> Result<()> {
-    info!("Starting Smart Run...");
-    info!("Input: {:?}", input);
-    
-    println!("Scanning file for schema...");
-    let mut config = scan_file(&input)?;
-    println!("Found {} tables.", config.tables.len());
-
-    println!("\nProposed Anonymization Plan:");
-    for (table, t_conf) in &config.tables {
-        println!("Table: {}", table);
-        for (col, strat) in &t_conf.columns {
-             if matches!(strat, ColumnStrategy::Keep) {
-             } else {
-                 println!("  - {} -> {:?}", col, strat);
-             }
-        }
-    }
-
-    let theme = ColorfulTheme::default();
-    let options = vec![
-        "Run (Execute Plan)",
-        "Customize Plan",
-        "Quit"
-    ];
-    
-    let selection = Select::with_theme(&theme)
-        .with_prompt("Ready to proceed?")
-        .default(0)
-        .items(&options)
-        .interact()?;
-
-    match selection {
-        0 => {
-            println!("Anonymizing to {:?}...", output);
-            run_processing(&input, &output, &config, 42)?;
-        }
-        1 => {
-            run_interactive_wizard(&mut config)?;
-            println!("Anonymizing to {:?}...", output);
-            run_processing(&input, &output, &config, 42)?;
-        }
-        _ => {
-            println!("Bye!");
+fn rotate_seed(args: RotateSeedArgs) -> Result<()> {
+    let config = AppConfig::load(&args.config)?;
+    if config.has_command_strategies() {
+        return Err(anyhow!("rotate-seed does not run `command` strategies").context(Failure::Config));
+    }
+    let rotations = rotation::compare_seeds(&args.input, &config, args.old_seed, args.new_seed)?;
+    for rotation in &rotations {
+        if !rotation.seeded {
+            println!("{}: not seeded; its output doesn't change with the seed", rotation.column);
+        } else if rotation.unchanged == 0 {
+            println!("{}: all {} values get new pseudonyms", rotation.column, rotation.values);
+        } else {
+            println!(
+                "{}: {} of {} values keep their pseudonym (values the strategy leaves alone, or chance collisions in a small output space)",
+                rotation.column, rotation.unchanged, rotation.values
+            );
         }
     }
-
+    if let Some(path) = &args.remapping {
+        rotation::write_remapping(path, &rotations)?;
+        status!("Remapping written to {:?}", path);
+    }
     Ok(())
 }
 
-fn scan_file(path: &Path) -> Result<AppConfig> {
-    let input_file = File::open(path)
-        .with_context(|| format!("Failed to open input file: {:?}", path))?;
-    let reader = BufReader::new(input_file);
-
-    let insert_regex = Regex::new(r"(?i)^INSERT\s+INTO\s+(\S+)\s*\((.*?)\)\s*VALUES")
-        .expect("Invalid regex pattern");
-
-    let mut tables_columns: HashMap<String, HashSet<String>> = HashMap::new();
-
-    for line_result in reader.lines() {
-        let line = line_result?;
-        if let Some(caps) = insert_regex.captures(&line) {
-            let table_full_name = caps.get(1).map(|m| m.as_str()).unwrap_or("").to_string();
-            let cols_part = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-
-            let columns: Vec<String> = cols_part
-                .split(',')
-                .map(|s| s.trim().trim_matches('"').to_string())
-                .collect();
-
-            let entry = tables_columns.entry(table_full_name).or_default();
-            for col in columns {
-                entry.insert(col);
-            }
-        }
+fn list_strategies() -> Result<()> {
+    let transformer = Transformer::new(42);
+    let strategies = catalog::strategies();
+    let names: Vec<String> = strategies.iter().map(|info| info.name()).collect();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    for (info, name) in strategies.iter().zip(&names) {
+        println!("{:width$}  {}", name, info.description, width = width);
+        println!(
+            "{:width$}  {} -> {}",
+            "",
+            info.example,
+            info.example_output(&transformer)?,
+            width = width
+        );
     }
+    Ok(())
+}
 
-    let mut config = AppConfig {
-        tables: HashMap::new(),
+/// A size in bytes, optionally with a binary `K`, `M` or `G` suffix
+/// (`512M`, `2G`).
+fn parse_size(s: &str) -> Result<usize, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches(['B', 'I']);
+    let (number, shift) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 10),
+        Some('M') => (&digits[..digits.len() - 1], 20),
+        Some('G') => (&digits[..digits.len() - 1], 30),
+        _ => (digits, 0),
     };
+    number
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("{} is not a size like 512M or 2G", s))
+}
 
-    for (table_name, columns) in tables_columns {
-        let mut table_config = TableConfig {
-            columns: HashMap::new(),
-        };
-        for col in columns {
-            let strategy = guess_strategy(&col);
-            table_config.columns.insert(col, strategy);
-        }
-        config.tables.insert(table_name, table_config);
+fn parse_fraction(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(f) if (0.0..=1.0).contains(&f) => Ok(f),
+        _ => Err(format!("{} is not a number between 0.0 and 1.0", s)),
     }
-
-    Ok(config)
 }
 
-fn guess_strategy(col_name: &str) -> ColumnStrategy {
-    let lower = col_name.to_lowercase();
-
-    if lower == "id" || lower.ends_with("_id") || lower.ends_with("uuid") || lower.ends_with("guid") {
-        return ColumnStrategy::Keep;
+/// Expands glob patterns in `inputs` and pairs each input with its output.
+/// With more than one input (or an existing directory as `output`), outputs
+/// are written into `output` under the input's file name.
+fn plan_run_jobs(inputs: &[PathBuf], output: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut expanded = expand_inputs(inputs)?;
+    if expanded.len() == 1 && (is_stdio(output) || !output.is_dir()) {
+        return Ok(vec![(expanded.remove(0), output.to_path_buf())]);
+    }
+    if is_stdio(output) || expanded.iter().any(|input| is_stdio(input)) {
+        return Err(anyhow!("stdin and stdout (`-`) only work with a single input").context(Failure::Config));
     }
 
-    if lower.contains("date") || lower.contains("time") || lower.ends_with("_at") {
-        return ColumnStrategy::Keep;
+    std::fs::create_dir_all(output)
+        .with_context(|| format!("Failed to create output directory: {:?}", output))
+        .context(Failure::Io)?;
+    let mut seen = HashSet::new();
+    let mut jobs = Vec::with_capacity(expanded.len());
+    for input in expanded {
+        let file_name = input
+            .file_name()
+            .ok_or_else(|| anyhow!("Input is not a file: {:?}", input))
+            .context(Failure::Io)?
+            .to_os_string();
+        if !seen.insert(file_name.clone()) {
+            return Err(anyhow!(
+                "Several inputs share the file name {:?}; their outputs would collide",
+                file_name
+            )
+            .context(Failure::Config));
+        }
+        let out = output.join(&file_name);
+        jobs.push((input, out));
     }
+    Ok(jobs)
+}
 
-    if lower.contains("amount") 
-        || lower.contains("price") 
-        || lower.contains("sum") 
-        || lower.contains("total") 
-        || lower.contains("balance") 
-        || lower.contains("cost") 
-        || lower.contains("currency") {
-        return ColumnStrategy::Keep;
+/// The input paths, with glob patterns expanded (sorted within each).
+fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+    for input in inputs {
+        let pattern = input.to_string_lossy();
+        if pattern.contains(['*', '?', '[']) {
+            let mut matched: Vec<PathBuf> = glob::glob(&pattern)
+                .with_context(|| format!("Invalid input pattern: {}", pattern))
+                .context(Failure::Config)?
+                .collect::<std::result::Result<_, _>>()
+                .with_context(|| format!("Failed to read input pattern: {}", pattern))
+                .context(Failure::Io)?;
+            if matched.is_empty() {
+                return Err(anyhow!("Input pattern matched no files: {}", pattern).context(Failure::Io));
+            }
+            matched.sort();
+            expanded.extend(matched);
+        } else {
+            expanded.push(input.clone());
+        }
     }
+    Ok(expanded)
+}
 
-    if lower.contains("email") {
-        return ColumnStrategy::Email;
+fn theme() -> Box<dyn Theme> {
+    if PLAIN.load(Ordering::Relaxed) {
+        Box::new(SimpleTheme)
+    } else {
+        Box::new(ColorfulTheme::default())
     }
-    if lower.contains("phone") || lower.contains("mobile") {
-        return ColumnStrategy::Phone;
+}
+
+fn process_scan(args: ScanArgs) -> Result<()> {
+    info!("Scanning file: {:?}", args.input);
+    let (mut config, mut samples) = scan_file_with_samples(&args.input)?;
+    for name in filter_scan(&mut config, &mut samples, &args.tables, args.non_keep_only) {
+        status!("Warning: --table {}: no such table in {:?}", name, args.input);
     }
-    if lower == "first_name" || lower == "firstname" {
-        return ColumnStrategy::FirstName;
+
+    if let Some(path) = &args.merge_into {
+        return merge_into(path, config, &samples, args.interactive);
     }
-    if lower == "last_name" || lower == "lastname" || lower == "surname" {
-        return ColumnStrategy::LastName;
+    if args.interactive {
+        run_interactive_wizard(&mut config, &samples)?;
+    } else {
+        let yaml = serde_yaml::to_string(&config)?;
+        println!("{}", yaml);
     }
-    if lower.contains("name") && !lower.contains("user") && !lower.contains("file") && !lower.contains("domain") {
-        return ColumnStrategy::FullName;
+    Ok(())
+}
+
+/// `scan --merge-into`: adds what the scan found and the config at `path`
+/// lacks, reports it, and writes the config back.
+fn merge_into(path: &Path, scanned: AppConfig, samples: &Samples, interactive: bool) -> Result<()> {
+    let mut config = AppConfig::load(path)?;
+    let merged = merge_scan(&mut config, scanned);
+    for table in &merged.tables {
+        let columns: BTreeMap<_, _> = config.tables[table].columns.iter().collect();
+        status!("Added table {} ({} columns)", table, columns.len());
+        for (column, strategy) in columns {
+            status!("  - {} -> {:?}", column, strategy);
+        }
     }
-    if lower.contains("address") || lower.contains("city") || lower.contains("street") {
-        return ColumnStrategy::Fixed("ANONYMIZED ADDRESS".to_string());
+    for (table, column) in &merged.columns {
+        status!("Added column {}.{} -> {:?}", table, column, config.tables[table].columns[column]);
     }
-    if lower.contains("password") || lower.contains("token") || lower.contains("secret") || lower.contains("key") {
-         return ColumnStrategy::Fixed("REDACTED_SECRET".to_string());
+    if merged.tables.is_empty() && merged.columns.is_empty() {
+        status!("Nothing new: {:?} already covers every scanned table and column.", path);
+        if !interactive {
+            return Ok(());
+        }
     }
-    if lower.contains("description") || lower.contains("comment") || lower.contains("note") {
-        return ColumnStrategy::Mask;
+    if interactive {
+        run_interactive_wizard(&mut config, samples)?;
     }
-
-    ColumnStrategy::Keep
+    std::fs::write(path, config.to_string_as(ConfigFormat::of(path)?)?)
+        .with_context(|| format!("Failed to write config file: {:?}", path))
+        .context(Failure::Io)?;
+    status!(
+        "Added {} table(s) and {} column(s) to {:?}.",
+        merged.tables.len(),
+        merged.columns.len(),
+        path
+    );
+    Ok(())
 }
 
-fn run_processing(input: &Path, output: &Path, config: &AppConfig, seed: u64) -> Result<()> {
-    let transformer = Transformer::new(seed);
-
-    let input_file = File::open(input)
-        .with_context(|| format!("Failed to open input file: {:?}", input))?;
-    let reader = BufReader::new(input_file);
-
-    let output_file = File::create(output)
-        .with_context(|| format!("Failed to create output file: {:?}", output))?;
-    let mut writer = BufWriter::new(output_file);
-
-    let insert_regex = Regex::new(r"(?i)^INSERT\s+INTO\s+(\S+)\s*\((.*?)\)\s*VALUES\s*\((.*)\);")
-        .expect("Invalid regex pattern");
+#[derive(Serialize)]
+struct PlanEntry<'a> {
+    table: &'a str,
+    column: &'a str,
+    strategy: &'a ColumnStrategy,
+    changes_value: bool,
+}
 
-    let mut processed_lines = 0;
-    let mut anonymized_count = 0;
+fn build_plan(config: &AppConfig) -> Vec<PlanEntry<'_>> {
+    let mut plan: Vec<PlanEntry> = config
+        .tables
+        .iter()
+        .flat_map(|(table, t_conf)| {
+            t_conf.columns.iter().map(move |(col, strat)| PlanEntry {
+                table,
+                column: col,
+                strategy: strat,
+                changes_value: !matches!(strat, ColumnStrategy::Keep),
+            })
+        })
+        .collect();
+    plan.sort_by(|a, b| (a.table, a.column).cmp(&(b.table, b.column)));
+    plan
+}
 
-    for line_result in reader.lines() {
-        let line = line_result.context("Error reading line from input")?;
-        processed_lines += 1;
+fn process_smart_run(input: PathBuf, output: PathBuf, plan_json: bool) -> Result<()> {
+    info!("Starting Smart Run...");
+    info!("Input: {:?}", input);
+    
+    status!("Scanning file for schema...");
+    let (mut config, samples) = scan_file_with_samples(&input)?;
+    status!("Found {} tables.", config.tables.len());
 
-        if processed_lines % 100_000 == 0 {
-            info!("Processed {} lines...", processed_lines);
+    if plan_json {
+        println!("{}", serde_json::to_string_pretty(&build_plan(&config))?);
+    } else {
+        status!("\nProposed Anonymization Plan:");
+        let tables: BTreeMap<_, _> = config.tables.iter().collect();
+        for (table, t_conf) in tables {
+            status!("Table: {}", table);
+            let columns: BTreeMap<_, _> = t_conf.columns.iter().collect();
+            for (col, strat) in columns {
+                 if matches!(strat, ColumnStrategy::Keep) {
+                 } else {
+                     status!("  - {} -> {:?}", col, strat);
+                 }
+            }
         }
+    }
 
-        if let Some(caps) = insert_regex.captures(&line) {
-            let table_full_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            
-            let table_key = if config.tables.contains_key(table_full_name) {
-                Some(table_full_name)
-            } else {
-                table_full_name.split('.').last().and_then(|name| {
-                    if config.tables.contains_key(name) {
-                        Some(name)
-                    } else {
-                        None
-                    }
-                })
-            };
+    let coverage = coverage(&config);
+    print_coverage(&coverage);
 
-            if let Some(key) = table_key {
-                if let Some(table_config) = config.tables.get(key) {
-                    let cols_part = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-                    let vals_part = caps.get(3).map(|m| m.as_str()).unwrap_or("");
+    let theme = theme();
+    let review = format!("Review Flagged Columns ({})", coverage.kept.len());
+    let mut options = vec!["Run (Execute Plan)"];
+    if !coverage.kept.is_empty() {
+        options.push(&review);
+    }
+    options.extend(["Customize Plan", "Quit"]);
+    
+    let selection = Select::with_theme(&*theme)
+        .with_prompt("Ready to proceed?")
+        .default(0)
+        .items(&options)
+        .interact()?;
 
-                    let columns: Vec<String> = cols_part
-                        .split(',')
-                        .map(|s| s.trim().trim_matches('"').to_string())
-                        .collect();
+    match options[selection] {
+        "Run (Execute Plan)" => {
+            anonymize_to(&input, &output, &config)?;
+        }
+        "Customize Plan" => {
+            run_interactive_wizard(&mut config, &samples)?;
+            anonymize_to(&input, &output, &config)?;
+        }
+        "Quit" => {
+            status!("Bye!");
+        }
+        _ => {
+            review_flagged(&mut config, &coverage.kept, &samples)?;
+            anonymize_to(&input, &output, &config)?;
+        }
+    }
 
-                    let values = Transformer::parse_values(vals_part);
+    Ok(())
+}
 
-                    if columns.len() != values.len() {
-                        warn!("Column count mismatch. Skipping line {}", processed_lines);
-                        writeln!(writer, "{}", line)?;
-                        continue;
-                    }
+/// Runs the plan, asking first if `output` already exists.
+fn anonymize_to(input: &Path, output: &Path, config: &AppConfig) -> Result<()> {
+    let overwrite = output.exists();
+    if overwrite
+        && !Confirm::with_theme(&*theme())
+            .with_prompt(format!("{:?} already exists. Overwrite it?", output))
+            .default(false)
+            .interact()?
+    {
+        status!("Left {:?} untouched.", output);
+        return Ok(());
+    }
+    status!("Anonymizing to {:?}...", output);
+    let options = RunOptions {
+        overwrite,
+        ..Default::default()
+    };
+    run_processing(input, output, config, 42, &options)
+}
 
-                    let mut new_values = Vec::with_capacity(values.len());
-
-                    for (i, col_name) in columns.iter().enumerate() {
-                        let strategy = table_config
-                            .columns
-                            .get(col_name)
-                            .unwrap_or(&ColumnStrategy::Keep);
-                        
-                        let original_val = &values[i];
-                        let new_val = transformer.transform(original_val, strategy);
-                        new_values.push(new_val);
-                    }
+/// The plan's coverage line, with the PII-candidate columns it keeps
+/// highlighted.
+fn print_coverage(coverage: &Coverage) {
+    if coverage.candidates == 0 {
+        return;
+    }
+    let line = format!(
+        "{} of {} PII-candidate columns will be anonymized",
+        coverage.anonymized, coverage.candidates
+    );
+    if coverage.kept.is_empty() {
+        status!("\n{}.", style(line).green());
+        return;
+    }
+    status!(
+        "\n{}; {} kept - review?",
+        style(line).yellow(),
+        style(coverage.kept.len()).yellow().bold()
+    );
+    for (table, column) in &coverage.kept {
+        status!("  {} {}.{} is kept as-is", style("!").yellow().bold(), table, column);
+    }
+}
 
-                    let new_values_str = new_values.join(", ");
-                    writeln!(
-                        writer,
-                        "INSERT INTO {} ({}) VALUES ({});",
-                        table_full_name, cols_part, new_values_str
-                    )?;
-                    anonymized_count += 1;
-                } else {
-                    writeln!(writer, "{}", line)?;
-                }
-            } else {
-                writeln!(writer, "{}", line)?;
+/// Asks for a strategy for each of the `flagged` columns, previewing it on
+/// the column's samples.
+fn review_flagged(config: &mut AppConfig, flagged: &[(String, String)], samples: &Samples) -> Result<()> {
+    for (table, column) in flagged {
+        status!("\nTable: {}", table);
+        let col_samples = samples
+            .get(table)
+            .and_then(|s| s.get(column))
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let strategy = loop {
+            let strategy = select_strategy(column)?;
+            if confirm_preview(&strategy, col_samples)? {
+                break strategy;
             }
-        } else {
-            writeln!(writer, "{}", line)?;
+        };
+        if let Some(table_config) = config.tables.get_mut(table) {
+            table_config.columns.insert(column.clone(), strategy);
         }
     }
-
-    writer.flush().context("Failed to flush output buffer")?;
-    info!("Done! Processed {} lines. Anonymized {} statements.", processed_lines, anonymized_count);
     Ok(())
 }
 
-fn run_interactive_wizard(config: &mut AppConfig) -> Result<()> {
-    let theme = ColorfulTheme::default();
-    println!("GhostDB Interactive Config Wizard");
+fn run_interactive_wizard(config: &mut AppConfig, samples: &Samples) -> Result<()> {
+    let theme = theme();
+    status!("GhostDB Interactive Config Wizard");
     
     loop {
         let mut table_names: Vec<String> = config.tables.keys().cloned().collect();
         table_names.sort();
         
         let mut choices = table_names.clone();
+        choices.push("Add a table...".to_string());
         choices.push("Save and Proceed".to_string());
 
-        let selection = Select::with_theme(&theme)
+        let selection = Select::with_theme(&*theme)
             .with_prompt("Select a table to configure")
             .default(0)
             .items(&choices)
             .interact()?;
 
-        if selection == table_names.len() {
+        if selection == table_names.len() + 1 {
             break;
         }
 
+        // Tables the scan didn't see, e.g. created but not yet populated.
+        if selection == table_names.len() {
+            let table_name = prompt_new_name("Table name", &table_names)?;
+            let mut table_config = TableConfig::default();
+            configure_table(&table_name, &mut table_config, None)?;
+            if !table_config.columns.is_empty() {
+                config.tables.insert(table_name, table_config);
+            }
+            continue;
+        }
+
         let table_name = &table_names[selection];
-        configure_table(table_name, config.tables.get_mut(table_name).unwrap())?;
+        configure_table(
+            table_name,
+            config.tables.get_mut(table_name).unwrap(),
+            samples.get(table_name),
+        )?;
     }
 
     Ok(())
 }
 
-fn configure_table(table_name: &str, table_config: &mut TableConfig) -> Result<()> {
-    let theme = ColorfulTheme::default();
+fn configure_table(
+    table_name: &str,
+    table_config: &mut TableConfig,
+    samples: Option<&HashMap<String, Vec<String>>>,
+) -> Result<()> {
+    let theme = theme();
     
     loop {
         let mut col_names: Vec<String> = table_config.columns.keys().cloned().collect();
@@ -374,53 +897,233 @@ fn configure_table(table_name: &str, table_config: &mut TableConfig) -> Result<(
         }).collect();
 
         let mut choices = display_items.clone();
+        choices.push("Add a column...".to_string());
         choices.push("Back to Tables".to_string());
 
-        let selection = Select::with_theme(&theme)
+        let selection = Select::with_theme(&*theme)
             .with_prompt(format!("Configure columns for table '{}'", table_name))
             .default(0)
             .items(&choices)
             .interact()?;
 
-        if selection == col_names.len() {
+        if selection == col_names.len() + 1 {
             break;
         }
 
+        // Columns the scan missed get a rule up front; there are no samples
+        // to preview.
+        if selection == col_names.len() {
+            let col_name = prompt_new_name("Column name", &col_names)?;
+            let strategy = select_strategy(&col_name)?;
+            table_config.columns.insert(col_name, strategy);
+            continue;
+        }
+
         let col_name = &col_names[selection];
-        let new_strategy = select_strategy(col_name)?;
+        let col_samples = samples
+            .and_then(|s| s.get(col_name))
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let new_strategy = loop {
+            let strategy = select_strategy(col_name)?;
+            if confirm_preview(&strategy, col_samples)? {
+                break strategy;
+            }
+        };
+        // A `max_len` from the scan only applies to names.
+        if !is_name_strategy(&new_strategy) {
+            if let Some(options) = table_config.options.get_mut(col_name) {
+                options.max_len = None;
+                if *options == ColumnOptions::default() {
+                    table_config.options.remove(col_name);
+                }
+            }
+        }
         table_config.columns.insert(col_name.clone(), new_strategy);
     }
     Ok(())
 }
 
+/// Asks for a table or column name that isn't empty and not in `existing`.
+fn prompt_new_name(prompt: &str, existing: &[String]) -> Result<String> {
+    let name: String = Input::with_theme(&*theme())
+        .with_prompt(prompt)
+        .validate_with(|name: &String| {
+            if name.trim().is_empty() {
+                Err("must not be empty")
+            } else if existing.iter().any(|e| e == name.trim()) {
+                Err("already in the config; select it from the list instead")
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+    Ok(name.trim().to_string())
+}
+
+/// Shows what `strategy` does to the column's sampled values (with the
+/// smart run's seed) and asks whether to keep it. Always accepts when
+/// there are no samples to show.
+fn confirm_preview(strategy: &ColumnStrategy, samples: &[String]) -> Result<bool> {
+    if samples.is_empty() {
+        return Ok(true);
+    }
+    let transformer = Transformer::new(42);
+    status!("Preview:");
+    for sample in samples {
+        let preview = transformer
+            .transform(sample, strategy)
+            .unwrap_or_else(|e| format!("<error: {}>", e));
+        status!("  {} -> {}", sample, preview);
+    }
+    Ok(Confirm::with_theme(&*theme())
+        .with_prompt("Use this strategy?")
+        .default(true)
+        .interact()?)
+}
+
 fn select_strategy(col_name: &str) -> Result<ColumnStrategy> {
-    let strategies = vec![
-        ("Keep (Original Value)", ColumnStrategy::Keep),
-        ("Email (fake@example.com)", ColumnStrategy::Email),
-        ("First Name (Alice)", ColumnStrategy::FirstName),
-        ("Last Name (Smith)", ColumnStrategy::LastName),
-        ("Full Name (Alice Smith)", ColumnStrategy::FullName),
-        ("Phone (+1-555...)", ColumnStrategy::Phone),
-        ("Mask (a***@e***.com)", ColumnStrategy::Mask),
-        ("Fixed Value...", ColumnStrategy::Fixed("".to_string())),
-    ];
-
-    let items: Vec<&str> = strategies.iter().map(|(n, _)| *n).collect();
-
-    let selection = Select::with_theme(&ColorfulTheme::default())
+    let strategies: Vec<StrategyInfo> = catalog::strategies()
+        .into_iter()
+        .filter(|info| info.in_menu)
+        .collect();
+    let items: Vec<&str> = strategies.iter().map(|info| info.label).collect();
+
+    let selection = Select::with_theme(&*theme())
         .with_prompt(format!("Select strategy for column '{}'", col_name))
         .items(&items)
         .interact()?;
 
-    let (_, strategy) = &strategies[selection];
+    let strategy = &strategies[selection].strategy;
     
     match strategy {
         ColumnStrategy::Fixed(_) => {
-            let val: String = Input::with_theme(&ColorfulTheme::default())
+            let val: String = Input::with_theme(&*theme())
                 .with_prompt("Enter the fixed value")
                 .interact_text()?;
             Ok(ColumnStrategy::Fixed(val))
         }
+        ColumnStrategy::CompanyEmail { domain, format } => {
+            let domain: String = Input::with_theme(&*theme())
+                .with_prompt("Email domain")
+                .default(domain.clone())
+                .interact_text()?;
+            let format: String = Input::with_theme(&*theme())
+                .with_prompt("Local part ({first}, {last}, {f} and {l} are the row's names and initials)")
+                .default(format.clone())
+                .interact_text()?;
+            Ok(ColumnStrategy::CompanyEmail { domain, format })
+        }
+        ColumnStrategy::Template(template) => {
+            let template: String = Input::with_theme(&*theme())
+                .with_prompt("Template ({seq}, {rand:N}, {hex:N}, {firstname}, {lastname}, {name}, {word})")
+                .default(template.clone())
+                .validate_with(|t: &String| Template::parse(t).map(|_| ()))
+                .interact_text()?;
+            Ok(ColumnStrategy::Template(template))
+        }
+        ColumnStrategy::Tokenize { prefix } => {
+            let prefix: String = Input::with_theme(&*theme())
+                .with_prompt("Token prefix (empty for bare numbers)")
+                .default(prefix.clone())
+                .allow_empty(true)
+                .interact_text()?;
+            Ok(ColumnStrategy::Tokenize { prefix })
+        }
+        ColumnStrategy::PrefixedHash { prefix, len } => {
+            let prefix: String = Input::with_theme(&*theme())
+                .with_prompt("Key prefix (empty for bare hex digits)")
+                .default(prefix.clone())
+                .allow_empty(true)
+                .interact_text()?;
+            let len: usize = Input::with_theme(&*theme())
+                .with_prompt("Hex digits to keep (more digits, fewer collisions)")
+                .default(*len)
+                .validate_with(|len: &usize| {
+                    if (1..=MAX_HASH_LEN).contains(len) {
+                        Ok(())
+                    } else {
+                        Err("must be between 1 and 64")
+                    }
+                })
+                .interact_text()?;
+            Ok(ColumnStrategy::PrefixedHash { prefix, len })
+        }
+        ColumnStrategy::Pseudonym { namespace } => {
+            let namespace: String = Input::with_theme(&*theme())
+                .with_prompt("Namespace (columns that share one get the same pseudonyms, e.g. a key and its foreign keys)")
+                .default(namespace.clone())
+                .interact_text()?;
+            Ok(ColumnStrategy::Pseudonym { namespace })
+        }
+        ColumnStrategy::RandomInt { min, max } => {
+            let min: i64 = Input::with_theme(&*theme())
+                .with_prompt("Smallest value")
+                .default(*min)
+                .interact_text()?;
+            let max: i64 = Input::with_theme(&*theme())
+                .with_prompt("Largest value")
+                .default((*max).max(min))
+                .validate_with(|max: &i64| {
+                    if *max >= min {
+                        Ok(())
+                    } else {
+                        Err("must not be below the smallest value")
+                    }
+                })
+                .interact_text()?;
+            Ok(ColumnStrategy::RandomInt { min, max })
+        }
+        ColumnStrategy::RandomNull { .. } => {
+            let probability: f64 = Input::with_theme(&*theme())
+                .with_prompt("Fraction of values to set to NULL (0.0 - 1.0)")
+                .default(0.1)
+                .validate_with(|p: &f64| {
+                    if (0.0..=1.0).contains(p) {
+                        Ok(())
+                    } else {
+                        Err("must be between 0.0 and 1.0")
+                    }
+                })
+                .interact_text()?;
+            Ok(ColumnStrategy::RandomNull { probability })
+        }
+        ColumnStrategy::Url { .. } => {
+            let keep_path = Confirm::with_theme(&*theme())
+                .with_prompt("Keep the scheme, path and query, replacing only the host?")
+                .default(false)
+                .interact()?;
+            Ok(ColumnStrategy::Url { keep_path })
+        }
+        ColumnStrategy::Mask { char, reveal_start, reveal_end } => {
+            let char: char = Input::with_theme(&*theme())
+                .with_prompt("Mask character")
+                .default(*char)
+                .validate_with(|c: &char| {
+                    if *c == '\'' || *c == '\\' {
+                        Err("quotes and backslashes would break the SQL string")
+                    } else {
+                        Ok(())
+                    }
+                })
+                .interact_text()?;
+            let reveal_start: usize = Input::with_theme(&*theme())
+                .with_prompt("Characters to reveal at the start")
+                .default(*reveal_start)
+                .interact_text()?;
+            let reveal_end: usize = Input::with_theme(&*theme())
+                .with_prompt("Characters to reveal at the end")
+                .default(*reveal_end)
+                .interact_text()?;
+            Ok(ColumnStrategy::Mask { char, reveal_start, reveal_end })
+        }
+        ColumnStrategy::ZipCode { keep_prefix } => {
+            let keep_prefix: usize = Input::with_theme(&*theme())
+                .with_prompt("Leading digits to keep (0 - 5)")
+                .default(*keep_prefix)
+                .interact_text()?;
+            Ok(ColumnStrategy::ZipCode { keep_prefix })
+        }
         _ => Ok(strategy.clone()),
     }
 }
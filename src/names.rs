@@ -0,0 +1,64 @@
+use rand::Rng;
+
+/// First names conventionally given to boys.
+const MALE: &[&str] = &[
+    "Aaron", "Adam", "Albert", "Andrew", "Anthony", "Arthur", "Benjamin", "Brian", "Carl",
+    "Charles", "Christopher", "Daniel", "David", "Dennis", "Donald", "Douglas", "Edward", "Eric",
+    "Frank", "Gary", "George", "Gregory", "Harold", "Henry", "Jack", "James", "Jason", "Jeffrey",
+    "John", "Jonathan", "Joseph", "Joshua", "Kenneth", "Kevin", "Lawrence", "Mark", "Matthew",
+    "Michael", "Nathan", "Patrick", "Paul", "Peter", "Raymond", "Richard", "Robert", "Ronald",
+    "Samuel", "Scott", "Stephen", "Thomas", "Timothy", "Walter", "William",
+];
+
+/// First names conventionally given to girls.
+const FEMALE: &[&str] = &[
+    "Alice", "Amanda", "Amy", "Angela", "Anna", "Barbara", "Betty", "Carol", "Catherine",
+    "Christine", "Deborah", "Diana", "Donna", "Dorothy", "Elizabeth", "Emily", "Emma", "Evelyn",
+    "Frances", "Grace", "Hannah", "Helen", "Janet", "Jennifer", "Jessica", "Joan", "Julia",
+    "Karen", "Katherine", "Laura", "Linda", "Lisa", "Margaret", "Maria", "Mary", "Megan",
+    "Melissa", "Michelle", "Nancy", "Olivia", "Pamela", "Rachel", "Rebecca", "Ruth", "Sandra",
+    "Sarah", "Sharon", "Sophia", "Susan", "Victoria", "Virginia",
+];
+
+/// First names in common use for any gender.
+const NEUTRAL: &[&str] = &[
+    "Addison", "Alex", "Ariel", "Avery", "Bailey", "Blake", "Cameron", "Casey", "Charlie",
+    "Dakota", "Drew", "Eden", "Elliot", "Emerson", "Finley", "Frankie", "Harper", "Hayden",
+    "Jamie", "Jesse", "Jordan", "Kai", "Kendall", "Logan", "Morgan", "Parker", "Peyton", "Quinn",
+    "Reese", "Riley", "River", "Rowan", "Sage", "Sam", "Skyler", "Taylor",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gender {
+    Male,
+    Female,
+    Neutral,
+}
+
+impl Gender {
+    /// Reads a gender column's (unquoted) value: `m`/`male`/`man`, `f`/
+    /// `female`/`woman` in any case, or the ISO/IEC 5218 codes `1` and `2`.
+    /// Anything else, including `NULL`, is neutral.
+    pub fn parse(value: &str) -> Gender {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "m" | "male" | "man" | "boy" | "1" => Gender::Male,
+            "f" | "female" | "woman" | "girl" | "2" => Gender::Female,
+            _ => Gender::Neutral,
+        }
+    }
+}
+
+/// The first names used for `gender`.
+pub fn pool(gender: Gender) -> &'static [&'static str] {
+    match gender {
+        Gender::Male => MALE,
+        Gender::Female => FEMALE,
+        Gender::Neutral => NEUTRAL,
+    }
+}
+
+/// A first name matching `gender`.
+pub fn first_name<R: Rng>(gender: Gender, rng: &mut R) -> &'static str {
+    let names = pool(gender);
+    names[rng.gen_range(0..names.len())]
+}
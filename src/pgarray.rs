@@ -0,0 +1,132 @@
+//! Parsing and serialization of PostgreSQL array literals (`{a,"b c",NULL}`).
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayItem {
+    Null,
+    Value(String),
+    Nested(Vec<ArrayItem>),
+}
+
+/// Parses an array literal such as `{a@b.com,"x, y",{1,2},NULL}`.
+/// Returns `None` if the text isn't a well-formed array.
+pub fn parse(literal: &str) -> Option<Vec<ArrayItem>> {
+    let chars: Vec<char> = literal.trim().chars().collect();
+    let mut pos = 0;
+    let items = parse_list(&chars, &mut pos)?;
+    if pos == chars.len() {
+        Some(items)
+    } else {
+        None
+    }
+}
+
+fn parse_list(chars: &[char], pos: &mut usize) -> Option<Vec<ArrayItem>> {
+    if chars.get(*pos) != Some(&'{') {
+        return None;
+    }
+    *pos += 1;
+    let mut items = Vec::new();
+
+    skip_whitespace(chars, pos);
+    if chars.get(*pos) == Some(&'}') {
+        *pos += 1;
+        return Some(items);
+    }
+
+    loop {
+        skip_whitespace(chars, pos);
+        let item = match chars.get(*pos)? {
+            '{' => ArrayItem::Nested(parse_list(chars, pos)?),
+            '"' => ArrayItem::Value(parse_quoted(chars, pos)?),
+            _ => {
+                let start = *pos;
+                while *pos < chars.len() && !matches!(chars[*pos], ',' | '}') {
+                    *pos += 1;
+                }
+                let raw: String = chars[start..*pos].iter().collect();
+                let raw = raw.trim();
+                if raw.is_empty() {
+                    return None;
+                }
+                if raw.eq_ignore_ascii_case("NULL") {
+                    ArrayItem::Null
+                } else {
+                    ArrayItem::Value(raw.to_string())
+                }
+            }
+        };
+        items.push(item);
+
+        skip_whitespace(chars, pos);
+        match chars.get(*pos)? {
+            ',' => *pos += 1,
+            '}' => {
+                *pos += 1;
+                return Some(items);
+            }
+            _ => return None,
+        }
+    }
+}
+
+fn parse_quoted(chars: &[char], pos: &mut usize) -> Option<String> {
+    *pos += 1;
+    let mut value = String::new();
+    loop {
+        match chars.get(*pos)? {
+            '\\' => {
+                value.push(*chars.get(*pos + 1)?);
+                *pos += 2;
+            }
+            '"' => {
+                *pos += 1;
+                return Some(value);
+            }
+            c => {
+                value.push(*c);
+                *pos += 1;
+            }
+        }
+    }
+}
+
+fn skip_whitespace(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+/// Serializes items back into an array literal, quoting elements that
+/// would otherwise be ambiguous.
+pub fn serialize(items: &[ArrayItem]) -> String {
+    let parts: Vec<String> = items
+        .iter()
+        .map(|item| match item {
+            ArrayItem::Null => "NULL".to_string(),
+            ArrayItem::Nested(nested) => serialize(nested),
+            ArrayItem::Value(value) => quote_element(value),
+        })
+        .collect();
+    format!("{{{}}}", parts.join(","))
+}
+
+fn quote_element(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.eq_ignore_ascii_case("NULL")
+        || value
+            .chars()
+            .any(|c| matches!(c, '{' | '}' | ',' | '"' | '\\') || c.is_whitespace());
+    if !needs_quotes {
+        return value.to_string();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
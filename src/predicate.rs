@@ -0,0 +1,306 @@
+//! Literal comparisons and `IN` lists in the `WHERE` clause of `UPDATE`
+//! and `DELETE` statements, which `--scrub-where` anonymizes in place.
+
+use std::ops::Range;
+
+/// `column <op> literal` (or `literal <op> column`) with `=`, `<>` or `!=`,
+/// or one element of an `IN` list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// The column name, unquoted and without any table qualifier.
+    pub column: String,
+    /// Byte range of the literal, quotes included.
+    pub value: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    /// A keyword or a (possibly quoted or qualified) name.
+    Word,
+    String,
+    Number,
+    Equality,
+    Open,
+    Close,
+    Comma,
+    Other,
+}
+
+/// Byte offset just after the first `WHERE` keyword outside quotes and
+/// comments, if `statement` has one.
+pub fn where_start(statement: &str) -> Option<usize> {
+    lex(statement)
+        .into_iter()
+        .find(|(kind, range)| {
+            *kind == Token::Word && statement[range.clone()].eq_ignore_ascii_case("WHERE")
+        })
+        .map(|(_, range)| range.end)
+}
+
+/// The literal comparisons in `condition`, with ranges relative to it.
+/// `col IN ('a', 'b')` and `(a, b) IN (('x', 1), ('y', 2))` give one
+/// comparison per element, also inside subqueries. Anything more complex
+/// (functions, arithmetic, `LIKE`) is left alone.
+pub fn comparisons(condition: &str) -> Vec<Comparison> {
+    let tokens = lex(condition);
+    let mut found = Vec::new();
+    for (i, (kind, range)) in tokens.iter().enumerate() {
+        if *kind == Token::Word && condition[range.clone()].eq_ignore_ascii_case("IN") {
+            found.extend(in_list(condition, &tokens, i));
+        }
+    }
+    for window in tokens.windows(3) {
+        let [(left, l), (Token::Equality, _), (right, r)] = window else {
+            continue;
+        };
+        let (name, value) = match (left, right) {
+            (Token::Word, Token::String | Token::Number) => (l, r),
+            (Token::String | Token::Number, Token::Word) => (r, l),
+            _ => continue,
+        };
+        found.push(Comparison {
+            column: unqualify(&condition[name.clone()]),
+            value: value.clone(),
+        });
+    }
+    found.sort_by_key(|c| c.value.start);
+    found
+}
+
+/// The elements of the `IN` list whose keyword is `tokens[at]`, paired with
+/// their columns. Empty unless every element is a literal (or `NULL`, which
+/// is skipped) or, for a row of columns, a row of literals of its width.
+fn in_list(text: &str, tokens: &[(Token, Range<usize>)], at: usize) -> Vec<Comparison> {
+    let word = |i: usize| text[tokens[i].1.clone()].to_string();
+    let mut before = at;
+    if before > 0 && tokens[before - 1].0 == Token::Word && word(before - 1).eq_ignore_ascii_case("NOT") {
+        before -= 1;
+    }
+    let columns = match before.checked_sub(1).map(|i| &tokens[i].0) {
+        Some(Token::Word) => vec![unqualify(&word(before - 1))],
+        Some(Token::Close) => {
+            let Some(open) = tokens[..before - 1].iter().rposition(|(k, _)| *k == Token::Open) else {
+                return Vec::new();
+            };
+            match row(&tokens[open..before], |t, _| t == Token::Word) {
+                Some(names) => names.iter().map(|r| unqualify(&text[r.clone()])).collect(),
+                None => return Vec::new(),
+            }
+        }
+        _ => return Vec::new(),
+    };
+    let Some((Token::Open, _)) = tokens.get(at + 1) else {
+        return Vec::new();
+    };
+
+    let is_null = |r: &Range<usize>| text[r.clone()].eq_ignore_ascii_case("NULL");
+    let literal = |t: Token, r: &Range<usize>| match t {
+        Token::String | Token::Number => true,
+        Token::Word => is_null(r),
+        _ => false,
+    };
+    let mut found = Vec::new();
+    let mut i = at + 2;
+    loop {
+        let element: Vec<Range<usize>> = if columns.len() == 1 {
+            match tokens.get(i) {
+                Some((kind, range)) if literal(*kind, range) => {
+                    i += 1;
+                    vec![range.clone()]
+                }
+                _ => return Vec::new(),
+            }
+        } else {
+            let Some(len) = tokens[i..].iter().position(|(k, _)| *k == Token::Close) else {
+                return Vec::new();
+            };
+            let values = row(&tokens[i..=i + len], literal);
+            i += len + 1;
+            match values {
+                Some(values) if values.len() == columns.len() => values,
+                _ => return Vec::new(),
+            }
+        };
+        for (column, value) in columns.iter().zip(element) {
+            if !is_null(&value) {
+                found.push(Comparison {
+                    column: column.clone(),
+                    value,
+                });
+            }
+        }
+        match tokens.get(i) {
+            Some((Token::Comma, _)) => i += 1,
+            Some((Token::Close, _)) => return found,
+            _ => return Vec::new(),
+        }
+    }
+}
+
+/// The items of `(item, item, ...)`, which must span all of `tokens` and
+/// contain only tokens accepted by `item`.
+fn row(
+    tokens: &[(Token, Range<usize>)],
+    item: impl Fn(Token, &Range<usize>) -> bool,
+) -> Option<Vec<Range<usize>>> {
+    let [(Token::Open, _), inner @ .., (Token::Close, _)] = tokens else {
+        return None;
+    };
+    let mut items = Vec::new();
+    for (n, (kind, range)) in inner.iter().enumerate() {
+        let ok = if n % 2 == 0 { item(*kind, range) } else { *kind == Token::Comma };
+        if !ok {
+            return None;
+        }
+        if n % 2 == 0 {
+            items.push(range.clone());
+        }
+    }
+    (inner.len() % 2 == 1).then_some(items)
+}
+
+/// The last part of a possibly qualified name, without its quotes:
+/// `u."e-mail"` is `e-mail`.
+fn unqualify(name: &str) -> String {
+    let mut start = 0;
+    let mut quote = None;
+    for (i, c) in name.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') => quote = Some('"'),
+            (None, '`') => quote = Some('`'),
+            (None, '[') => quote = Some(']'),
+            (None, '.') => start = i + 1,
+            _ => {}
+        }
+    }
+    name[start..].trim_matches(|c| matches!(c, '"' | '`' | '[' | ']')).to_string()
+}
+
+/// Splits `text` into tokens with their byte ranges, skipping whitespace
+/// and comments. Qualified names (`t.col`) are a single token.
+fn lex(text: &str) -> Vec<(Token, Range<usize>)> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = text[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = text[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4);
+                continue;
+            }
+            b'\'' => {
+                i = quoted_end(bytes, i, b'\'');
+                Token::String
+            }
+            b'"' | b'`' | b'[' => {
+                i = name_end(bytes, i);
+                Token::Word
+            }
+            b'(' => {
+                i += 1;
+                Token::Open
+            }
+            b')' => {
+                i += 1;
+                Token::Close
+            }
+            b',' => {
+                i += 1;
+                Token::Comma
+            }
+            b'=' => {
+                i += 1;
+                Token::Equality
+            }
+            b'<' if bytes.get(i + 1) == Some(&b'>') => {
+                i += 2;
+                Token::Equality
+            }
+            b'!' if bytes.get(i + 1) == Some(&b'=') => {
+                i += 2;
+                Token::Equality
+            }
+            b'-' | b'0'..=b'9' if starts_number(&bytes[i..]) => {
+                i += 1;
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                Token::Number
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' || b >= 0x80 => {
+                i = name_end(bytes, i);
+                Token::Word
+            }
+            _ => {
+                i += 1;
+                Token::Other
+            }
+        };
+        tokens.push((kind, start..i));
+    }
+    tokens
+}
+
+fn starts_number(bytes: &[u8]) -> bool {
+    match bytes {
+        [b'-', next, ..] => next.is_ascii_digit(),
+        [first, ..] => first.is_ascii_digit(),
+        [] => false,
+    }
+}
+
+/// End of the quoted run starting at `start`, honoring doubled and
+/// backslash-escaped quotes.
+fn quoted_end(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if quote != b'`' => i += 2,
+            b if b == quote && bytes.get(i + 1) == Some(&quote) => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// End of a possibly qualified name starting at `start`: words and quoted
+/// identifiers joined by dots.
+fn name_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start;
+    loop {
+        i = match bytes.get(i) {
+            Some(b'"') => quoted_end(bytes, i, b'"'),
+            Some(b'`') => quoted_end(bytes, i, b'`'),
+            Some(b'[') => bytes[i..]
+                .iter()
+                .position(|&b| b == b']')
+                .map_or(bytes.len(), |n| i + n + 1),
+            _ => {
+                let mut j = i;
+                while j < bytes.len()
+                    && (bytes[j].is_ascii_alphanumeric() || matches!(bytes[j], b'_' | b'$') || bytes[j] >= 0x80)
+                {
+                    j += 1;
+                }
+                j
+            }
+        };
+        if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(|&b| !b.is_ascii_whitespace()) {
+            i += 1;
+        } else {
+            return i;
+        }
+    }
+}
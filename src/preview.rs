@@ -0,0 +1,56 @@
+//! Dry runs: what `run` would change, without writing any output. The
+//! input is anonymized exactly as a real run with the same config and
+//! seed would, and the first few changed values of each column are kept
+//! with their replacements for review.
+
+use crate::config::AppConfig;
+use crate::failure::Failure;
+use crate::processor::{is_stdio, process_stream, InputFormat, RunOptions};
+use crate::report::RunReport;
+use crate::transformer::Transformer;
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+/// Anonymizes `inputs` into nothing with one shared transformer, like
+/// [`run_batch`](crate::processor::run_batch), and returns the report:
+/// statement counts, each column's strategy and, in `pseudonyms`, up to
+/// `values` changed originals per column with their replacements.
+pub fn dry_run(
+    inputs: &[PathBuf],
+    config: &AppConfig,
+    seed: u64,
+    options: &RunOptions,
+    values: usize,
+) -> Result<RunReport> {
+    if options.format != InputFormat::Sql {
+        return Err(anyhow!("--dry-run only works for SQL input").context(Failure::Config));
+    }
+    let options = RunOptions {
+        preview_values: Some(values),
+        ..options.clone()
+    };
+    let transformer = Transformer::new(seed)
+        .with_cache(options.cache_size)
+        .with_memory_limit(options.max_memory);
+    let mut report = RunReport::default();
+    for input in inputs {
+        let reader: Box<dyn BufRead> = if is_stdio(input) {
+            Box::new(io::stdin().lock())
+        } else {
+            let file = File::open(input)
+                .with_context(|| format!("Failed to open input file: {:?}", input))
+                .context(Failure::Io)?;
+            Box::new(BufReader::new(file))
+        };
+        let file_report = process_stream(&transformer, reader, &mut io::sink(), config, &options)?;
+        if inputs.len() == 1 {
+            report = file_report;
+        } else {
+            report.merge_file(&input.display().to_string(), file_report);
+        }
+    }
+    report.unparseable_values = transformer.unparseable_values();
+    Ok(report)
+}
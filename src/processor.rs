@@ -0,0 +1,1843 @@
+use crate::baseline::Incremental;
+use crate::checksum::{HashingReader, HashingWriter};
+use crate::config::{AppConfig, ColumnOptions, ColumnStrategy, QuotedNull, TableConfig};
+use crate::copy::{self, CopyHeader};
+use crate::delimited;
+use crate::diff::UnifiedDiff;
+use crate::failure::Failure;
+use crate::ident::{requote, split_column_list, unquote_identifier, unquote_qualified};
+use crate::predicate;
+use crate::rename;
+use crate::report::RunReport;
+use crate::resume::{self, Checkpoint, CountingLines, CountingWriter, CHECKPOINT_INTERVAL};
+use crate::schema::{fitted_type, normalize_type, parse_create_table, parse_with_type_ranges, Schemas, TableSchema};
+use crate::sql::{
+    normalize_keywords, split_statements, statement_body, Chunk, ChunkKind, INSERT_PREFIX,
+    INSERT_SET_PREFIX, PREDICATE_PREFIX, TABLE_NAME,
+};
+use crate::transformer::{unescape_literal, ColumnRule, Transformer};
+use anyhow::{anyhow, Context, Result};
+use log::{info, warn};
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::cell::Cell;
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::thread;
+
+#[derive(Debug, Default, Clone)]
+pub struct RunOptions {
+    pub fail_on_unconfigured: bool,
+    pub checksum: bool,
+    pub checksum_sidecar: bool,
+    pub preserve_formatting: bool,
+    /// Like `preserve_formatting`, and also keep the value list as written,
+    /// replacing only the values that change.
+    pub no_header_rebuild: bool,
+    pub summary_json: Option<PathBuf>,
+    /// Run reading, transforming and writing on separate threads, with this
+    /// many transform workers.
+    pub threads_io: Option<usize>,
+    /// Write a unified diff of the rewritten statements to this path.
+    pub diff: Option<PathBuf>,
+    /// Cache this many recent fake values (0 disables the cache).
+    pub cache_size: usize,
+    /// Abort with [`Failure::MemoryLimit`] once `unique` and `tokenize`
+    /// state is estimated to exceed this many bytes.
+    pub max_memory: Option<usize>,
+    /// How to read the inputs.
+    pub format: InputFormat,
+    /// The config table for CSV/TSV inputs; defaults to the file's stem.
+    pub table: Option<String>,
+    /// Leave this seeded fraction of statements of configured tables
+    /// untransformed, tagged with [`KEPT_SAMPLE_TAG`]. For debugging
+    /// against known originals only; it copies real values to the output.
+    pub unsafe_keep_sample: Option<f64>,
+    /// Continue from the checkpoint an interrupted run left next to each
+    /// output (see `resume.rs`).
+    pub resume: bool,
+    /// Also anonymize the literals compared against in `UPDATE`/`DELETE`
+    /// `WHERE` clauses.
+    pub scrub_where: bool,
+    /// Record every original and its pseudonym in `RunReport::pseudonyms`.
+    /// Memory grows with the number of distinct values.
+    pub collect_pseudonyms: bool,
+    /// Record in `RunReport::pseudonyms` only values that change, and at
+    /// most this many distinct ones per column, in order of appearance.
+    pub preview_values: Option<usize>,
+    /// Conventions of the database that wrote the dump.
+    pub dialect: SqlDialect,
+    /// A file of `CREATE TABLE` statements read before the input, so a
+    /// data-only dump's `INSERT`s and `COPY` blocks without a column list
+    /// can still be matched to their columns.
+    pub schema: Option<PathBuf>,
+    /// A mapping written by an earlier run's `mapping_out`: rows of tables
+    /// with a `key` that haven't changed since get their previous values.
+    pub baseline: Option<PathBuf>,
+    /// Record the rows of tables with a `key` here, for a later `baseline`.
+    pub mapping_out: Option<PathBuf>,
+    /// Replace outputs that already exist instead of refusing to run.
+    pub overwrite: bool,
+    /// Flush the output after every this many chunks (statements, or
+    /// lines outside statements), so a reader downstream sees them without
+    /// waiting for the input to end. Only the serial loop honors it.
+    pub flush_every: Option<usize>,
+}
+
+/// The input or output path that stands for stdin or stdout.
+pub const STDIO: &str = "-";
+
+/// Whether `path` is [`STDIO`].
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO)
+}
+
+/// Appended to statements kept by `unsafe_keep_sample`.
+pub const KEPT_SAMPLE_TAG: &str = "/* ghostdb: unsafe-keep-sample, NOT anonymized */";
+
+/// What `run` reads: SQL dumps, or delimited exports of a single table
+/// with a header row.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    #[default]
+    Sql,
+    Csv,
+    Tsv,
+}
+
+impl InputFormat {
+    /// The field delimiter, or `None` for SQL.
+    pub fn delimiter(self) -> Option<u8> {
+        match self {
+            InputFormat::Sql => None,
+            InputFormat::Csv => Some(b','),
+            InputFormat::Tsv => Some(b'\t'),
+        }
+    }
+}
+
+/// The database a SQL dump comes from, for conventions that would be wrong
+/// to assume of every dump.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    #[default]
+    Generic,
+    /// Oracle folds unquoted identifiers to uppercase, so `INSERT INTO
+    /// HR.EMPLOYEES (ID, EMAIL)` has to find a config written as
+    /// `employees: {columns: {id: ..., email: ...}}`: table and column names
+    /// are matched ignoring case, as with `case_insensitive_tables`.
+    Oracle,
+}
+
+impl SqlDialect {
+    /// `config` as this dialect needs it.
+    fn adapt(self, config: &AppConfig) -> Cow<'_, AppConfig> {
+        match self {
+            SqlDialect::Oracle if !config.case_insensitive_tables => Cow::Owned(AppConfig {
+                case_insensitive_tables: true,
+                ..config.clone()
+            }),
+            _ => Cow::Borrowed(config),
+        }
+    }
+}
+
+/// Statements handed to a pipeline worker at a time.
+const PIPELINE_BATCH: usize = 256;
+
+pub(crate) const KEEP: ColumnStrategy = ColumnStrategy::Keep;
+/// Stands in for the strategy of a `'NULL'` string under
+/// `quoted_null: normalize`; always yields SQL `NULL`.
+const NULLIFY: ColumnStrategy = ColumnStrategy::RandomNull { probability: 1.0 };
+const NO_OPTIONS: ColumnOptions = ColumnOptions {
+    max_len: None,
+    unique: false,
+    when: None,
+    domain_from: None,
+    seed_from: None,
+    must_match: None,
+};
+
+/// The transform workers a run gets when none are asked for: one per
+/// available CPU. `None`, a serial run, on a single CPU and where the run
+/// would be serial anyway: for `--diff`, `--resume`, delimited input and
+/// configs whose output depends on input order.
+pub fn default_threads_io(config: &AppConfig, options: &RunOptions) -> Option<usize> {
+    let serial = options.format != InputFormat::Sql
+        || options.diff.is_some()
+        || options.resume
+        || config.has_order_dependent_columns();
+    if serial {
+        return None;
+    }
+    thread::available_parallelism().ok().map(usize::from).filter(|&n| n > 1)
+}
+
+pub fn run_processing(
+    input: &Path,
+    output: &Path,
+    config: &AppConfig,
+    seed: u64,
+    options: &RunOptions,
+) -> Result<()> {
+    run_batch(&[(input.to_path_buf(), output.to_path_buf())], config, seed, options)
+}
+
+/// Refuses to run before anything is written if an output is one of the
+/// inputs, or already exists and `overwrite` isn't set. `resume` continues
+/// an existing output, so it is allowed to exist then. stdin and stdout
+/// can't be resumed, and stdout can't also carry checksums.
+fn check_outputs(jobs: &[(PathBuf, PathBuf)], options: &RunOptions) -> Result<()> {
+    for (input, output) in jobs {
+        if is_stdio(input) || is_stdio(output) {
+            if jobs.len() > 1 {
+                return Err(anyhow!("stdin and stdout (`-`) only work with a single input").context(Failure::Config));
+            }
+            if options.resume {
+                return Err(anyhow!("--resume needs an input and an output file, not stdin or stdout")
+                    .context(Failure::Config));
+            }
+            if is_stdio(output) && options.checksum {
+                return Err(anyhow!("--checksum prints to stdout, which carries the output; write to a file")
+                    .context(Failure::Config));
+            }
+        }
+        if is_stdio(output) || !output.exists() {
+            continue;
+        }
+        // Creating the output would truncate the input before it is read.
+        let same = |path: &Path| path.canonicalize().ok();
+        if jobs.iter().any(|(input, _)| same(input).is_some() && same(input) == same(output)) {
+            return Err(anyhow!("Output {:?} is also an input; write to another file", output)
+                .context(Failure::Io));
+        }
+        if !options.overwrite && !options.resume {
+            return Err(anyhow!(
+                "Output {:?} already exists (input {:?}); pass --force to overwrite it",
+                output,
+                input
+            )
+            .context(Failure::Io));
+        }
+    }
+    Ok(())
+}
+
+/// Anonymizes several `(input, output)` pairs with one shared transformer,
+/// so pseudonyms stay consistent across files.
+pub fn run_batch(
+    jobs: &[(PathBuf, PathBuf)],
+    config: &AppConfig,
+    seed: u64,
+    options: &RunOptions,
+) -> Result<()> {
+    check_outputs(jobs, options)?;
+    let incremental = if options.baseline.is_some() || options.mapping_out.is_some() {
+        if options.baseline.is_some() && config.has_order_dependent_columns() {
+            return Err(anyhow!(
+                "--baseline can't be used with `unique` or `tokenize` columns: rows taken from the baseline would be missing from their state"
+            )
+            .context(Failure::Config));
+        }
+        if options.resume && options.mapping_out.is_some() {
+            return Err(anyhow!("--mapping-out can't be combined with --resume").context(Failure::Config));
+        }
+        Some(Incremental::new(
+            options.baseline.as_deref(),
+            options.mapping_out.as_deref(),
+            config,
+            seed,
+        )?)
+    } else {
+        None
+    };
+    let transformer = Transformer::new(seed)
+        .with_cache(options.cache_size)
+        .with_memory_limit(options.max_memory)
+        .with_incremental(incremental);
+    let mut report = RunReport::default();
+    let mut diff = match &options.diff {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create diff file: {:?}", path))
+                .context(Failure::Io)?;
+            Some(UnifiedDiff::new(BufWriter::new(file)))
+        }
+        None => None,
+    };
+
+    for (input, output) in jobs {
+        let file_report =
+            process_file(&transformer, input, output, config, options, diff.as_mut())?;
+        if jobs.len() == 1 {
+            report = file_report;
+        } else {
+            info!(
+                event = "file_done",
+                file:% = input.display(),
+                processed_lines = file_report.processed_lines,
+                anonymized_statements = file_report.anonymized_statements;
+                "{:?}: processed {} lines, anonymized {} statements.",
+                input, file_report.processed_lines, file_report.anonymized_statements
+            );
+            report.merge_file(&input.display().to_string(), file_report);
+        }
+    }
+    report.unparseable_values = transformer.unparseable_values();
+    if let Some(incremental) = transformer.incremental() {
+        incremental.finish()?;
+        if options.baseline.is_some() {
+            info!(
+                event = "baseline",
+                reused_rows = incremental.reused_rows();
+                "Reused {} unchanged row(s) from the baseline.",
+                incremental.reused_rows()
+            );
+        }
+    }
+    info!(
+        event = "run_done",
+        processed_lines = report.processed_lines,
+        insert_statements = report.insert_statements,
+        anonymized_statements = report.anonymized_statements,
+        skipped_statements = report.skipped_statements,
+        unconfigured_columns = report.unconfigured_columns.len(),
+        unparseable_values = report.unparseable_values,
+        warnings = report.warnings.len() + report.suppressed_warnings;
+        "Done! Processed {} lines. Anonymized {} statements.",
+        report.processed_lines, report.anonymized_statements
+    );
+    if report.unparseable_values > 0 {
+        warn!(
+            event = "unparseable_values",
+            count = report.unparseable_values;
+            "{} value(s) couldn't be parsed by date_shift or geo_fuzz and were handled by their `on_error` setting.",
+            report.unparseable_values
+        );
+    }
+
+    if let Some(diff) = diff {
+        diff.into_inner()
+            .flush()
+            .context("Failed to write diff")
+            .context(Failure::Io)?;
+    }
+    if let Some(path) = &options.summary_json {
+        report.write_json(path)?;
+    }
+
+    let unconfigured = &report.unconfigured_columns;
+    if !unconfigured.is_empty() {
+        if options.fail_on_unconfigured {
+            for (_, output) in jobs.iter().filter(|(_, output)| !is_stdio(output)) {
+                let _ = std::fs::remove_file(output);
+            }
+            let list: Vec<String> = unconfigured.iter().map(|c| format!("  - {}", c)).collect();
+            return Err(anyhow!(
+                "Found {} unconfigured column(s), refusing to pass them through:\n{}",
+                unconfigured.len(),
+                list.join("\n")
+            )
+            .context(Failure::Validation));
+        }
+        warn!(
+            event = "unconfigured_columns",
+            count = unconfigured.len();
+            "{} column(s) had no config entry and were kept as-is.",
+            unconfigured.len()
+        );
+    }
+    if report.skipped_statements > 0 {
+        return Err(anyhow!(
+            "{} statement(s) of configured tables could not be parsed and were copied unchanged (see --summary-json or RUST_LOG=warn)",
+            report.skipped_statements
+        )
+        .context(Failure::Incomplete));
+    }
+    Ok(())
+}
+
+pub fn process_file(
+    transformer: &Transformer,
+    input: &Path,
+    output: &Path,
+    config: &AppConfig,
+    options: &RunOptions,
+    mut diff: Option<&mut UnifiedDiff<BufWriter<File>>>,
+) -> Result<RunReport> {
+    let mut input_file = if is_stdio(input) {
+        None
+    } else {
+        Some(
+            File::open(input)
+                .with_context(|| format!("Failed to open input file: {:?}", input))
+                .context(Failure::Io)?,
+        )
+    };
+    // Runs over SQL files without a diff record their progress.
+    let plain_sql = options.format == InputFormat::Sql && diff.is_none();
+    let checkpoints = if plain_sql && input_file.is_some() && !is_stdio(output) {
+        Some(Checkpointing::new(input, output, config, transformer.seed(), options)?)
+    } else if options.resume {
+        return Err(anyhow!("--resume only works for SQL input without --diff").context(Failure::Config));
+    } else {
+        None
+    };
+    let resume_from = checkpoints.as_ref().and_then(|c| c.from.as_ref());
+
+    let output_file: Box<dyn Write + Send> = match (resume_from, input_file.as_mut()) {
+        _ if is_stdio(output) => Box::new(io::stdout()),
+        (Some(from), Some(input_file)) => {
+            info!(event = "resume", line = from.lines; "Resuming {:?} after line {}", input, from.lines);
+            input_file
+                .seek(SeekFrom::Start(from.input_offset))
+                .context("Failed to seek input")
+                .context(Failure::Io)?;
+            Box::new(reopen_output(output, from.output_offset)?)
+        }
+        _ => Box::new(
+            File::create(output)
+                .with_context(|| format!("Failed to create output file: {:?}", output))
+                .context(Failure::Io)?,
+        ),
+    };
+    let input_file: Box<dyn Read> = match input_file {
+        Some(file) => Box::new(file),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut reader = BufReader::new(HashingReader::new(input_file, options.checksum));
+    let mut writer = BufWriter::new(HashingWriter::new(output_file, options.checksum));
+
+    if let Some(diff) = diff.as_mut() {
+        diff.begin_file(&input.display().to_string(), &output.display().to_string())
+            .context("Failed to write diff")
+            .context(Failure::Io)?;
+    }
+
+    let report = match options.format.delimiter() {
+        Some(delimiter) => {
+            if diff.is_some() || options.threads_io.is_some() || options.unsafe_keep_sample.is_some() {
+                warn!("--diff, --threads-io and --unsafe-keep-sample only apply to SQL input; ignoring them");
+            }
+            let table = match &options.table {
+                Some(table) => table.clone(),
+                None if is_stdio(input) => {
+                    return Err(anyhow!("Delimited input from stdin needs --table").context(Failure::Config));
+                }
+                None => input.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+            };
+            delimited::process_delimited(
+                transformer,
+                &mut reader,
+                &mut writer,
+                config,
+                options,
+                &table,
+                delimiter,
+            )?
+        }
+        None => match options.threads_io {
+            Some(_) if diff.is_some() => {
+                warn!("--diff needs the statements in order; ignoring --threads-io");
+                process_serial(transformer, &mut reader, &mut writer, config, options, diff, None)?
+            }
+            Some(_) if config.has_order_dependent_columns() => {
+                warn!("`unique` and `tokenize` columns depend on input order; ignoring --threads-io");
+                process_serial(transformer, &mut reader, &mut writer, config, options, diff, checkpoints.as_ref())?
+            }
+            Some(_) if resume_from.is_some() => {
+                warn!("A resumed run continues serially; ignoring --threads-io");
+                process_serial(transformer, &mut reader, &mut writer, config, options, diff, checkpoints.as_ref())?
+            }
+            Some(workers) => pipelined(
+                transformer,
+                &mut reader,
+                &mut writer,
+                config,
+                options,
+                workers,
+                checkpoints.as_ref(),
+            )?,
+            None => process_serial(
+                transformer,
+                &mut reader,
+                &mut writer,
+                config,
+                options,
+                diff,
+                checkpoints.as_ref(),
+            )?,
+        },
+    };
+
+    let output_digest = writer
+        .into_inner()
+        .map_err(|e| e.into_error())
+        .context("Failed to flush output buffer")
+        .context(Failure::Io)?
+        .finish();
+    let input_digest = reader.into_inner().finish();
+    if let (Some(input_digest), Some(output_digest)) = (input_digest, output_digest) {
+        println!("SHA-256 input:  {}  {}", input_digest, input.display());
+        println!("SHA-256 output: {}  {}", output_digest, output.display());
+
+        if options.checksum_sidecar {
+            let mut sidecar = output.as_os_str().to_os_string();
+            sidecar.push(".sha256");
+            let contents = format!(
+                "{}  {}\n{}  {}\n",
+                input_digest,
+                input.display(),
+                output_digest,
+                output.display()
+            );
+            std::fs::write(&sidecar, contents)
+                .with_context(|| format!("Failed to write checksum file: {:?}", sidecar))
+                .context(Failure::Io)?;
+        }
+    }
+    if let Some(checkpoints) = &checkpoints {
+        checkpoints.finish()?;
+    }
+    Ok(report)
+}
+
+/// Progress recording for `process_serial` and the pipeline's writer, and
+/// the checkpoint a resumed run starts from.
+struct Checkpointing<'a> {
+    input: &'a Path,
+    path: PathBuf,
+    fingerprint: String,
+    from: Option<Checkpoint>,
+}
+
+impl<'a> Checkpointing<'a> {
+    fn new(
+        input: &'a Path,
+        output: &Path,
+        config: &AppConfig,
+        seed: u64,
+        options: &RunOptions,
+    ) -> Result<Self> {
+        let path = resume::state_path(output);
+        let fingerprint = resume::fingerprint(input, config, seed)?;
+        let from = if options.resume {
+            match Checkpoint::load(&path)? {
+                Some(checkpoint) if checkpoint.fingerprint != fingerprint => {
+                    return Err(anyhow!(
+                        "{:?} was taken with a different input, config or seed; run without --resume to start over",
+                        path
+                    )
+                    .context(Failure::Config));
+                }
+                Some(_) if options.checksum => {
+                    return Err(anyhow!("--checksum needs the whole input; it can't be combined with --resume")
+                        .context(Failure::Config));
+                }
+                Some(_) if config.has_order_dependent_columns() => {
+                    return Err(anyhow!(
+                        "`unique` and `tokenize` columns remember every value seen, which a resumed run has lost; run without --resume to start over"
+                    )
+                    .context(Failure::Config));
+                }
+                Some(checkpoint) => Some(checkpoint),
+                None => {
+                    warn!("No checkpoint for {:?}; starting from the beginning", output);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        Ok(Self {
+            input,
+            path,
+            fingerprint,
+            from,
+        })
+    }
+
+    fn save(&self, input_offset: u64, output_offset: u64, lines: usize) -> Result<()> {
+        Checkpoint {
+            fingerprint: self.fingerprint.clone(),
+            input_offset,
+            output_offset,
+            lines,
+        }
+        .save(&self.path)
+    }
+
+    /// Removes the checkpoint once the file is done.
+    fn finish(&self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(anyhow!(e))
+                .with_context(|| format!("Failed to remove checkpoint {:?}", self.path))
+                .context(Failure::Io),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An empty report, collecting pseudonyms if `options` asks for them.
+pub(crate) fn new_report(options: &RunOptions) -> RunReport {
+    RunReport {
+        pseudonyms: (options.collect_pseudonyms || options.preview_values.is_some()).then(BTreeMap::new),
+        pseudonym_limit: options.preview_values,
+        ..RunReport::default()
+    }
+}
+
+/// `text` with each range in `edits` (ascending, non-overlapping) replaced.
+fn splice(text: &str, edits: Vec<(Range<usize>, String)>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (range, replacement) in edits {
+        out.push_str(&text[copied..range.start]);
+        out.push_str(&replacement);
+        copied = range.end;
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// Opens an interrupted run's output for appending, dropping anything
+/// written after the checkpoint.
+fn reopen_output(output: &Path, len: u64) -> Result<File> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(output)
+        .with_context(|| format!("Failed to reopen output file: {:?}", output))
+        .context(Failure::Io)?;
+    file.set_len(len)
+        .and_then(|_| file.seek(SeekFrom::End(0)))
+        .with_context(|| format!("Failed to truncate output file: {:?}", output))
+        .context(Failure::Io)?;
+    Ok(file)
+}
+
+/// The table name to emit: the configured pseudonym (keeping any schema
+/// prefix and quoting) or the name as written in the dump.
+fn output_table_name(table_token: &str, table_config: &TableConfig) -> String {
+    match &table_config.rename {
+        Some(new_name) => rename::rename_table(table_token, new_name),
+        None => table_token.to_string(),
+    }
+}
+
+/// Rewrites single statements; shared by the serial loop and the
+/// `--threads-io` pipeline workers.
+pub(crate) struct StatementProcessor<'a> {
+    transformer: &'a Transformer,
+    config: &'a AppConfig,
+    options: &'a RunOptions,
+    insert_regex: Regex,
+    /// `INSERT INTO t VALUES (...)` without a column list, as written by
+    /// SQLite's `.dump`; columns come from the table's `CREATE TABLE`.
+    insert_positional_regex: Regex,
+    insert_set_regex: Regex,
+    /// The table of an `UPDATE` or `DELETE`, for `--scrub-where`.
+    predicate_regex: Regex,
+    /// `type_rules` keyed by normalized type name.
+    type_rules: HashMap<String, &'a ColumnStrategy>,
+    /// Column names and types from the `CREATE TABLE`s seen so far, keyed
+    /// by table name (both qualified and unqualified).
+    /// Shared with the chunks that took a snapshot of them, and copied on
+    /// the next change.
+    schemas: RwLock<Arc<Schemas>>,
+    /// Stands in for tables that have no config entry but a schema, so
+    /// their columns can still pick up type rules.
+    unconfigured_table: TableConfig,
+    /// `table.column`s already logged as lacking type information.
+    untyped_logged: Mutex<HashSet<String>>,
+    /// `enum` columns already logged as having no known `ENUM` type.
+    enum_logged: Mutex<HashSet<String>>,
+    /// Compiled `protected_columns`.
+    protected: Vec<Regex>,
+    /// `table.column`s already logged as protected from their strategy.
+    protected_logged: Mutex<HashSet<String>>,
+}
+
+impl<'a> StatementProcessor<'a> {
+    pub(crate) fn new(
+        transformer: &'a Transformer,
+        config: &'a AppConfig,
+        options: &'a RunOptions,
+    ) -> Result<Self> {
+        let processor = Self {
+            transformer,
+            config,
+            options,
+            insert_regex: Regex::new(&format!(
+                r"(?is)^({})\s+({})\s*\((.*?)\)\s*VALUES\s*\((.*)\)$",
+                INSERT_PREFIX, TABLE_NAME
+            ))
+            .expect("Invalid regex pattern"),
+            insert_positional_regex: Regex::new(&format!(
+                r"(?is)^({})\s+({})\s*VALUES\s*\((.*)\)$",
+                INSERT_PREFIX, TABLE_NAME
+            ))
+            .expect("Invalid regex pattern"),
+            insert_set_regex: Regex::new(&format!(
+                r"(?is)^({})\s+({})\s+SET\s+(.*)$",
+                INSERT_SET_PREFIX, TABLE_NAME
+            ))
+            .expect("Invalid regex pattern"),
+            predicate_regex: Regex::new(&format!(r"(?is)^{}\s+({})", PREDICATE_PREFIX, TABLE_NAME))
+                .expect("Invalid regex pattern"),
+            type_rules: config
+                .type_rules
+                .iter()
+                .map(|(sql_type, strategy)| (normalize_type(sql_type), strategy))
+                .collect(),
+            schemas: RwLock::new(Arc::new(Schemas::new())),
+            unconfigured_table: TableConfig::default(),
+            untyped_logged: Mutex::new(HashSet::new()),
+            enum_logged: Mutex::new(HashSet::new()),
+            protected: config.protected_patterns()?,
+            protected_logged: Mutex::new(HashSet::new()),
+        };
+        if let Some(path) = &options.schema {
+            processor.load_schema(path)?;
+        }
+        Ok(processor)
+    }
+
+    /// Records the `CREATE TABLE`s of a schema file, as if they came before
+    /// the input. A `CREATE TABLE` in the input replaces its table's.
+    fn load_schema(&self, path: &Path) -> Result<()> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open schema file: {:?}", path))
+            .context(Failure::Io)?;
+        let mut tables = 0;
+        for chunk in split_statements(BufReader::new(file).lines()) {
+            let chunk = chunk.context("Error reading line from schema file").context(Failure::Io)?;
+            tables += usize::from(chunk.kind == ChunkKind::CreateTable);
+            self.observe(&chunk);
+        }
+        if tables == 0 {
+            return Err(anyhow!("Schema file {:?} has no CREATE TABLE statements", path).context(Failure::Config));
+        }
+        Ok(())
+    }
+
+    /// Records what later statements need to know from this chunk (column
+    /// names and types from `CREATE TABLE`). Must be called for every chunk, in input
+    /// order, before any later chunk is processed.
+    fn observe(&self, chunk: &Chunk) {
+        if chunk.kind != ChunkKind::CreateTable {
+            return;
+        }
+        if let Some(schema) = parse_create_table(&chunk.text) {
+            let schema = Arc::new(schema);
+            let mut guard = self.schemas.write().unwrap_or_else(|e| e.into_inner());
+            let schemas = Arc::make_mut(&mut guard);
+            if let Some((_, short)) = schema.name.rsplit_once('.') {
+                schemas.insert(short.to_string(), Arc::clone(&schema));
+            }
+            schemas.insert(schema.name.clone(), schema);
+        }
+    }
+
+    /// The `CREATE TABLE`s observed so far, as they are now.
+    fn snapshot(&self) -> Arc<Schemas> {
+        Arc::clone(&self.schemas.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// The schema of `table_name` as of `chunk`: from the chunk's snapshot
+    /// if it has one, else from the `CREATE TABLE`s observed so far.
+    fn schema_for(&self, chunk: &Chunk, table_name: &str) -> Option<Arc<TableSchema>> {
+        let name = unquote_qualified(table_name);
+        let schemas = match &chunk.schemas {
+            Some(snapshot) => Arc::clone(snapshot),
+            None => self.snapshot(),
+        };
+        schemas
+            .get(&name)
+            .or_else(|| name.rsplit_once('.').and_then(|(_, short)| schemas.get(short)))
+            .cloned()
+    }
+
+    /// The config entry for a table (tried as written, then unquoted), or a
+    /// blank one if only type rules apply to it.
+    pub(crate) fn table_config(&self, table_name: &str, schema: Option<&TableSchema>) -> Option<&TableConfig> {
+        self.config
+            .find_table(table_name)
+            .or_else(|| self.config.find_table(&unquote_qualified(table_name)))
+            .or_else(|| {
+                schema
+                    .filter(|_| !self.type_rules.is_empty())
+                    .map(|_| &self.unconfigured_table)
+            })
+    }
+
+    /// Looks up the strategy and options for each column: protected columns
+    /// are kept, others are looked up by name, then by the column's declared
+    /// type. Columns matching neither are recorded as unconfigured and fall
+    /// back to `Keep`.
+    pub(crate) fn resolve_rules<'t>(
+        &'t self,
+        table_config: &'t TableConfig,
+        schema: Option<&'t TableSchema>,
+        table_name: &str,
+        columns: &[String],
+        unconfigured: &mut BTreeSet<String>,
+    ) -> Vec<ColumnRule<'t>> {
+        let ci = self.config.case_insensitive_tables;
+        columns
+            .iter()
+            .map(|col_name| {
+                if self.is_protected(table_config, schema, table_name, col_name) {
+                    return ColumnRule {
+                        strategy: &KEEP,
+                        options: &NO_OPTIONS,
+                        enum_values: &[],
+                    };
+                }
+                ColumnRule {
+                    strategy: table_config
+                        .column(col_name, ci)
+                        .or_else(|| self.type_rule(schema, table_name, col_name))
+                        .unwrap_or_else(|| {
+                            unconfigured.insert(format!("{}.{}", table_name, col_name));
+                            &KEEP
+                        }),
+                    options: table_config.column_options(col_name, ci).unwrap_or(&NO_OPTIONS),
+                    enum_values: schema
+                        .and_then(|s| s.column(col_name))
+                        .map(|def| def.enum_values.as_slice())
+                        .unwrap_or(&[]),
+                }
+            })
+            .collect()
+    }
+
+    /// `text` with each column's declared type replaced by one that holds
+    /// what the column's strategy writes, where it doesn't already, or
+    /// `None` if every type fits.
+    fn fit_create_table(&self, text: &str) -> Option<String> {
+        let (schema, ranges) = parse_with_type_ranges(text)?;
+        let table_config = self.table_config(&schema.name, Some(&schema))?;
+        let columns: Vec<String> = schema.columns.iter().map(|c| c.name.clone()).collect();
+        let rules = self.resolve_rules(table_config, Some(&schema), &schema.name, &columns, &mut BTreeSet::new());
+        let mut out = text.to_string();
+        let mut changed = false;
+        // Back to front, so earlier ranges stay valid.
+        for ((def, rule), range) in schema.columns.iter().zip(&rules).zip(ranges).rev() {
+            let Some(fitted) = fitted_type(rule.strategy, &def.sql_type) else {
+                continue;
+            };
+            info!(event = "fitted_column", table = schema.name.as_str(), column = def.name.as_str(); "Changed the type of {}.{} from {} to {}", schema.name, def.name, def.sql_type, fitted);
+            out.replace_range(range, &fitted);
+            changed = true;
+        }
+        changed.then_some(out)
+    }
+
+    /// Whether `unsafe_keep_sample` picks this statement. The pick hashes
+    /// the seed and the statement text, so it doesn't depend on order.
+    fn keeps_sample(&self, chunk: &Chunk) -> bool {
+        let Some(fraction) = self.options.unsafe_keep_sample else {
+            return false;
+        };
+        let mut hasher = DefaultHasher::new();
+        self.transformer.seed().hash(&mut hasher);
+        chunk.text.hash(&mut hasher);
+        (hasher.finish() as f64 / u64::MAX as f64) < fraction
+    }
+
+    /// Copies a statement picked by `keeps_sample` through, tagged.
+    fn write_kept_sample<W: Write>(&self, line: &str, writer: &mut W, report: &mut RunReport) -> Result<()> {
+        report.kept_sample_statements += 1;
+        writeln!(writer, "{} {}", line, KEPT_SAMPLE_TAG)?;
+        Ok(())
+    }
+
+    /// Downgrades to `Keep` the columns whose `when` condition doesn't hold
+    /// for this row. Conditions are checked against the row's original
+    /// values, before any column is transformed, so several conditional
+    /// columns never see each other's output.
+    pub(crate) fn apply_guards(&self, rules: &mut [ColumnRule], columns: &[String], values: &[String]) {
+        let ci = self.config.case_insensitive_tables;
+        for rule in rules.iter_mut() {
+            let Some(when) = &rule.options.when else {
+                continue;
+            };
+            let holds = columns
+                .iter()
+                .position(|c| *c == when.column || (ci && c.eq_ignore_ascii_case(&when.column)))
+                .is_some_and(|i| {
+                    let value = values[i].trim();
+                    let is_quoted = value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'');
+                    if is_quoted {
+                        unescape_literal(&value[1..value.len() - 1]) == when.equals
+                    } else {
+                        !value.eq_ignore_ascii_case("NULL") && value == when.equals
+                    }
+                });
+            if !holds {
+                rule.strategy = &KEEP;
+            }
+        }
+    }
+
+    /// Applies `quoted_null` to the row: unless it is `data`, columns whose
+    /// value is the string `'NULL'` are kept, or with `normalize` (and
+    /// `nullify`) turned into SQL `NULL`.
+    pub(crate) fn apply_quoted_null(&self, rules: &mut [ColumnRule], values: &[String], nullify: bool) {
+        let replacement = match self.config.quoted_null {
+            QuotedNull::Data => return,
+            QuotedNull::Normalize if nullify => &NULLIFY,
+            _ => &KEEP,
+        };
+        for (rule, value) in rules.iter_mut().zip(values) {
+            let value = value.trim();
+            if value.len() == 6 && value.starts_with('\'') && value.ends_with('\'') && value[1..5].eq_ignore_ascii_case("NULL") {
+                rule.strategy = replacement;
+            }
+        }
+    }
+
+    /// Warns about `enum` columns whose value isn't a member of the column's
+    /// `ENUM` (it passes through unchanged), and once per column about
+    /// `enum` columns with no known `ENUM` type.
+    fn check_enum_values(
+        &self,
+        table_name: &str,
+        columns: &[String],
+        values: &[String],
+        rules: &[ColumnRule],
+        line: usize,
+        report: &mut RunReport,
+    ) {
+        for (i, rule) in rules.iter().enumerate() {
+            if !matches!(rule.strategy, ColumnStrategy::Enum) {
+                continue;
+            }
+            let key = format!("{}.{}", table_name, columns[i]);
+            if rule.enum_values.is_empty() {
+                let mut logged = self.enum_logged.lock().unwrap_or_else(|e| e.into_inner());
+                if logged.insert(key.clone()) {
+                    warn!(event = "unknown_enum", column = key.as_str(); "No ENUM type known for {}; values kept", key);
+                    report.warn(format!("{}: no ENUM type known, values kept", key));
+                }
+                continue;
+            }
+            let value = values[i].trim();
+            if value.eq_ignore_ascii_case("NULL") {
+                continue;
+            }
+            let member = unescape_literal(value.trim_matches('\''));
+            if !rule.enum_values.contains(&member) {
+                let message =
+                    format!("line {}: {} is not a member of the ENUM of {}; kept", line, value, key);
+                warn!(event = "enum_mismatch", line = line, column = key.as_str(); "{}", message);
+                report.warn(message);
+            }
+        }
+    }
+
+    /// Whether `protected_columns` matches the column, by name or as
+    /// `table.column` (qualified or not). Logs once per column when this
+    /// overrides a strategy that would otherwise have applied.
+    fn is_protected(
+        &self,
+        table_config: &TableConfig,
+        schema: Option<&TableSchema>,
+        table_name: &str,
+        column: &str,
+    ) -> bool {
+        if self.protected.is_empty() {
+            return false;
+        }
+        let table = unquote_qualified(table_name);
+        let short = table.rsplit('.').next().unwrap_or(&table);
+        let qualified = format!("{}.{}", table, column);
+        let unqualified = format!("{}.{}", short, column);
+        let matched = self.protected.iter().any(|re| {
+            re.is_match(column) || re.is_match(&qualified) || re.is_match(&unqualified)
+        });
+        if !matched {
+            return false;
+        }
+
+        let configured = table_config
+            .column(column, self.config.case_insensitive_tables)
+            .or_else(|| self.type_rule(schema, table_name, column))
+            .filter(|strategy| !matches!(strategy, ColumnStrategy::Keep));
+        if let Some(strategy) = configured {
+            let mut logged = self.protected_logged.lock().unwrap_or_else(|e| e.into_inner());
+            if logged.insert(qualified.clone()) {
+                warn!(
+                    event = "protected_column",
+                    column = qualified.as_str();
+                    "{} is in protected_columns; keeping it instead of applying {:?}",
+                    qualified, strategy
+                );
+            }
+        }
+        true
+    }
+
+    fn type_rule(
+        &self,
+        schema: Option<&TableSchema>,
+        table_name: &str,
+        column: &str,
+    ) -> Option<&'a ColumnStrategy> {
+        if self.type_rules.is_empty() {
+            return None;
+        }
+        let Some(def) = schema.and_then(|s| s.column(column)) else {
+            let key = format!("{}.{}", table_name, column);
+            let mut logged = self.untyped_logged.lock().unwrap_or_else(|e| e.into_inner());
+            if logged.insert(key.clone()) {
+                info!(event = "untyped_column", column = key.as_str(); "No CREATE TABLE type known for {}; type_rules not applied", key);
+            }
+            return None;
+        };
+        let sql_type = normalize_type(&def.sql_type);
+        self.type_rules.get(&sql_type).copied().or_else(|| {
+            let base = sql_type.split_whitespace().next()?;
+            self.type_rules.get(base).copied()
+        })
+    }
+
+    /// Writes the (possibly rewritten) chunk to `writer`, recording what
+    /// was done in `report`.
+    fn process_chunk<W: Write>(
+        &self,
+        chunk: &Chunk,
+        writer: &mut W,
+        report: &mut RunReport,
+    ) -> Result<()> {
+        let line = chunk.text.as_str();
+        // The regexes see the statement without its `;` or trailing
+        // comments; the rewrites copy whatever follows from `line`.
+        let body = statement_body(line);
+        if chunk.kind == ChunkKind::Insert {
+            report.insert_statements += 1;
+        }
+        if chunk.kind == ChunkKind::CreateTable
+            && (self.config.rename_create_table || self.config.fit_create_table)
+        {
+            // Types are fitted first, while the columns still have the
+            // names their rules are configured under.
+            let mut text = line.to_string();
+            if self.config.fit_create_table {
+                if let Some(fitted) = self.fit_create_table(&text) {
+                    text = fitted;
+                }
+            }
+            if self.config.rename_create_table {
+                if let Some(renamed) = rename::rewrite_create_table(&text, self.config) {
+                    text = renamed;
+                }
+            }
+            writeln!(writer, "{}", text)?;
+        } else if matches!(chunk.kind, ChunkKind::Update | ChunkKind::Delete) && self.options.scrub_where {
+            self.scrub_where(chunk, body, writer, report)?;
+        } else if chunk.kind == ChunkKind::Copy {
+            self.rewrite_copy_header(chunk, body, writer, report)?;
+        } else if let (ChunkKind::CopyData, Some(header)) = (chunk.kind, &chunk.copy) {
+            self.rewrite_copy_row(chunk, header, writer, report)?;
+        } else if chunk.kind != ChunkKind::Insert {
+            writeln!(writer, "{}", line)?;
+        } else if let Some(caps) = self.insert_regex.captures(body) {
+            let cols_part = caps.get(3).map(|m| m.as_str()).unwrap_or("");
+            let columns = split_column_list(cols_part);
+            self.rewrite_insert(chunk, &caps, columns, true, writer, report)?;
+        } else if let Some(caps) = self.insert_positional_regex.captures(body) {
+            let table_full_name = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+            match self.schema_for(chunk, table_full_name) {
+                Some(schema) => {
+                    let columns = schema.columns.iter().map(|c| c.name.clone()).collect();
+                    self.rewrite_insert(chunk, &caps, columns, false, writer, report)?;
+                }
+                None => {
+                    if self.table_config(table_full_name, None).is_some() {
+                        warn!(
+                            event = "skipped_statement",
+                            line = chunk.first_line,
+                            table = table_full_name;
+                            "No CREATE TABLE seen for {}; INSERT without column list left unchanged at line {}",
+                            table_full_name, chunk.first_line
+                        );
+                        report.warn(format!(
+                            "line {}: INSERT into {} has no column list and no CREATE TABLE was seen; left unchanged",
+                            chunk.first_line, table_full_name
+                        ));
+                        report.skipped_statements += 1;
+                    }
+                    writeln!(writer, "{}", line)?;
+                }
+            }
+        } else if let Some(caps) = self.insert_set_regex.captures(body) {
+            let prefix = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+            let table_full_name = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+            let set_part = caps.get(3).map(|m| m.as_str()).unwrap_or("");
+            let assignments = Transformer::parse_assignments(set_part);
+            let schema = self.schema_for(chunk, table_full_name);
+            let columns: Vec<String> = assignments
+                .iter()
+                .map(|(col, _)| unquote_identifier(col).into_owned())
+                .collect();
+
+            if assignments.is_empty() {
+                warn!(
+                    event = "skipped_statement",
+                    line = chunk.first_line,
+                    table = table_full_name;
+                    "Could not parse SET assignments. Skipping line {}",
+                    chunk.first_line
+                );
+                report.warn(format!(
+                    "line {}: could not parse SET assignments in {}",
+                    chunk.first_line, table_full_name
+                ));
+                if self.table_config(table_full_name, None).is_some() {
+                    report.skipped_statements += 1;
+                }
+                writeln!(writer, "{}", line)?;
+            } else if self.table_config(table_full_name, schema.as_deref()).is_some()
+                && self.keeps_sample(chunk)
+            {
+                self.write_kept_sample(line, writer, report)?;
+            } else if let Some(table_config) = self.table_config(table_full_name, schema.as_deref()) {
+                let values: Vec<String> = assignments.iter().map(|(_, v)| v.clone()).collect();
+                let mut rules = self.resolve_rules(
+                    table_config,
+                    schema.as_deref(),
+                    table_full_name,
+                    &columns,
+                    &mut report.unconfigured_columns,
+                );
+                self.apply_guards(&mut rules, &columns, &values);
+                self.apply_quoted_null(&mut rules, &values, true);
+                self.check_enum_values(
+                    table_full_name,
+                    &columns,
+                    &values,
+                    &rules,
+                    chunk.first_line,
+                    report,
+                );
+                let new_values = self.transformer.transform_row(
+                    table_full_name,
+                    &columns,
+                    &values,
+                    &rules,
+                    table_config.seed,
+                    table_config.key.as_deref(),
+                )?;
+                report.record_row(table_full_name, &columns, &rules, &values, &new_values);
+                let ci = self.config.case_insensitive_tables;
+                let new_set_str = if self.options.no_header_rebuild {
+                    let mut edits = Vec::new();
+                    for (((span, (col, _)), name), (old, new)) in Transformer::value_spans(set_part)
+                        .into_iter()
+                        .zip(&assignments)
+                        .zip(&columns)
+                        .zip(values.iter().zip(&new_values))
+                    {
+                        let item = &set_part[span.clone()];
+                        let eq = item.find('=').unwrap_or(0);
+                        if let Some(new_name) = table_config.renamed_column(name, ci) {
+                            let col_end = span.start + item[..eq].trim_end().len();
+                            edits.push((span.start..col_end, requote(col, new_name)));
+                        }
+                        if old != new {
+                            let value = &item[eq + 1..];
+                            let value_start = span.end - value.trim_start().len();
+                            edits.push((value_start..span.end, new.clone()));
+                        }
+                    }
+                    splice(set_part, edits)
+                } else {
+                    assignments
+                        .iter()
+                        .zip(&columns)
+                        .zip(&new_values)
+                        .map(|(((col, _), name), val)| match table_config.renamed_column(name, ci) {
+                            Some(new_name) => format!("{} = {}", requote(col, new_name), val),
+                            None => format!("{} = {}", col, val),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                let out_table = output_table_name(table_full_name, table_config);
+                if self.options.preserve_formatting || self.options.no_header_rebuild {
+                    let table_range = caps.get(2).map(|m| m.range()).unwrap_or(0..0);
+                    let set_range = caps.get(3).map(|m| m.range()).unwrap_or(0..0);
+                    writeln!(
+                        writer,
+                        "{}{}{}{}{}",
+                        &line[..table_range.start],
+                        out_table,
+                        &line[table_range.end..set_range.start],
+                        new_set_str,
+                        &line[set_range.end..]
+                    )?;
+                } else {
+                    writeln!(
+                        writer,
+                        "{} {} SET {};",
+                        normalize_keywords(prefix),
+                        out_table,
+                        new_set_str
+                    )?;
+                }
+                report.record_statement(table_full_name);
+            } else {
+                for col_name in &columns {
+                    report.unconfigured_columns.insert(format!("{}.{}", table_full_name, col_name));
+                }
+                writeln!(writer, "{}", line)?;
+            }
+        } else {
+            writeln!(writer, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Anonymizes the literals compared against in an `UPDATE`/`DELETE`'s
+    /// `WHERE` clause, splicing them into the statement as written. They
+    /// get the same pseudonyms as in `INSERT`s, so the predicate still
+    /// selects the anonymized rows. `when` guards are not applied: the
+    /// rest of the row is unknown here, and scrubbing errs on the side of
+    /// privacy.
+    fn scrub_where<W: Write>(
+        &self,
+        chunk: &Chunk,
+        body: &str,
+        writer: &mut W,
+        report: &mut RunReport,
+    ) -> Result<()> {
+        let line = chunk.text.as_str();
+        let Some(table_full_name) = self.predicate_regex.captures(body).and_then(|c| c.get(1)) else {
+            writeln!(writer, "{}", line)?;
+            return Ok(());
+        };
+        let table_full_name = table_full_name.as_str();
+        let schema = self.schema_for(chunk, table_full_name);
+        let (Some(table_config), Some(start)) = (
+            self.table_config(table_full_name, schema.as_deref()),
+            predicate::where_start(body),
+        ) else {
+            writeln!(writer, "{}", line)?;
+            return Ok(());
+        };
+        let condition = &body[start..];
+        let comparisons = predicate::comparisons(condition);
+        if comparisons.is_empty() {
+            writeln!(writer, "{}", line)?;
+            return Ok(());
+        }
+
+        let columns: Vec<String> = comparisons.iter().map(|c| c.column.clone()).collect();
+        let values: Vec<String> = comparisons
+            .iter()
+            .map(|c| condition[c.value.clone()].to_string())
+            .collect();
+        let mut rules = self.resolve_rules(
+            table_config,
+            schema.as_deref(),
+            table_full_name,
+            &columns,
+            &mut report.unconfigured_columns,
+        );
+        // `col = NULL` would never match, so `normalize` only keeps here.
+        self.apply_quoted_null(&mut rules, &values, false);
+        let new_values = self.transformer.transform_row(
+            table_full_name,
+            &columns,
+            &values,
+            &rules,
+            table_config.seed,
+            table_config.key.as_deref(),
+        )?;
+
+        let edits = comparisons
+            .iter()
+            .zip(new_values)
+            .map(|(c, new_value)| (start + c.value.start..start + c.value.end, new_value))
+            .collect();
+        writeln!(writer, "{}", splice(line, edits))?;
+        report.scrubbed_predicates += 1;
+        Ok(())
+    }
+
+    /// Writes a `COPY` header with the table and its columns renamed as
+    /// configured. The rows that follow are rewritten one by one by
+    /// [`Self::rewrite_copy_row`].
+    fn rewrite_copy_header<W: Write>(
+        &self,
+        chunk: &Chunk,
+        body: &str,
+        writer: &mut W,
+        report: &mut RunReport,
+    ) -> Result<()> {
+        let line = chunk.text.as_str();
+        let Some(header) = copy::parse_header(body) else {
+            writeln!(writer, "{}", line)?;
+            return Ok(());
+        };
+        let schema = self.schema_for(chunk, &header.table);
+        let Some(table_config) = self.table_config(&header.table, schema.as_deref()) else {
+            for col_name in &header.columns {
+                report.unconfigured_columns.insert(format!("{}.{}", header.table, col_name));
+            }
+            writeln!(writer, "{}", line)?;
+            return Ok(());
+        };
+        if header.columns_range.is_none() && schema.is_none() {
+            warn!(
+                event = "skipped_statement",
+                line = chunk.first_line,
+                table = header.table.as_str();
+                "No CREATE TABLE seen for {}; COPY without column list left unchanged at line {}",
+                header.table, chunk.first_line
+            );
+            report.warn(format!(
+                "line {}: COPY into {} has no column list and no CREATE TABLE was seen; left unchanged",
+                chunk.first_line, header.table
+            ));
+            report.skipped_statements += 1;
+        }
+        let mut edits = vec![(header.table_range.clone(), output_table_name(&header.table, table_config))];
+        if let Some(range) = header.columns_range.clone().filter(|_| !table_config.rename_columns.is_empty()) {
+            let ci = self.config.case_insensitive_tables;
+            edits.push((range.clone(), rename::rename_column_list(&body[range], table_config, ci)));
+        }
+        writeln!(writer, "{}", splice(line, edits))?;
+        Ok(())
+    }
+
+    /// Rewrites one data row of a `COPY ... FROM stdin` block. Rows with the
+    /// wrong number of fields are copied unchanged and counted as skipped.
+    fn rewrite_copy_row<W: Write>(
+        &self,
+        chunk: &Chunk,
+        header: &CopyHeader,
+        writer: &mut W,
+        report: &mut RunReport,
+    ) -> Result<()> {
+        let line = chunk.text.as_str();
+        let table_full_name = header.table.as_str();
+        let schema = self.schema_for(chunk, table_full_name);
+        let columns: Vec<String> = match &schema {
+            _ if !header.columns.is_empty() => header.columns.clone(),
+            Some(schema) => schema.columns.iter().map(|c| c.name.clone()).collect(),
+            // Reported once, at the header.
+            None => {
+                writeln!(writer, "{}", line)?;
+                return Ok(());
+            }
+        };
+        let Some(table_config) = self.table_config(table_full_name, schema.as_deref()) else {
+            writeln!(writer, "{}", line)?;
+            return Ok(());
+        };
+        let fields = copy::split_row(line);
+        if fields.len() != columns.len() {
+            warn!(
+                event = "skipped_statement",
+                line = chunk.first_line,
+                table = table_full_name;
+                "Column count mismatch. Skipping line {}",
+                chunk.first_line
+            );
+            report.warn(format!(
+                "line {}: column count mismatch in {}",
+                chunk.first_line, table_full_name
+            ));
+            report.skipped_statements += 1;
+            writeln!(writer, "{}", line)?;
+            return Ok(());
+        }
+
+        let values: Vec<String> = fields.iter().map(|f| copy::to_literal(f.as_deref())).collect();
+        let mut rules = self.resolve_rules(
+            table_config,
+            schema.as_deref(),
+            table_full_name,
+            &columns,
+            &mut report.unconfigured_columns,
+        );
+        self.apply_guards(&mut rules, &columns, &values);
+        self.apply_quoted_null(&mut rules, &values, true);
+        self.check_enum_values(table_full_name, &columns, &values, &rules, chunk.first_line, report);
+        let new_values = self.transformer.transform_row(
+            table_full_name,
+            &columns,
+            &values,
+            &rules,
+            table_config.seed,
+            table_config.key.as_deref(),
+        )?;
+        report.record_row(table_full_name, &columns, &rules, &values, &new_values);
+        // Unchanged fields are copied as written, escapes and all.
+        let new_fields: Vec<String> = line
+            .split('\t')
+            .zip(values.iter().zip(&new_values))
+            .map(|(raw, (old, new))| {
+                if old == new {
+                    raw.to_string()
+                } else {
+                    copy::encode_field(copy::from_literal(new).as_deref())
+                }
+            })
+            .collect();
+        writeln!(writer, "{}", new_fields.join("\t"))?;
+        report.record_statement(table_full_name);
+        Ok(())
+    }
+
+    /// Rewrites an `INSERT ... VALUES` matched by `insert_regex` (groups:
+    /// prefix, table, column list, values) or, without `has_column_list`,
+    /// by `insert_positional_regex` (groups: prefix, table, values). Each
+    /// tuple of a multi-row `VALUES` list is anonymized as its own row.
+    fn rewrite_insert<W: Write>(
+        &self,
+        chunk: &Chunk,
+        caps: &Captures,
+        columns: Vec<String>,
+        has_column_list: bool,
+        writer: &mut W,
+        report: &mut RunReport,
+    ) -> Result<()> {
+        let line = chunk.text.as_str();
+        let prefix = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+        let table_full_name = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+        let cols_match = caps.get(3).filter(|_| has_column_list);
+        let vals_match = caps.get(if has_column_list { 4 } else { 3 });
+
+        let schema = self.schema_for(chunk, table_full_name);
+        if let Some(table_config) = self.table_config(table_full_name, schema.as_deref()) {
+            let vals_part = vals_match.map(|m| m.as_str()).unwrap_or("");
+            let Some(tuples) = Transformer::tuple_spans(vals_part) else {
+                warn!(
+                    event = "skipped_statement",
+                    line = chunk.first_line,
+                    table = table_full_name;
+                    "Malformed VALUES list. Skipping line {}",
+                    chunk.first_line
+                );
+                report.warn(format!(
+                    "line {}: malformed VALUES list in {}",
+                    chunk.first_line, table_full_name
+                ));
+                report.skipped_statements += 1;
+                writeln!(writer, "{}", line)?;
+                return Ok(());
+            };
+            let rows: Vec<Vec<String>> = tuples
+                .iter()
+                .map(|tuple| Transformer::parse_values(&vals_part[tuple.clone()]))
+                .collect();
+
+            if rows.iter().any(|values| values.len() != columns.len()) {
+                warn!(
+                    event = "skipped_statement",
+                    line = chunk.first_line,
+                    table = table_full_name;
+                    "Column count mismatch. Skipping line {}",
+                    chunk.first_line
+                );
+                report.warn(format!(
+                    "line {}: column count mismatch in {}",
+                    chunk.first_line, table_full_name
+                ));
+                report.skipped_statements += 1;
+                writeln!(writer, "{}", line)?;
+                return Ok(());
+            }
+            if self.keeps_sample(chunk) {
+                return self.write_kept_sample(line, writer, report);
+            }
+
+            let mut new_tuples = Vec::with_capacity(rows.len());
+            for (tuple, values) in tuples.iter().zip(&rows) {
+                let mut rules = self.resolve_rules(
+                    table_config,
+                    schema.as_deref(),
+                    table_full_name,
+                    &columns,
+                    &mut report.unconfigured_columns,
+                );
+                self.apply_guards(&mut rules, &columns, values);
+                self.apply_quoted_null(&mut rules, values, true);
+                self.check_enum_values(
+                    table_full_name,
+                    &columns,
+                    values,
+                    &rules,
+                    chunk.first_line,
+                    report,
+                );
+                let new_values = self.transformer.transform_row(
+                    table_full_name,
+                    &columns,
+                    values,
+                    &rules,
+                    table_config.seed,
+                    table_config.key.as_deref(),
+                )?;
+                report.record_row(table_full_name, &columns, &rules, values, &new_values);
+                let tuple_str = &vals_part[tuple.clone()];
+                new_tuples.push(if self.options.no_header_rebuild {
+                    let edits = Transformer::value_spans(tuple_str)
+                        .into_iter()
+                        .zip(values.iter().zip(&new_values))
+                        .filter(|(_, (old, new))| old != new)
+                        .map(|(span, (_, new))| (span, new.clone()))
+                        .collect();
+                    splice(tuple_str, edits)
+                } else {
+                    new_values.join(", ")
+                });
+            }
+            // Preserved formatting keeps what was written between the
+            // tuples; otherwise they are rejoined with `, `.
+            let new_values_str = if self.options.preserve_formatting || self.options.no_header_rebuild {
+                splice(vals_part, tuples.iter().cloned().zip(new_tuples).collect())
+            } else {
+                new_tuples.join("), (")
+            };
+            let out_table = output_table_name(table_full_name, table_config);
+            let out_cols = cols_match.map(|m| {
+                if table_config.rename_columns.is_empty() {
+                    m.as_str().to_string()
+                } else {
+                    let ci = self.config.case_insensitive_tables;
+                    rename::rename_column_list(m.as_str(), table_config, ci)
+                }
+            });
+            if self.options.preserve_formatting || self.options.no_header_rebuild {
+                let table_range = caps.get(2).map(|m| m.range()).unwrap_or(0..0);
+                let vals_range = vals_match.map(|m| m.range()).unwrap_or(0..0);
+                let cols_range = cols_match.map(|m| m.range()).unwrap_or(vals_range.start..vals_range.start);
+                writeln!(
+                    writer,
+                    "{}{}{}{}{}{}{}",
+                    &line[..table_range.start],
+                    out_table,
+                    &line[table_range.end..cols_range.start],
+                    out_cols.as_deref().unwrap_or(""),
+                    &line[cols_range.end..vals_range.start],
+                    new_values_str,
+                    &line[vals_range.end..]
+                )?;
+            } else if let Some(out_cols) = out_cols {
+                writeln!(
+                    writer,
+                    "{} {} ({}) VALUES ({});",
+                    normalize_keywords(prefix),
+                    out_table,
+                    out_cols,
+                    new_values_str
+                )?;
+            } else {
+                writeln!(
+                    writer,
+                    "{} {} VALUES ({});",
+                    normalize_keywords(prefix),
+                    out_table,
+                    new_values_str
+                )?;
+            }
+            report.record_statement(table_full_name);
+        } else {
+            for col_name in &columns {
+                report.unconfigured_columns.insert(format!("{}.{}", table_full_name, col_name));
+            }
+            writeln!(writer, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Anonymizes a dump read from `reader` into `writer`, returning the
+/// per-run report. The caller owns both streams (files, stdin/stdout or
+/// in-memory buffers) and is responsible for flushing `writer`.
+pub fn process_stream<R: BufRead, W: Write>(
+    transformer: &Transformer,
+    reader: R,
+    writer: &mut W,
+    config: &AppConfig,
+    options: &RunOptions,
+) -> Result<RunReport> {
+    process_serial::<_, _, io::Sink>(transformer, reader, writer, config, options, None, None)
+}
+
+/// Anonymizes statements from `reader` as they arrive, for use as a filter
+/// in a live pipeline (`ghostdb stream`): each statement is written as soon
+/// as its closing `;` has been read, `writer` is flushed after every
+/// `options.flush_every` chunks (every one by default) and once more at the
+/// end of the input. Fails like [`run_batch`] if statements of configured
+/// tables couldn't be parsed.
+pub fn run_stream<R: BufRead, W: Write>(
+    reader: R,
+    writer: &mut W,
+    config: &AppConfig,
+    seed: u64,
+    options: &RunOptions,
+) -> Result<()> {
+    let transformer = Transformer::new(seed)
+        .with_cache(options.cache_size)
+        .with_memory_limit(options.max_memory);
+    let options = RunOptions {
+        flush_every: Some(options.flush_every.unwrap_or(1).max(1)),
+        ..options.clone()
+    };
+    let mut report = process_stream(&transformer, reader, writer, config, &options)?;
+    writer.flush().context("Failed to write output").context(Failure::Io)?;
+    report.unparseable_values = transformer.unparseable_values();
+    info!(
+        event = "run_done",
+        processed_lines = report.processed_lines,
+        anonymized_statements = report.anonymized_statements;
+        "Done! Processed {} lines. Anonymized {} statements.",
+        report.processed_lines, report.anonymized_statements
+    );
+    if report.skipped_statements > 0 {
+        return Err(anyhow!(
+            "{} statement(s) of configured tables could not be parsed and were copied unchanged (see RUST_LOG=warn)",
+            report.skipped_statements
+        )
+        .context(Failure::Incomplete));
+    }
+    Ok(())
+}
+
+/// The serial loop behind [`process_stream`], optionally recording each
+/// statement's rewrite in `diff` and checkpointing its progress. `reader`
+/// must already be positioned at the checkpoint being resumed from.
+fn process_serial<R: BufRead, W: Write, D: Write>(
+    transformer: &Transformer,
+    reader: R,
+    writer: &mut W,
+    config: &AppConfig,
+    options: &RunOptions,
+    mut diff: Option<&mut UnifiedDiff<D>>,
+    checkpoints: Option<&Checkpointing>,
+) -> Result<RunReport> {
+    let config = options.dialect.adapt(config);
+    let processor = StatementProcessor::new(transformer, &config, options)?;
+    let from = checkpoints.and_then(|c| c.from.as_ref());
+    if let (Some(checkpoints), Some(from)) = (checkpoints, from) {
+        // Statements after the checkpoint may depend on earlier CREATE TABLEs.
+        let prefix = File::open(checkpoints.input)
+            .with_context(|| format!("Failed to open input file: {:?}", checkpoints.input))
+            .context(Failure::Io)?
+            .take(from.input_offset);
+        for chunk in split_statements(BufReader::new(prefix).lines()) {
+            processor.observe(&chunk.context("Error reading line from input").context(Failure::Io)?);
+        }
+    }
+
+    let mut processed_lines = from.map_or(0, |f| f.lines);
+    let mut report = new_report(options);
+    let mut buf = Vec::new();
+    let consumed = Cell::new(from.map_or(0, |f| f.input_offset));
+    let output_base = from.map_or(0, |f| f.output_offset);
+    let mut next_checkpoint = consumed.get() + CHECKPOINT_INTERVAL;
+    let mut writer = CountingWriter::new(writer);
+
+    let mut statements =
+        split_statements(CountingLines::new(reader, &consumed)).starting_after_line(processed_lines);
+    let mut unflushed = 0;
+    while let Some(chunk) = statements.next() {
+        let chunk = chunk
+            .context("Error reading line from input")
+            .context(Failure::Io)?;
+        if chunk.last_line / 100_000 > processed_lines / 100_000 {
+            info!(event = "progress", line = chunk.last_line; "Processed {} lines...", chunk.last_line);
+        }
+        processed_lines = chunk.last_line;
+        processor.observe(&chunk);
+        match diff.as_mut() {
+            Some(diff) => {
+                buf.clear();
+                processor.process_chunk(&chunk, &mut buf, &mut report)?;
+                writer.write_all(&buf)?;
+                diff.chunk(&chunk, &buf)
+                    .context("Failed to write diff")
+                    .context(Failure::Io)?;
+            }
+            None => processor.process_chunk(&chunk, &mut writer, &mut report)?,
+        }
+        if let Some(flush_every) = options.flush_every {
+            unflushed += 1;
+            if unflushed >= flush_every {
+                writer.flush().context("Failed to write output").context(Failure::Io)?;
+                unflushed = 0;
+            }
+        }
+
+        if let Some(checkpoints) = checkpoints {
+            if consumed.get() >= next_checkpoint && !statements.in_block_comment() && !statements.in_copy_data() {
+                writer.flush().context("Failed to write output").context(Failure::Io)?;
+                checkpoints.save(consumed.get(), output_base + writer.written, processed_lines)?;
+                next_checkpoint = consumed.get() + CHECKPOINT_INTERVAL;
+            }
+        }
+    }
+
+    report.processed_lines = processed_lines;
+    Ok(report)
+}
+
+/// Like [`process_stream`], but overlaps I/O with transformation: the
+/// calling thread reads and groups statements into batches, `workers`
+/// threads rewrite them, and a writer thread emits the results in input
+/// order (batches carry sequence numbers). All channels are bounded and the
+/// reader stays at most two batches per worker ahead of the writer, so a
+/// slow disk or a slow batch pushes back on the reader and memory stays
+/// bounded to a few batches per worker.
+pub fn process_stream_pipelined<R: BufRead, W: Write + Send>(
+    transformer: &Transformer,
+    reader: R,
+    writer: &mut W,
+    config: &AppConfig,
+    options: &RunOptions,
+    workers: usize,
+) -> Result<RunReport> {
+    pipelined(transformer, reader, writer, config, options, workers, None)
+}
+
+/// [`process_stream_pipelined`], checkpointing its progress when given
+/// `checkpoints`: the reader marks the first batch boundary past each
+/// [`CHECKPOINT_INTERVAL`] with the input read so far, and the writer saves
+/// the checkpoint once everything before it is written.
+fn pipelined<R: BufRead, W: Write + Send>(
+    transformer: &Transformer,
+    reader: R,
+    writer: &mut W,
+    config: &AppConfig,
+    options: &RunOptions,
+    workers: usize,
+    checkpoints: Option<&Checkpointing>,
+) -> Result<RunReport> {
+    let config = options.dialect.adapt(config);
+    let processor = StatementProcessor::new(transformer, &config, options)?;
+    let workers = workers.max(1);
+    // Batches carry their sequence number and, at a checkpoint, the input
+    // offset and line they end at.
+    type Mark = Option<(u64, usize)>;
+    let (batch_tx, batch_rx) = mpsc::sync_channel::<(usize, Vec<Chunk>, Mark)>(workers * 2);
+    // Shared by the workers only, so it is dropped (unblocking the reader)
+    // once they have all stopped.
+    let batch_rx = Arc::new(Mutex::new(batch_rx));
+    let (out_tx, out_rx) = mpsc::sync_channel::<Result<(usize, Vec<u8>, RunReport, Mark)>>(workers * 2);
+    // One credit per batch that may be in flight between the reader and the
+    // writer, handed back as each is written, so batches finished ahead of a
+    // slow one can't pile up in the writer's reorder buffer.
+    let window = workers * 2;
+    let (credit_tx, credit_rx) = mpsc::sync_channel::<()>(window);
+    for _ in 0..window {
+        let _ = credit_tx.send(());
+    }
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let batch_rx = Arc::clone(&batch_rx);
+            let out_tx = out_tx.clone();
+            let processor = &processor;
+            scope.spawn(move || loop {
+                let next = batch_rx.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                let Ok((seq, chunks, mark)) = next else { break };
+                let mut buf = Vec::new();
+                let mut report = new_report(options);
+                let result = chunks
+                    .iter()
+                    .try_for_each(|chunk| processor.process_chunk(chunk, &mut buf, &mut report))
+                    .map(|()| (seq, buf, report, mark));
+                let failed = result.is_err();
+                if out_tx.send(result).is_err() || failed {
+                    break;
+                }
+            });
+        }
+        drop(batch_rx);
+        drop(out_tx);
+
+        let writer_thread = scope.spawn(move || -> Result<RunReport> {
+            let mut report = RunReport::default();
+            let mut waiting = BTreeMap::new();
+            let mut next_seq = 0;
+            let mut writer = CountingWriter::new(writer);
+            for result in out_rx {
+                let (seq, buf, batch_report, mark) = result?;
+                waiting.insert(seq, (buf, batch_report, mark));
+                while let Some((buf, batch_report, mark)) = waiting.remove(&next_seq) {
+                    writer.write_all(&buf)?;
+                    report.merge(batch_report);
+                    next_seq += 1;
+                    let _ = credit_tx.send(());
+                    if let (Some(checkpoints), Some((input_offset, lines))) = (checkpoints, mark) {
+                        writer.flush().context("Failed to write output").context(Failure::Io)?;
+                        checkpoints.save(input_offset, writer.written, lines)?;
+                    }
+                }
+            }
+            Ok(report)
+        });
+
+        let mut processed_lines = 0;
+        let read_result = (|| -> Result<()> {
+            let mut batch = Vec::with_capacity(PIPELINE_BATCH);
+            let mut seq = 0;
+            let consumed = Cell::new(0);
+            let mut next_checkpoint = CHECKPOINT_INTERVAL;
+            let mut statements = split_statements(CountingLines::new(reader, &consumed));
+            while let Some(chunk) = statements.next() {
+                let chunk = chunk
+                    .context("Error reading line from input")
+                    .context(Failure::Io)?;
+                if chunk.last_line / 100_000 > processed_lines / 100_000 {
+                    info!(event = "progress", line = chunk.last_line; "Processed {} lines...", chunk.last_line);
+                }
+                processed_lines = chunk.last_line;
+                processor.observe(&chunk);
+                // Workers rewrite this chunk while later ones are observed.
+                let chunk = Chunk {
+                    schemas: Some(processor.snapshot()),
+                    ..chunk
+                };
+                batch.push(chunk);
+                if batch.len() == PIPELINE_BATCH {
+                    let mark = (checkpoints.is_some()
+                        && consumed.get() >= next_checkpoint
+                        && !statements.in_block_comment()
+                        && !statements.in_copy_data())
+                    .then(|| {
+                        next_checkpoint = consumed.get() + CHECKPOINT_INTERVAL;
+                        (consumed.get(), processed_lines)
+                    });
+                    // A failed receive or send means the writer or the workers
+                    // stopped on an error, which the writer thread reports.
+                    if credit_rx.recv().is_err() || batch_tx.send((seq, std::mem::take(&mut batch), mark)).is_err() {
+                        return Ok(());
+                    }
+                    seq += 1;
+                }
+            }
+            if !batch.is_empty() && credit_rx.recv().is_ok() {
+                let _ = batch_tx.send((seq, batch, None));
+            }
+            Ok(())
+        })();
+        drop(batch_tx);
+
+        let mut report = writer_thread.join().expect("writer thread panicked")?;
+        read_result?;
+        report.processed_lines = processed_lines;
+        Ok(report)
+    })
+}
@@ -0,0 +1,113 @@
+//! Pseudonyms for the `pseudonym` strategy: values mapped through a keyed
+//! function of the seed and a namespace, not of the column, so foreign keys
+//! in any table that share a namespace with the key they reference get the
+//! same replacement.
+//!
+//! Integers (bare or quoted, with an optional `-`) are permuted among the
+//! integers with as many digits, through a small Feistel network walked
+//! until it lands in range. The mapping is a bijection, so distinct keys
+//! stay distinct and a primary key stays unique. Other values keep their
+//! format: digits become digits, letters letters of the same case, and hex
+//! strings such as UUIDs stay hex. Nothing is stored, so nothing maps back
+//! without the seed.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+
+/// Most digits permuted as a number; longer runs of digits are scrambled
+/// like other values, so distinct ones may collide.
+const MAX_PERMUTED_DIGITS: usize = 18;
+
+/// Feistel rounds. Four make a keyed permutation that doesn't leak the
+/// structure of its input.
+const ROUNDS: u8 = 4;
+
+/// The pseudonym of `value` (without SQL quotes) in `namespace`.
+pub fn pseudonym(value: &str, namespace: &str, seed: u64) -> String {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", value),
+    };
+    if !digits.is_empty() && digits.len() <= MAX_PERMUTED_DIGITS && digits.bytes().all(|b| b.is_ascii_digit()) {
+        return format!("{}{}", sign, permute_digits(digits, namespace, seed));
+    }
+    let mut rng = StdRng::seed_from_u64(key(seed, namespace, &[value.as_bytes()]));
+    let hex = value.chars().all(|c| c.is_ascii_hexdigit() || !c.is_ascii_alphanumeric());
+    value
+        .chars()
+        .map(|c| {
+            if hex && c.is_ascii_hexdigit() {
+                let digit = char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0');
+                if c.is_ascii_uppercase() {
+                    digit.to_ascii_uppercase()
+                } else {
+                    digit
+                }
+            } else if c.is_ascii_digit() {
+                char::from(b'0' + rng.gen_range(0..10u8))
+            } else if c.is_ascii_uppercase() {
+                char::from(b'A' + rng.gen_range(0..26u8))
+            } else if c.is_ascii_lowercase() {
+                char::from(b'a' + rng.gen_range(0..26u8))
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Another number with as many digits. Numbers without a leading zero
+/// stay without one; zero-padded codes (`007`) are permuted among all
+/// codes of their width.
+fn permute_digits(digits: &str, namespace: &str, seed: u64) -> String {
+    let width = digits.len();
+    let padded = width > 1 && digits.starts_with('0');
+    let low = if padded || width == 1 { 0 } else { 10u64.pow(width as u32 - 1) };
+    let size = 10u64.pow(width as u32) - low;
+    let value: u64 = digits.parse().unwrap_or(0);
+    let permuted = low + permute(value - low, size, namespace, seed);
+    if padded {
+        format!("{:0width$}", permuted, width = width)
+    } else {
+        permuted.to_string()
+    }
+}
+
+/// `x` (below `size`) mapped through a keyed permutation of `0..size`.
+fn permute(x: u64, size: u64, namespace: &str, seed: u64) -> u64 {
+    // An even number of bits covering `size`, split in two halves.
+    let bits = (64 - (size - 1).leading_zeros()).max(2);
+    let half = bits.div_ceil(2);
+    let mask = (1u64 << half) - 1;
+    let mut x = x;
+    // Cycle walking: the network permutes `0..2^(2 * half)`, which holds
+    // fewer than four times `size` values, so this ends after a few steps.
+    loop {
+        let (mut left, mut right) = (x >> half, x & mask);
+        for round in 0..ROUNDS {
+            let f = key(seed, namespace, &[&[round], &right.to_le_bytes()]) & mask;
+            (left, right) = (right, left ^ f);
+        }
+        x = (left << half) | right;
+        if x < size {
+            return x;
+        }
+    }
+}
+
+/// A 64-bit key from SHA-256 of the seed, the namespace and `parts`.
+fn key(seed: u64, namespace: &str, parts: &[&[u8]]) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.to_le_bytes());
+    // Length-prefixed, so ("ab", "c") and ("a", "bc") differ.
+    hasher.update((namespace.len() as u64).to_le_bytes());
+    hasher.update(namespace.as_bytes());
+    for part in parts {
+        hasher.update(part);
+    }
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes)
+}
@@ -0,0 +1,189 @@
+//! Rewrites table and column identifiers to the pseudonyms configured with
+//! `rename` / `rename_columns`, keeping the original quoting style.
+
+use crate::config::{AppConfig, TableConfig};
+use crate::ident::{requote, split_outside_quotes, unquote_identifier, unquote_qualified};
+use crate::schema::{create_table_regex, CONSTRAINT_KEYWORDS};
+
+/// Replaces the last segment of a possibly schema-qualified table name,
+/// so `public."users"` becomes `public."t1"`.
+pub fn rename_table(token: &str, new_name: &str) -> String {
+    match token.rsplit_once('.') {
+        Some((schema, table)) => format!("{}.{}", schema, requote(table, new_name)),
+        None => requote(token, new_name),
+    }
+}
+
+/// Renames the configured columns of a comma-separated column list,
+/// keeping the whitespace around each name.
+pub fn rename_column_list(cols_part: &str, table: &TableConfig, case_insensitive: bool) -> String {
+    split_outside_quotes(cols_part, ',')
+        .into_iter()
+        .map(|raw| {
+            let token = raw.trim();
+            match table.renamed_column(&unquote_identifier(token), case_insensitive) {
+                Some(new_name) => raw.replacen(token, &requote(token, new_name), 1),
+                None => raw.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Applies the configured renames to a `CREATE TABLE` statement: the
+/// table name, column definitions, column lists of keys and constraints,
+/// and `REFERENCES` targets. Returns `None` if nothing changed.
+///
+/// Only the first identifier of each definition and identifiers inside
+/// parentheses are considered column names, so type names that happen to
+/// match a column (`created date`) are left alone.
+pub fn rewrite_create_table(text: &str, config: &AppConfig) -> Option<String> {
+    let ci = config.case_insensitive_tables;
+    let caps = create_table_regex().captures(text)?;
+    let table_token = caps.get(2)?;
+    let own = config.find_table(&unquote_qualified(table_token.as_str()));
+
+    let mut out = String::with_capacity(text.len());
+    out.push_str(&text[..table_token.start()]);
+    match own.and_then(|t| t.rename.as_deref()) {
+        Some(new_name) => out.push_str(&rename_table(table_token.as_str(), new_name)),
+        None => out.push_str(table_token.as_str()),
+    }
+    let body_start = caps.get(3)?.end();
+    out.push_str(&text[table_token.end()..body_start]);
+
+    let body = &text[body_start..];
+    let mut depth = 1;
+    let mut at_definition_start = true;
+    let mut after_references = false;
+    let mut referenced: Option<&TableConfig> = None;
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' => {
+                let mut end = body.len();
+                let mut escape = false;
+                for (j, c) in chars.by_ref() {
+                    if escape {
+                        escape = false;
+                    } else if c == '\\' {
+                        escape = true;
+                    } else if c == '\'' {
+                        end = j + 1;
+                        break;
+                    }
+                }
+                out.push_str(&body[i..end]);
+                at_definition_start = false;
+            }
+            '(' => {
+                depth += 1;
+                out.push(c);
+            }
+            ')' => {
+                depth -= 1;
+                out.push(c);
+                if depth == 0 {
+                    out.push_str(&body[i + 1..]);
+                    break;
+                }
+            }
+            ',' if depth == 1 => {
+                out.push(c);
+                at_definition_start = true;
+                referenced = None;
+            }
+            '`' | '"' | '[' | 'A'..='Z' | 'a'..='z' | '_' => {
+                let end = identifier_end(body, i, c);
+                let token = &body[i..end];
+                while chars.peek().is_some_and(|(j, _)| *j < end) {
+                    chars.next();
+                }
+                let name = unquote_identifier(token);
+                let bare = !matches!(c, '`' | '"' | '[');
+
+                let renamed = if after_references {
+                    after_references = false;
+                    referenced = config.find_table(&unquote_qualified(token));
+                    referenced
+                        .and_then(|t| t.rename.as_deref())
+                        .map(|new_name| rename_table(token, new_name))
+                } else if bare && name.eq_ignore_ascii_case("REFERENCES") {
+                    after_references = true;
+                    None
+                } else if depth == 1 && at_definition_start {
+                    let is_keyword = bare
+                        && CONSTRAINT_KEYWORDS
+                            .iter()
+                            .any(|k| name.eq_ignore_ascii_case(k));
+                    if is_keyword {
+                        None
+                    } else {
+                        own.and_then(|t| t.renamed_column(&name, ci))
+                            .map(|new_name| requote(token, new_name))
+                    }
+                } else if depth > 1 {
+                    referenced
+                        .or(own)
+                        .and_then(|t| t.renamed_column(&name, ci))
+                        .map(|new_name| requote(token, new_name))
+                } else {
+                    None
+                };
+                out.push_str(renamed.as_deref().unwrap_or(token));
+                if depth == 1 {
+                    at_definition_start = false;
+                }
+            }
+            _ => {
+                out.push(c);
+                if !c.is_whitespace() && depth == 1 {
+                    at_definition_start = false;
+                }
+            }
+        }
+    }
+
+    (out != text).then_some(out)
+}
+
+/// Byte offset just past the identifier starting at `start`, which may be
+/// quoted and schema-qualified (`a.b`, `"a"."b"`).
+fn identifier_end(s: &str, start: usize, first: char) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = start;
+    let mut quote = match first {
+        '`' | '"' => Some(first as u8),
+        '[' => Some(b']'),
+        _ => None,
+    };
+    if quote.is_some() {
+        i += 1;
+    }
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            Some(q) => {
+                i += 1;
+                if b == q {
+                    quote = None;
+                }
+            }
+            None if b.is_ascii_alphanumeric() || b == b'_' || b == b'$' => i += 1,
+            None if b == b'.' && i + 1 < bytes.len() => {
+                i += 1;
+                quote = match bytes[i] {
+                    b'`' | b'"' => Some(bytes[i]),
+                    b'[' => Some(b']'),
+                    _ => None,
+                };
+                if quote.is_some() {
+                    i += 1;
+                }
+            }
+            None => break,
+        }
+    }
+    i
+}
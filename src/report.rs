@@ -0,0 +1,190 @@
+use crate::config::ColumnStrategy;
+use crate::failure::Failure;
+use crate::transformer::ColumnRule;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::Path;
+
+/// Caps the number of individual warnings kept in memory and in the report.
+const MAX_REPORTED_WARNINGS: usize = 1000;
+
+/// Structured record of a run, built up while statements are processed.
+#[derive(Debug, Default, Serialize)]
+pub struct RunReport {
+    pub processed_lines: usize,
+    /// `INSERT` statements read, whether or not their table is configured.
+    pub insert_statements: usize,
+    pub anonymized_statements: usize,
+    pub tables: BTreeMap<String, TableReport>,
+    /// `table.column` pairs seen in the dump without a config entry.
+    pub unconfigured_columns: BTreeSet<String>,
+    /// Per-file counts when several inputs were processed in one run.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, FileSummary>,
+    pub warnings: Vec<String>,
+    /// Warnings beyond `MAX_REPORTED_WARNINGS` that were counted but not listed.
+    pub suppressed_warnings: usize,
+    /// Statements of configured tables that couldn't be parsed and were
+    /// copied through unchanged.
+    pub skipped_statements: usize,
+    /// Statements left untransformed by `--unsafe-keep-sample`.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub kept_sample_statements: usize,
+    /// `UPDATE`/`DELETE` statements whose `WHERE` values `--scrub-where`
+    /// anonymized.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub scrubbed_predicates: usize,
+    /// Values `date_shift` or `geo_fuzz` couldn't parse and replaced as
+    /// their `on_error` says, over the whole run.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub unparseable_values: usize,
+    /// Every original value and its pseudonym, by `table.column`, when
+    /// `RunOptions::collect_pseudonyms` is set. Never serialized: the keys
+    /// are the personal data being anonymized.
+    #[serde(skip)]
+    pub pseudonyms: Option<BTreeMap<String, BTreeMap<String, String>>>,
+    /// Set by `RunOptions::preview_values`: `pseudonyms` only gets values
+    /// that change, up to this many per column.
+    #[serde(skip)]
+    pub pseudonym_limit: Option<usize>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TableReport {
+    pub statements: usize,
+    pub columns: BTreeMap<String, ColumnReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileSummary {
+    pub processed_lines: usize,
+    pub anonymized_statements: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnReport {
+    pub strategy: ColumnStrategy,
+    pub transformed: usize,
+    pub kept: usize,
+}
+
+impl RunReport {
+    /// Records one anonymized row of `table`.
+    pub fn record_row(
+        &mut self,
+        table: &str,
+        columns: &[String],
+        rules: &[ColumnRule],
+        old_values: &[String],
+        new_values: &[String],
+    ) {
+        let table_report = self.tables.entry(table.to_string()).or_default();
+        for (i, column) in columns.iter().enumerate() {
+            let column_report = table_report
+                .columns
+                .entry(column.clone())
+                .or_insert_with(|| ColumnReport {
+                    strategy: rules[i].strategy.clone(),
+                    transformed: 0,
+                    kept: 0,
+                });
+            if old_values[i] == new_values[i] {
+                column_report.kept += 1;
+            } else {
+                column_report.transformed += 1;
+            }
+        }
+        if let Some(pseudonyms) = &mut self.pseudonyms {
+            for (i, column) in columns.iter().enumerate() {
+                let values = pseudonyms.entry(format!("{}.{}", table, column)).or_default();
+                if let Some(limit) = self.pseudonym_limit {
+                    if old_values[i] == new_values[i] || values.len() >= limit {
+                        continue;
+                    }
+                }
+                values.insert(old_values[i].clone(), new_values[i].clone());
+            }
+        }
+    }
+
+    /// Records one rewritten statement of `table`.
+    pub fn record_statement(&mut self, table: &str) {
+        self.anonymized_statements += 1;
+        self.tables.entry(table.to_string()).or_default().statements += 1;
+    }
+
+    pub fn warn(&mut self, message: String) {
+        if self.warnings.len() < MAX_REPORTED_WARNINGS {
+            self.warnings.push(message);
+        } else {
+            self.suppressed_warnings += 1;
+        }
+    }
+
+    /// Folds the report of one input file into this aggregate report.
+    pub fn merge_file(&mut self, file: &str, mut other: RunReport) {
+        self.files.insert(
+            file.to_string(),
+            FileSummary {
+                processed_lines: other.processed_lines,
+                anonymized_statements: other.anonymized_statements,
+            },
+        );
+        for warning in &mut other.warnings {
+            *warning = format!("{}: {}", file, warning);
+        }
+        self.merge(other);
+    }
+
+    /// Adds the counts, columns and warnings of `other` to this report.
+    pub fn merge(&mut self, other: RunReport) {
+        self.processed_lines += other.processed_lines;
+        self.insert_statements += other.insert_statements;
+        self.anonymized_statements += other.anonymized_statements;
+        for (table, table_report) in other.tables {
+            let total = self.tables.entry(table).or_default();
+            total.statements += table_report.statements;
+            for (column, column_report) in table_report.columns {
+                match total.columns.get_mut(&column) {
+                    Some(existing) => {
+                        existing.transformed += column_report.transformed;
+                        existing.kept += column_report.kept;
+                    }
+                    None => {
+                        total.columns.insert(column, column_report);
+                    }
+                }
+            }
+        }
+        self.unconfigured_columns.extend(other.unconfigured_columns);
+        for warning in other.warnings {
+            self.warn(warning);
+        }
+        self.suppressed_warnings += other.suppressed_warnings;
+        self.skipped_statements += other.skipped_statements;
+        self.kept_sample_statements += other.kept_sample_statements;
+        self.scrubbed_predicates += other.scrubbed_predicates;
+        if let Some(other) = other.pseudonyms {
+            let pseudonyms = self.pseudonyms.get_or_insert_with(BTreeMap::new);
+            for (column, values) in other {
+                pseudonyms.entry(column).or_default().extend(values);
+            }
+        }
+    }
+
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create summary file: {:?}", path))
+            .context(Failure::Io)?;
+        serde_json::to_writer_pretty(file, self)
+            .context("Failed to write summary JSON")
+            .context(Failure::Io)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+//! Checkpoints that let an interrupted `run` continue where it stopped
+//! (`--resume`). Output is deterministic by seed, so a resumed run writes
+//! the same bytes a full run would have.
+
+use crate::config::AppConfig;
+use crate::failure::Failure;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Input bytes between checkpoints.
+pub const CHECKPOINT_INTERVAL: u64 = 64 * 1024 * 1024;
+
+/// How far a run got: everything before `input_offset` has been turned
+/// into the first `output_offset` bytes of the output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Identifies the input file, config and seed of the run.
+    pub fingerprint: String,
+    pub input_offset: u64,
+    pub output_offset: u64,
+    /// Lines of input consumed, so line numbers in warnings stay right.
+    pub lines: usize,
+}
+
+/// Where the checkpoint for `output` is kept: `<output>.ghostdb-resume`.
+pub fn state_path(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_os_string();
+    path.push(".ghostdb-resume");
+    PathBuf::from(path)
+}
+
+/// Hashes the input's size and modification time with the config and
+/// seed, so a checkpoint is never applied to a different run.
+pub fn fingerprint(input: &Path, config: &AppConfig, seed: u64) -> Result<String> {
+    let metadata = fs::metadata(input)
+        .with_context(|| format!("Failed to read metadata of {:?}", input))
+        .context(Failure::Io)?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos());
+    let mut hasher = DefaultHasher::new();
+    metadata.len().hash(&mut hasher);
+    modified.hash(&mut hasher);
+    seed.hash(&mut hasher);
+    // Through `serde_json::Value`, whose maps are sorted, so the hash
+    // doesn't depend on `HashMap` iteration order.
+    serde_json::to_value(config)?.to_string().hash(&mut hasher);
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+impl Checkpoint {
+    /// Reads the checkpoint at `path`, if there is one.
+    pub fn load(path: &Path) -> Result<Option<Checkpoint>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(anyhow!(e))
+                    .with_context(|| format!("Failed to read checkpoint {:?}", path))
+                    .context(Failure::Io)
+            }
+        };
+        let checkpoint = serde_json::from_str(&contents)
+            .with_context(|| format!("Corrupt checkpoint {:?}; delete it to start over", path))
+            .context(Failure::Config)?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Writes the checkpoint to a temporary file and renames it over `path`,
+    /// so a crash mid-write leaves the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp = path.as_os_str().to_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let file = File::create(&tmp)
+            .with_context(|| format!("Failed to create checkpoint {:?}", tmp))
+            .context(Failure::Io)?;
+        serde_json::to_writer(file, self)
+            .context("Failed to write checkpoint")
+            .context(Failure::Io)?;
+        fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to write checkpoint {:?}", path))
+            .context(Failure::Io)?;
+        Ok(())
+    }
+}
+
+/// Like `BufRead::lines`, but adds the size of every line read, line
+/// ending included, to `consumed`.
+pub struct CountingLines<'a, R> {
+    reader: R,
+    consumed: &'a Cell<u64>,
+}
+
+impl<'a, R: BufRead> CountingLines<'a, R> {
+    pub fn new(reader: R, consumed: &'a Cell<u64>) -> Self {
+        Self { reader, consumed }
+    }
+}
+
+impl<R: BufRead> Iterator for CountingLines<'_, R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(n) => {
+                self.consumed.set(self.consumed.get() + n as u64);
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some(Ok(line))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Passes writes through to `inner`, counting the bytes.
+pub struct CountingWriter<'a, W> {
+    inner: &'a mut W,
+    pub written: u64,
+}
+
+impl<'a, W: Write> CountingWriter<'a, W> {
+    pub fn new(inner: &'a mut W) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
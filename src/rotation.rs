@@ -0,0 +1,105 @@
+//! Seed rotation: how a dump's pseudonyms change between an old and a new
+//! seed, and the remapping from one to the other for data anonymized with
+//! the old seed.
+
+use crate::config::{AppConfig, ColumnStrategy};
+use crate::failure::Failure;
+use crate::processor::{process_stream, RunOptions};
+use crate::report::RunReport;
+use crate::transformer::Transformer;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+/// How one column's pseudonyms change between two seeds.
+#[derive(Debug)]
+pub struct ColumnRotation {
+    /// `table.column`.
+    pub column: String,
+    /// Whether the column's strategy uses the seed at all.
+    pub seeded: bool,
+    /// Distinct original values seen.
+    pub values: usize,
+    /// Originals that got the same pseudonym under both seeds.
+    pub unchanged: usize,
+    /// Old pseudonym to new pseudonym, for the values that changed. Never
+    /// contains an original value.
+    pub remapping: BTreeMap<String, String>,
+}
+
+/// Anonymizes `input` with both seeds and compares the pseudonyms, column
+/// by column (`keep` columns are left out). Nothing is written; the
+/// originals only live in memory.
+pub fn compare_seeds(
+    input: &Path,
+    config: &AppConfig,
+    old_seed: u64,
+    new_seed: u64,
+) -> Result<Vec<ColumnRotation>> {
+    let old = pseudonyms(input, config, old_seed)?;
+    let new = pseudonyms(input, config, new_seed)?;
+    let mut rotations = Vec::new();
+    for (column, old_values) in old.pseudonyms.unwrap_or_default() {
+        let Some(strategy) = column
+            .rsplit_once('.')
+            .and_then(|(table, name)| old.tables.get(table)?.columns.get(name))
+            .map(|c| &c.strategy)
+        else {
+            continue;
+        };
+        if *strategy == ColumnStrategy::Keep {
+            continue;
+        }
+        let seeded = strategy.is_seeded();
+        let new_values = new.pseudonyms.as_ref().and_then(|p| p.get(&column));
+        let mut rotation = ColumnRotation {
+            column,
+            seeded,
+            values: old_values.len(),
+            unchanged: 0,
+            remapping: BTreeMap::new(),
+        };
+        for (original, old_pseudonym) in &old_values {
+            match new_values.and_then(|n| n.get(original)) {
+                Some(new_pseudonym) if new_pseudonym == old_pseudonym => rotation.unchanged += 1,
+                Some(new_pseudonym) if seeded => {
+                    rotation
+                        .remapping
+                        .insert(old_pseudonym.clone(), new_pseudonym.clone());
+                }
+                _ => {}
+            }
+        }
+        rotations.push(rotation);
+    }
+    Ok(rotations)
+}
+
+/// Writes the remapping of every column as CSV: `column,old,new`.
+pub fn write_remapping(path: &Path, rotations: &[ColumnRotation]) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create remapping file: {:?}", path))
+        .context(Failure::Io)?;
+    let mut writer = csv::Writer::from_writer(file);
+    writer.write_record(["column", "old", "new"])?;
+    for rotation in rotations {
+        for (old, new) in &rotation.remapping {
+            writer.write_record([rotation.column.as_str(), old, new])?;
+        }
+    }
+    writer.flush().context("Failed to write remapping").context(Failure::Io)?;
+    Ok(())
+}
+
+fn pseudonyms(input: &Path, config: &AppConfig, seed: u64) -> Result<RunReport> {
+    let file = File::open(input)
+        .with_context(|| format!("Failed to open input file: {:?}", input))
+        .context(Failure::Io)?;
+    let options = RunOptions {
+        collect_pseudonyms: true,
+        ..RunOptions::default()
+    };
+    process_stream(&Transformer::new(seed), BufReader::new(file), &mut io::sink(), config, &options)
+}
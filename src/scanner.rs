@@ -0,0 +1,502 @@
+use crate::config::{AppConfig, ColumnOptions, ColumnStrategy, TableConfig};
+use crate::copy;
+use crate::detect;
+use crate::failure::Failure;
+use crate::ident::{split_column_list, unquote_identifier, unquote_qualified};
+use crate::lint::is_name_strategy;
+use crate::schema::{declared_length, is_numeric_type, parse_create_table, TableSchema};
+use crate::sql::{split_statements, statement_body, ChunkKind, INSERT_PREFIX, INSERT_SET_PREFIX, TABLE_NAME};
+use crate::transformer::Transformer;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A few distinct original values per table and column, as SQL literals,
+/// collected while scanning so the wizard can preview strategies.
+pub type Samples = HashMap<String, HashMap<String, Vec<String>>>;
+
+/// Distinct non-`NULL` values kept per column by [`scan_file_with_samples`].
+pub const SAMPLES_PER_COLUMN: usize = 3;
+
+/// Non-`NULL` values per column, in order of appearance, that
+/// [`guess_strategy_with_values`] looks at. Bounded so scanning a large
+/// dump stays fast.
+pub const GUESS_VALUES_PER_COLUMN: usize = 50;
+
+/// Declared lengths from which a scanned name column gets no `max_len`:
+/// fake names are rarely that long.
+const NAME_LENGTH_UNBOUNDED: usize = 32;
+
+pub fn scan_file(path: &Path) -> Result<AppConfig> {
+    Ok(scan_file_with_samples(path)?.0)
+}
+
+/// Like [`scan_file`], but also returns up to [`SAMPLES_PER_COLUMN`]
+/// sample values for each column.
+pub fn scan_file_with_samples(path: &Path) -> Result<(AppConfig, Samples)> {
+    let input_file = File::open(path)
+        .with_context(|| format!("Failed to open input file: {:?}", path))
+        .context(Failure::Io)?;
+    let reader = BufReader::new(input_file);
+
+    let insert_regex = Regex::new(&format!(
+        r"(?is)^{}\s+({})\s*\((.*?)\)\s*VALUES(?:\s*\((.*)\))?",
+        INSERT_PREFIX, TABLE_NAME
+    ))
+    .expect("Invalid regex pattern");
+    let insert_positional_regex = Regex::new(&format!(
+        r"(?is)^{}\s+({})\s*VALUES(?:\s*\((.*)\))?",
+        INSERT_PREFIX, TABLE_NAME
+    ))
+    .expect("Invalid regex pattern");
+    let insert_set_regex = Regex::new(&format!(
+        r"(?is)^{}\s+({})\s+SET\s+(.*)$",
+        INSERT_SET_PREFIX, TABLE_NAME
+    ))
+    .expect("Invalid regex pattern");
+
+    let mut tables_columns: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut samples = Samples::new();
+    let mut guess_values = Samples::new();
+    // Column names from CREATE TABLE, for INSERTs without a column list.
+    let mut schema_columns: HashMap<String, Vec<String>> = HashMap::new();
+    // And the whole schemas, for the declared types of the columns.
+    let mut schemas: HashMap<String, TableSchema> = HashMap::new();
+
+    let mut record = |table: &str, columns: &[String], values: &[String]| {
+        let entry = tables_columns.entry(table.to_string()).or_default();
+        entry.extend(columns.iter().cloned());
+        if columns.len() != values.len() {
+            return;
+        }
+        let table_samples = samples.entry(table.to_string()).or_default();
+        let table_values = guess_values.entry(table.to_string()).or_default();
+        for (column, value) in columns.iter().zip(values) {
+            if value.eq_ignore_ascii_case("NULL") {
+                continue;
+            }
+            let column_values = table_values.entry(column.clone()).or_default();
+            if column_values.len() < GUESS_VALUES_PER_COLUMN {
+                column_values.push(value.clone());
+            }
+            let column_samples = table_samples.entry(column.clone()).or_default();
+            if column_samples.len() < SAMPLES_PER_COLUMN && !column_samples.contains(value)
+            {
+                column_samples.push(value.clone());
+            }
+        }
+    };
+
+    for chunk in split_statements(reader.lines()) {
+        let chunk = chunk.context("Error reading line from input").context(Failure::Io)?;
+        if chunk.kind == ChunkKind::CreateTable {
+            if let Some(schema) = parse_create_table(&chunk.text) {
+                let columns = schema.columns.iter().map(|c| c.name.clone()).collect();
+                schema_columns.insert(schema.name.clone(), columns);
+                schemas.insert(schema.name.clone(), schema);
+            }
+            continue;
+        }
+        // Columns are learned from the header, even for blocks without rows.
+        if chunk.kind == ChunkKind::Copy {
+            if let Some(header) = copy::parse_header(statement_body(&chunk.text)) {
+                match schema_columns.get(&unquote_qualified(&header.table)) {
+                    _ if !header.columns.is_empty() => record(&header.table, &header.columns, &[]),
+                    Some(columns) => record(&header.table, columns, &[]),
+                    None => {}
+                }
+            }
+            continue;
+        }
+        if let (ChunkKind::CopyData, Some(header)) = (chunk.kind, &chunk.copy) {
+            let columns = match schema_columns.get(&unquote_qualified(&header.table)) {
+                _ if !header.columns.is_empty() => &header.columns,
+                Some(columns) => columns,
+                None => continue,
+            };
+            let values: Vec<String> = copy::split_row(&chunk.text)
+                .iter()
+                .map(|f| copy::to_literal(f.as_deref()))
+                .collect();
+            record(&header.table, columns, &values);
+            continue;
+        }
+        if chunk.kind != ChunkKind::Insert {
+            continue;
+        }
+        let line = statement_body(&chunk.text);
+        if let Some(caps) = insert_regex.captures(line) {
+            let table_full_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+            let cols_part = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+            let vals_part = caps.get(3).map(|m| m.as_str()).unwrap_or("");
+
+            let columns = split_column_list(cols_part);
+            for values in tuple_values(vals_part) {
+                record(table_full_name, &columns, &values);
+            }
+        } else if let Some(caps) = insert_positional_regex.captures(line) {
+            let table_full_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+            let vals_part = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+            if let Some(columns) = schema_columns.get(&unquote_qualified(table_full_name)) {
+                for values in tuple_values(vals_part) {
+                    record(table_full_name, columns, &values);
+                }
+            }
+        } else if let Some(caps) = insert_set_regex.captures(line) {
+            let table_full_name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+            let set_part = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+
+            let (columns, values): (Vec<String>, Vec<String>) =
+                Transformer::parse_assignments(set_part)
+                    .into_iter()
+                    .map(|(col, value)| (unquote_identifier(&col).into_owned(), value))
+                    .unzip();
+            record(table_full_name, &columns, &values);
+        }
+    }
+
+    let mut config = AppConfig::default();
+
+    for (table_name, columns) in tables_columns {
+        let mut table_config = TableConfig::default();
+        let siblings: Vec<String> = columns.iter().cloned().collect();
+        let schema = schemas.get(&unquote_qualified(&table_name));
+        for col in columns {
+            let values = guess_values
+                .get(&table_name)
+                .and_then(|t| t.get(&col))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let mut strategy = guess_strategy_with_values(&col, &siblings, values);
+            if let Some(sql_type) = schema.and_then(|s| s.column(&col)).map(|c| c.sql_type.as_str()) {
+                strategy = fit_guess_to_type(strategy, sql_type);
+                let is_name = is_name_strategy(&strategy);
+                if let Some(len) = declared_length(sql_type).filter(|len| is_name && *len < NAME_LENGTH_UNBOUNDED) {
+                    let options = ColumnOptions {
+                        max_len: Some(len),
+                        ..Default::default()
+                    };
+                    table_config.options.insert(col.clone(), options);
+                }
+            }
+            table_config.columns.insert(col, strategy);
+        }
+        config.tables.insert(table_name, table_config);
+    }
+
+    Ok((config, samples))
+}
+
+/// The values of each tuple of a (possibly multi-row) `VALUES` list; none
+/// if it is malformed.
+fn tuple_values(vals_part: &str) -> Vec<Vec<String>> {
+    Transformer::tuple_spans(vals_part)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|tuple| Transformer::parse_values(&vals_part[tuple]))
+        .collect()
+}
+
+/// Narrows a scan result to the tables named in `tables` (all tables if it
+/// is empty) and, with `non_keep_only`, to the columns whose guessed
+/// strategy changes values; tables left without columns are dropped. A
+/// name matches a scanned table with or without quotes and schema prefix.
+/// Returns the names that matched no table.
+pub fn filter_scan(
+    config: &mut AppConfig,
+    samples: &mut Samples,
+    tables: &[String],
+    non_keep_only: bool,
+) -> Vec<String> {
+    let matches = |scanned: &str, name: &str| {
+        let unquoted = unquote_qualified(scanned);
+        unquoted == name || unquoted.rsplit('.').next() == Some(name)
+    };
+    let unmatched = tables
+        .iter()
+        .filter(|name| !config.tables.keys().any(|t| matches(t, name)))
+        .cloned()
+        .collect();
+
+    if !tables.is_empty() {
+        config
+            .tables
+            .retain(|table, _| tables.iter().any(|name| matches(table, name)));
+    }
+    if non_keep_only {
+        for table in config.tables.values_mut() {
+            table
+                .columns
+                .retain(|_, strategy| !matches!(strategy, ColumnStrategy::Keep));
+        }
+        config.tables.retain(|_, table| !table.columns.is_empty());
+    }
+    samples.retain(|table, _| config.tables.contains_key(table));
+    unmatched
+}
+
+/// What [`merge_scan`] added to an existing config.
+#[derive(Debug, Default, PartialEq)]
+pub struct Merged {
+    /// Tables that were new, with all their scanned columns.
+    pub tables: Vec<String>,
+    /// `(table, column)` pairs that were new in tables the config already had.
+    pub columns: Vec<(String, String)>,
+}
+
+/// Adds the tables and columns of a scan result that `existing` has no
+/// entry for, with their guessed strategies; everything already in
+/// `existing` is left as it is. Tables are matched like `run` matches
+/// them: with or without quotes, by qualified or unqualified name, and
+/// ignoring case under `case_insensitive_tables`.
+pub fn merge_scan(existing: &mut AppConfig, scanned: AppConfig) -> Merged {
+    let ci = existing.case_insensitive_tables;
+    let same = |a: &str, b: &str| if ci { a.eq_ignore_ascii_case(b) } else { a == b };
+    let mut merged = Merged::default();
+    let scanned: BTreeMap<String, TableConfig> = scanned.tables.into_iter().collect();
+    for (name, table) in scanned {
+        let unquoted = unquote_qualified(&name);
+        let short = unquoted.rsplit('.').next().unwrap_or(&unquoted).to_string();
+        let key = existing
+            .tables
+            .keys()
+            .find(|key| {
+                let key = unquote_qualified(key);
+                same(&key, &unquoted) || same(&key, &short)
+            })
+            .cloned();
+        let Some(key) = key else {
+            existing.tables.insert(name.clone(), table);
+            merged.tables.push(name);
+            continue;
+        };
+        let target = existing.tables.get_mut(&key).expect("key was just found");
+        let mut options = table.options;
+        let columns: BTreeMap<String, ColumnStrategy> = table.columns.into_iter().collect();
+        for (column, strategy) in columns {
+            if target.column(&column, ci).is_none() {
+                if let Some(column_options) = options.remove(&column) {
+                    target.options.insert(column.clone(), column_options);
+                }
+                target.columns.insert(column.clone(), strategy);
+                merged.columns.push((key.clone(), column));
+            }
+        }
+    }
+    merged
+}
+
+/// How much of the personal data a config is likely to cover, by column
+/// names and guessed strategies.
+#[derive(Debug, Default, PartialEq)]
+pub struct Coverage {
+    /// Columns that look like personal data (see [`is_pii_candidate`]) or
+    /// have a strategy that changes values.
+    pub candidates: usize,
+    /// Candidates with a strategy that changes values.
+    pub anonymized: usize,
+    /// `(table, column)` of the candidates that are kept as they are, in
+    /// order.
+    pub kept: Vec<(String, String)>,
+}
+
+/// Counts the PII-candidate columns of `config` and which of them are
+/// anonymized, to show before a run what a plan would leave untouched.
+pub fn coverage(config: &AppConfig) -> Coverage {
+    let mut coverage = Coverage::default();
+    for (table, table_config) in &config.tables {
+        for (column, strategy) in &table_config.columns {
+            let keep = matches!(strategy, ColumnStrategy::Keep);
+            if keep && !is_pii_candidate(column) {
+                continue;
+            }
+            coverage.candidates += 1;
+            if keep {
+                coverage.kept.push((table.clone(), column.clone()));
+            } else {
+                coverage.anonymized += 1;
+            }
+        }
+    }
+    coverage.kept.sort();
+    coverage
+}
+
+/// Whether a column's name suggests personal data: [`guess_strategy`] has
+/// a strategy for it, or it names something `guess_strategy` keeps on
+/// purpose but that can still identify a person (a birth date, a username,
+/// an IP address, a salary).
+pub fn is_pii_candidate(col_name: &str) -> bool {
+    if !matches!(guess_strategy(col_name), ColumnStrategy::Keep) {
+        return true;
+    }
+    let lower = col_name.to_lowercase();
+    let words: Vec<&str> = lower.split('_').collect();
+    words
+        .iter()
+        .any(|w| matches!(*w, "dob" | "ip" | "age" | "gender" | "sex" | "lat" | "lng" | "lon" | "iban" | "login"))
+        || [
+            "birth", "username", "user_name", "salary", "passport", "license", "licence", "national",
+            "latitude", "longitude", "card_number", "account_number",
+        ]
+        .iter()
+        .any(|w| lower.contains(w))
+}
+
+pub fn guess_strategy(col_name: &str) -> ColumnStrategy {
+    let lower = col_name.to_lowercase();
+
+    if lower == "id" || lower.ends_with("_id") || lower.ends_with("uuid") || lower.ends_with("guid") {
+        return ColumnStrategy::Keep;
+    }
+
+    if lower.contains("date") || lower.contains("time") || lower.ends_with("_at") {
+        return ColumnStrategy::Keep;
+    }
+
+    if lower.contains("amount") 
+        || lower.contains("price") 
+        || lower.contains("sum") 
+        || lower.contains("total") 
+        || lower.contains("balance") 
+        || lower.contains("cost") 
+        || lower.contains("currency") {
+        return ColumnStrategy::Keep;
+    }
+
+    if lower.contains("email") {
+        return ColumnStrategy::Email;
+    }
+    if lower.contains("phone") || lower.contains("mobile") {
+        return ColumnStrategy::Phone;
+    }
+    let words: Vec<&str> = lower.split('_').collect();
+    if words
+        .iter()
+        .any(|w| matches!(*w, "url" | "uri" | "website" | "homepage" | "link" | "referrer" | "referer"))
+    {
+        return ColumnStrategy::Url { keep_path: false };
+    }
+    if words.iter().any(|w| matches!(*w, "host" | "hostname")) {
+        return ColumnStrategy::Hostname;
+    }
+    if lower.contains("zip") || lower.contains("postal") || lower.contains("postcode") {
+        return ColumnStrategy::ZipCode { keep_prefix: 3 };
+    }
+    if words.contains(&"ssn") || lower.contains("social_security") {
+        return ColumnStrategy::Ssn;
+    }
+    if words.contains(&"vin") {
+        return ColumnStrategy::Vin;
+    }
+    if matches!(lower.as_str(), "salutation" | "honorific" | "name_prefix" | "name_title") {
+        return ColumnStrategy::NamePrefix;
+    }
+    if matches!(
+        lower.as_str(),
+        "job" | "job_title" | "jobtitle" | "job_position" | "job_role" | "occupation" | "designation"
+    ) {
+        return ColumnStrategy::JobTitle;
+    }
+    if lower == "first_name" || lower == "firstname" {
+        return ColumnStrategy::FirstName;
+    }
+    if lower == "last_name" || lower == "lastname" || lower == "surname" {
+        return ColumnStrategy::LastName;
+    }
+    if lower.contains("name") && !lower.contains("user") && !lower.contains("file") && !lower.contains("domain") {
+        return ColumnStrategy::FullName;
+    }
+    if lower.contains("address") || lower.contains("city") || lower.contains("street") {
+        return ColumnStrategy::Fixed("ANONYMIZED ADDRESS".to_string());
+    }
+    if lower.contains("password") {
+         return ColumnStrategy::Fixed("REDACTED_SECRET".to_string());
+    }
+    // Tokens and keys are grouped and joined on, so they keep their
+    // cardinality.
+    if lower.contains("token") || lower.contains("secret") || lower.contains("key") {
+        return ColumnStrategy::Hash;
+    }
+    if lower.contains("description") || lower.contains("comment") || lower.contains("note") {
+        return ColumnStrategy::default_mask();
+    }
+
+    ColumnStrategy::Keep
+}
+
+/// The strategy for a column of a table with `columns`, judged by its name
+/// ([`guess_strategy_among`]) and by `values`, some of its non-`NULL`
+/// values as SQL literals. Values that all look like email addresses,
+/// SSNs, phone numbers or JSON with PII in it win over the name, so a
+/// `notes` column of emails gets `email`. Values that all look like names
+/// only count when the name says nothing, since title-case addresses and
+/// cities look like names too. A column of bare numbers is kept where the
+/// name asks for text (a `name_count` isn't a person's name). Without
+/// values, or when they don't agree, the name decides.
+pub fn guess_strategy_with_values(col_name: &str, columns: &[String], values: &[String]) -> ColumnStrategy {
+    let by_name = guess_strategy_among(col_name, columns);
+    match detect::guess_from_samples(values) {
+        Some(ColumnStrategy::FullName) if by_name != ColumnStrategy::Keep => return by_name,
+        Some(by_values) => return by_values,
+        None => {}
+    }
+    let numeric = !values.is_empty() && values.iter().all(|v| v.parse::<f64>().is_ok());
+    let writes_text = matches!(
+        by_name,
+        ColumnStrategy::FirstName
+            | ColumnStrategy::LastName
+            | ColumnStrategy::FullName
+            | ColumnStrategy::JobTitle
+            | ColumnStrategy::NamePrefix
+            | ColumnStrategy::Mask { .. }
+    );
+    if numeric && writes_text {
+        ColumnStrategy::Keep
+    } else {
+        by_name
+    }
+}
+
+/// A guessed strategy adjusted to the column's declared type. A numeric
+/// column can't hold the text most strategies write, so anything the name
+/// asked to change gets `digit_scramble`, which keeps a number a number
+/// (and distinct keys distinct): phone numbers, SSNs and keys stored as
+/// numbers, but also a numeric `password` or `street_number` that would have
+/// been redacted. Only columns the name would keep are kept.
+pub fn fit_guess_to_type(strategy: ColumnStrategy, sql_type: &str) -> ColumnStrategy {
+    if !is_numeric_type(sql_type) || strategy == ColumnStrategy::Keep {
+        return strategy;
+    }
+    ColumnStrategy::DigitScramble
+}
+
+/// [`guess_strategy`] for a column of a table with `columns`, settling names
+/// whose meaning depends on the table: `title`, `position` and `role` are
+/// job titles next to employment columns (`department`, `salary`, ...), and
+/// `title` is otherwise an honorific next to name columns. Elsewhere (a
+/// book's title, a user's permission role) they are kept.
+pub fn guess_strategy_among(col_name: &str, columns: &[String]) -> ColumnStrategy {
+    let lower = col_name.to_lowercase();
+    if !matches!(lower.as_str(), "title" | "position" | "role") {
+        return guess_strategy(col_name);
+    }
+    let employment = ["department", "dept", "salary", "employer", "employee", "manager", "hire", "hired", "payroll"];
+    let is_employment = |c: &String| c.to_lowercase().split('_').any(|w| employment.contains(&w));
+    let is_name = |c: &String| {
+        matches!(
+            guess_strategy(c),
+            ColumnStrategy::FirstName | ColumnStrategy::LastName | ColumnStrategy::FullName
+        )
+    };
+    if columns.iter().any(is_employment) {
+        ColumnStrategy::JobTitle
+    } else if lower == "title" && columns.iter().any(is_name) {
+        ColumnStrategy::NamePrefix
+    } else {
+        guess_strategy(col_name)
+    }
+}
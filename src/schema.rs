@@ -0,0 +1,316 @@
+//! Column names and types read from `CREATE TABLE` statements.
+
+use crate::config::{ColumnStrategy, MAX_HASH_LEN};
+use crate::ident::{unquote_identifier, unquote_qualified};
+use crate::sql::TABLE_NAME;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+/// Schemas by table name, both qualified and unqualified.
+pub type Schemas = HashMap<String, Arc<TableSchema>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSchema {
+    /// The table name with identifier quotes removed.
+    pub name: String,
+    pub columns: Vec<ColumnDef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    /// The declared type as written, e.g. `varchar(255)` or
+    /// `timestamp with time zone`.
+    pub sql_type: String,
+    /// Members of an `ENUM(...)` type, unquoted; empty for other types.
+    pub enum_values: Vec<String>,
+}
+
+impl TableSchema {
+    pub fn column(&self, name: &str) -> Option<&ColumnDef> {
+        self.columns
+            .iter()
+            .find(|c| c.name == name)
+            .or_else(|| self.columns.iter().find(|c| c.name.eq_ignore_ascii_case(name)))
+    }
+}
+
+pub(crate) fn create_table_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?is)^(\s*CREATE\s+(?:TEMP(?:ORARY)?\s+)?TABLE\s+(?:IF\s+NOT\s+EXISTS\s+)?)({})(\s*\()",
+            TABLE_NAME
+        ))
+        .expect("Invalid regex pattern")
+    })
+}
+
+/// Words that open a table constraint rather than a column definition.
+pub(crate) const CONSTRAINT_KEYWORDS: &[&str] = &[
+    "PRIMARY", "KEY", "INDEX", "UNIQUE", "CONSTRAINT", "FOREIGN", "CHECK", "FULLTEXT", "SPATIAL",
+    "EXCLUDE",
+];
+
+/// Words that end the type part of a column definition.
+const TYPE_TERMINATORS: &[&str] = &[
+    "NOT", "NULL", "DEFAULT", "PRIMARY", "UNIQUE", "REFERENCES", "CHECK", "COLLATE", "CHARSET",
+    "AUTO_INCREMENT", "AUTOINCREMENT", "COMMENT", "GENERATED", "CONSTRAINT", "ON", "AS",
+];
+
+/// String types whose size argument is a length in characters
+/// (`character varying` ahead of its prefix `character`).
+const SIZED_STRING_TYPES: &[&str] = &[
+    "character varying", "character", "char", "nchar", "varchar", "nvarchar", "varchar2",
+    "nvarchar2",
+];
+
+/// Of [`SIZED_STRING_TYPES`], those that mean a single character when
+/// written without a size.
+const SINGLE_CHAR_TYPES: &[&str] = &["char", "character", "nchar"];
+
+/// First words of numeric types, in the spellings of PostgreSQL, MySQL,
+/// SQLite, SQL Server and Oracle.
+const NUMERIC_TYPES: &[&str] = &[
+    "int", "integer", "smallint", "tinyint", "mediumint", "bigint", "int2", "int4", "int8",
+    "serial", "smallserial", "bigserial", "numeric", "decimal", "number", "float", "float4",
+    "float8", "double", "real", "money",
+];
+
+/// Parses a `CREATE TABLE` statement into its column definitions. Table
+/// constraints (`PRIMARY KEY (...)`, `CONSTRAINT ...`) are skipped.
+pub fn parse_create_table(text: &str) -> Option<TableSchema> {
+    let (schema, _) = parse_with_type_ranges(text)?;
+    Some(schema)
+}
+
+/// Like [`parse_create_table`], also returning the byte range in `text` of
+/// each column's declared type.
+pub(crate) fn parse_with_type_ranges(text: &str) -> Option<(TableSchema, Vec<Range<usize>>)> {
+    let caps = create_table_regex().captures(text)?;
+    let name = unquote_qualified(&caps[2]);
+    let body_start = caps.get(3)?.end();
+    let body = &text[body_start..];
+
+    let (columns, ranges) = split_definitions(body)
+        .into_iter()
+        .filter_map(|part| {
+            let (column, range) = parse_column(&body[part.clone()])?;
+            let start = body_start + part.start + range.start;
+            Some((column, start..start + range.len()))
+        })
+        .unzip();
+    Some((TableSchema { name, columns }, ranges))
+}
+
+/// Splits the body of a `CREATE TABLE` (after its opening parenthesis) at
+/// top-level commas, stopping at the closing parenthesis.
+fn split_definitions(body: &str) -> Vec<Range<usize>> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' | '`' => quote = Some(c),
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                parts.push(start..i);
+                return parts;
+            }
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(start..i);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(start..body.len());
+    parts
+}
+
+/// A column definition and the range of its declared type within
+/// `definition`.
+fn parse_column(definition: &str) -> Option<(ColumnDef, Range<usize>)> {
+    let offset = definition.len() - definition.trim_start().len();
+    let definition = definition.trim();
+    let (name_token, rest) = split_identifier(definition)?;
+    let quoted = name_token.starts_with(['`', '"', '[']);
+    if !quoted
+        && CONSTRAINT_KEYWORDS
+            .iter()
+            .any(|k| name_token.eq_ignore_ascii_case(k))
+    {
+        return None;
+    }
+
+    let mut type_end = rest.len();
+    let mut depth = 0;
+    let mut word_start = None;
+    for (i, c) in rest.char_indices().chain(std::iter::once((rest.len(), ' '))) {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = word_start.take() {
+                    let word = &rest[start..i];
+                    let next_word = rest[i..].split_whitespace().next().unwrap_or("");
+                    let is_charset = word.eq_ignore_ascii_case("CHARACTER")
+                        && next_word.eq_ignore_ascii_case("SET");
+                    if is_charset || TYPE_TERMINATORS.iter().any(|k| word.eq_ignore_ascii_case(k)) {
+                        type_end = start;
+                        break;
+                    }
+                }
+            }
+            _ if depth == 0 && word_start.is_none() => word_start = Some(i),
+            _ => {}
+        }
+    }
+    let sql_type = rest[..type_end].trim().to_string();
+    let type_start = offset + name_token.len() + rest.len() - rest.trim_start().len();
+    let enum_values = if sql_type.to_ascii_lowercase().starts_with("enum") {
+        quoted_literals(&sql_type)
+    } else {
+        Vec::new()
+    };
+    let range = type_start..type_start + sql_type.len();
+    let column = ColumnDef {
+        name: unquote_identifier(name_token).to_string(),
+        sql_type,
+        enum_values,
+    };
+    Some((column, range))
+}
+
+/// Splits a leading (possibly quoted) identifier off `s`.
+fn split_identifier(s: &str) -> Option<(&str, &str)> {
+    let close = match s.chars().next()? {
+        '`' => Some('`'),
+        '"' => Some('"'),
+        '[' => Some(']'),
+        _ => None,
+    };
+    let end = match close {
+        Some(q) => s[1..].find(q)? + 2,
+        None => s.find(char::is_whitespace).unwrap_or(s.len()),
+    };
+    Some((&s[..end], &s[end..]))
+}
+
+/// The single-quoted literals in `s`, unescaped (`''` and `\'`).
+fn quoted_literals(s: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut value = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => value.extend(chars.next()),
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    value.push('\'');
+                }
+                '\'' => break,
+                c => value.push(c),
+            }
+        }
+        values.push(value);
+    }
+    values
+}
+
+/// Lowercases a declared type, drops size/precision arguments and collapses
+/// whitespace: `VARCHAR(255)` -> `varchar`, `numeric(10, 2) unsigned` ->
+/// `numeric unsigned`.
+pub fn normalize_type(sql_type: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0;
+    for c in sql_type.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if depth == 0 => out.push(c.to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether a declared type holds numbers, e.g. `bigint unsigned` or
+/// `numeric(10, 2)`.
+pub fn is_numeric_type(declared: &str) -> bool {
+    let normalized = normalize_type(declared);
+    normalized
+        .split_whitespace()
+        .next()
+        .is_some_and(|word| NUMERIC_TYPES.contains(&word))
+}
+
+/// The most characters a string type holds: its size argument
+/// (`varchar(40)` -> 40), or 1 for `char` without one. `None` for unsized
+/// and non-string types.
+pub fn declared_length(declared: &str) -> Option<usize> {
+    let normalized = normalize_type(declared);
+    let base = SIZED_STRING_TYPES.iter().find(|t| {
+        normalized
+            .strip_prefix(**t)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    })?;
+    let Some(open) = declared.find('(') else {
+        return SINGLE_CHAR_TYPES.contains(base).then_some(1);
+    };
+    let close = declared[open..].find(')').map_or(declared.len(), |n| open + n);
+    declared[open + 1..close].trim().parse().ok()
+}
+
+/// A declared type that holds what `strategy` writes, if `declared` doesn't:
+/// `prefixed_hash` keys and `hash` digests are text of a known width, so a numeric (or any
+/// other non-string) column becomes `VARCHAR(width)`, and `fixed` values
+/// widen string columns that are too short for them. Every other strategy
+/// writes values of the column's own type, which are left alone, as are
+/// unsized text types and columns without a declared type.
+pub fn fitted_type(strategy: &ColumnStrategy, declared: &str) -> Option<String> {
+    let (width, retype) = match strategy {
+        ColumnStrategy::PrefixedHash { prefix, len } if prefix.is_empty() => (*len, true),
+        ColumnStrategy::PrefixedHash { prefix, len } => (prefix.chars().count() + 1 + len, true),
+        ColumnStrategy::Hash => (MAX_HASH_LEN, true),
+        ColumnStrategy::Fixed(value) => (value.chars().count(), false),
+        _ => return None,
+    };
+    let normalized = normalize_type(declared);
+    if normalized.is_empty() {
+        return None;
+    }
+    let base = SIZED_STRING_TYPES.iter().find(|t| {
+        normalized
+            .strip_prefix(**t)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    });
+    let Some(base) = base else {
+        let is_text = normalized.contains("text") || normalized.contains("clob") || normalized == "string";
+        return (retype && !is_text).then(|| format!("VARCHAR({})", width));
+    };
+    let Some(open) = declared.find('(') else {
+        let single = SINGLE_CHAR_TYPES.contains(base);
+        let (word, rest) = declared.split_at(declared.find(char::is_whitespace).unwrap_or(declared.len()));
+        return (single && width > 1).then(|| format!("{}({}){}", word, width, rest));
+    };
+    let close = declared[open..].find(')').map_or(declared.len(), |n| open + n);
+    let size: usize = declared[open + 1..close].trim().parse().ok()?;
+    (size < width).then(|| {
+        format!("{}({}){}", &declared[..open], width, declared.get(close + 1..).unwrap_or(""))
+    })
+}
@@ -0,0 +1,321 @@
+//! Recognition of SQL statement shapes shared by the scanner and processor.
+
+use crate::copy::{self, CopyHeader, END_OF_DATA};
+use crate::schema::Schemas;
+use std::io;
+use std::sync::Arc;
+
+/// `INSERT [LOW_PRIORITY | DELAYED | HIGH_PRIORITY] [IGNORE] [INTO]`.
+/// `INTO` is optional in MySQL and SQL Server (`INSERT [dbo].[t] (...)`).
+pub const INSERT_PREFIX: &str =
+    r"INSERT(?:\s+(?:LOW_PRIORITY|DELAYED|HIGH_PRIORITY|IGNORE))*(?:\s+INTO)?";
+/// MySQL's `INSERT ... SET` form, where `INTO` is optional.
+pub const INSERT_SET_PREFIX: &str =
+    r"INSERT(?:\s+(?:LOW_PRIORITY|DELAYED|HIGH_PRIORITY|IGNORE))*(?:\s+INTO)?";
+
+/// `UPDATE [LOW_PRIORITY] [IGNORE] [ONLY]` or `DELETE [LOW_PRIORITY]
+/// [QUICK] [IGNORE] FROM [ONLY]`, the statements `--scrub-where` rewrites.
+pub const PREDICATE_PREFIX: &str = r"(?:UPDATE(?:\s+(?:LOW_PRIORITY|IGNORE|ONLY))*|DELETE(?:\s+(?:LOW_PRIORITY|QUICK|IGNORE))*\s+FROM(?:\s+ONLY)?)";
+
+/// A table name as written after `INSERT INTO`: bare, or quoted with
+/// double quotes, backticks or brackets (which may contain spaces), and
+/// optionally schema-qualified.
+pub const TABLE_NAME: &str = r#"(?:"[^"]*"|`[^`]*`|\[[^\]]*\]|[^\s("`\[])+"#;
+
+/// Uppercases statement keywords and collapses the whitespace between them,
+/// so `insert  ignore into` is re-emitted as `INSERT IGNORE INTO`.
+pub fn normalize_keywords(prefix: &str) -> String {
+    prefix
+        .split_whitespace()
+        .map(|w| w.to_uppercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Statements the splitter keeps whole; everything else is line by line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkKind {
+    Insert,
+    CreateTable,
+    Update,
+    Delete,
+    /// A `COPY` statement; with `FROM stdin`, its data rows follow.
+    Copy,
+    /// One data row of a `COPY ... FROM stdin` block.
+    CopyData,
+    Other,
+}
+
+/// A piece of the input: one complete `INSERT`, `CREATE TABLE`, `UPDATE`,
+/// `DELETE` or `COPY` statement (which may span several lines), one row of
+/// `COPY` data, or a single line of anything else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// The original text, with lines joined by `\n`.
+    pub text: String,
+    pub first_line: usize,
+    pub last_line: usize,
+    pub kind: ChunkKind,
+    /// For `CopyData` rows, the header of their block.
+    pub copy: Option<Arc<CopyHeader>>,
+    /// The `CREATE TABLE`s seen up to and including this chunk, for chunks
+    /// rewritten while later ones are already being read. `None` means the
+    /// processor's own, which are current for serial runs.
+    pub schemas: Option<Arc<Schemas>>,
+}
+
+/// Groups input lines into [`Chunk`]s. A statement is accumulated until a
+/// line ends after a `;` that is outside quotes and comments, so
+/// semicolons and quotes in string literals, `-- ...` comments and
+/// `/* ... */` (including MySQL's `/*!40101 ... */`) don't split it.
+/// Other lines pass through one at a time; only block comments are tracked
+/// across them, so an `INSERT` inside a commented-out block stays a comment.
+/// After a `COPY ... FROM stdin` header, every line up to the `\.` that
+/// ends the block is a `CopyData` row, whatever it looks like.
+pub fn split_statements<I>(lines: I) -> Statements<I>
+where
+    I: Iterator<Item = io::Result<String>>,
+{
+    Statements {
+        lines,
+        line_no: 0,
+        in_block_comment: false,
+        pending: None,
+        copy: None,
+    }
+}
+
+pub struct Statements<I> {
+    lines: I,
+    line_no: usize,
+    in_block_comment: bool,
+    pending: Option<(Chunk, Lexer)>,
+    /// The header of the `COPY` block being read, if inside one.
+    copy: Option<Arc<CopyHeader>>,
+}
+
+impl<I: Iterator<Item = io::Result<String>>> Statements<I> {
+    /// Numbers lines as if `lines` had already been read, for input that
+    /// starts partway through a file.
+    pub fn starting_after_line(mut self, lines: usize) -> Self {
+        self.line_no = lines;
+        self
+    }
+
+    /// Whether the input read so far ends inside a `/* ... */` comment.
+    pub fn in_block_comment(&self) -> bool {
+        self.in_block_comment
+    }
+
+    /// Whether the input read so far ends inside the data of a `COPY`
+    /// block.
+    pub fn in_copy_data(&self) -> bool {
+        self.copy.is_some()
+    }
+
+    fn push_line(&mut self, line: String) -> Option<Chunk> {
+        if let Some(header) = &self.copy {
+            let header = Arc::clone(header);
+            let kind = if line == END_OF_DATA {
+                self.copy = None;
+                ChunkKind::Other
+            } else {
+                ChunkKind::CopyData
+            };
+            return Some(Chunk {
+                text: line,
+                first_line: self.line_no,
+                last_line: self.line_no,
+                kind,
+                copy: (kind == ChunkKind::CopyData).then_some(header),
+                schemas: None,
+            });
+        }
+        if let Some((mut chunk, mut lexer)) = self.pending.take() {
+            lexer.scan(&line);
+            chunk.text.push('\n');
+            chunk.text.push_str(&line);
+            chunk.last_line = self.line_no;
+            if lexer.is_complete() {
+                return Some(self.complete(chunk));
+            }
+            self.pending = Some((chunk, lexer));
+            return None;
+        }
+
+        let mut lexer = Lexer {
+            block_comment: self.in_block_comment,
+            ..Lexer::default()
+        };
+        let kind = if self.in_block_comment {
+            ChunkKind::Other
+        } else {
+            chunk_kind(&line)
+        };
+        lexer.scan(&line);
+        let chunk = Chunk {
+            text: line,
+            first_line: self.line_no,
+            last_line: self.line_no,
+            kind,
+            copy: None,
+            schemas: None,
+        };
+        if kind != ChunkKind::Other && !lexer.is_complete() {
+            self.pending = Some((chunk, lexer));
+            return None;
+        }
+        self.in_block_comment = lexer.block_comment;
+        Some(self.complete(chunk))
+    }
+
+    /// Hands out a finished statement, entering its data if it is a
+    /// `COPY ... FROM stdin`.
+    fn complete(&mut self, chunk: Chunk) -> Chunk {
+        if chunk.kind == ChunkKind::Copy {
+            self.copy = copy::parse_header(statement_body(&chunk.text)).map(Arc::new);
+        }
+        chunk
+    }
+}
+
+impl<I: Iterator<Item = io::Result<String>>> Iterator for Statements<I> {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.lines.next() {
+                Some(Ok(line)) => {
+                    self.line_no += 1;
+                    if let Some(chunk) = self.push_line(line) {
+                        return Some(Ok(chunk));
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                // An unterminated statement at EOF is still handed out whole.
+                None => return self.pending.take().map(|(chunk, _)| Ok(chunk)),
+            }
+        }
+    }
+}
+
+fn chunk_kind(line: &str) -> ChunkKind {
+    let mut words = line.split_whitespace().map(|w| w.to_ascii_uppercase());
+    match words.next().as_deref() {
+        Some("INSERT") => ChunkKind::Insert,
+        Some("UPDATE") => ChunkKind::Update,
+        Some("DELETE") => ChunkKind::Delete,
+        Some("COPY") => ChunkKind::Copy,
+        Some("CREATE") => {
+            let mut next = words.next();
+            if matches!(next.as_deref(), Some("TEMPORARY" | "TEMP")) {
+                next = words.next();
+            }
+            if next.as_deref() == Some("TABLE") {
+                ChunkKind::CreateTable
+            } else {
+                ChunkKind::Other
+            }
+        }
+        _ => ChunkKind::Other,
+    }
+}
+
+/// The statement in `text` without its terminating `;` and anything after
+/// it, or, for a statement that lacks one (the last in some dumps), without
+/// trailing whitespace and comments.
+pub fn statement_body(text: &str) -> &str {
+    let mut chars = text.char_indices().peekable();
+    let mut quote = None;
+    let mut escape = false;
+    let mut end = 0;
+    while let Some((i, c)) = chars.next() {
+        if let Some(q) = quote {
+            if escape {
+                escape = false;
+            } else if c == '\\' && q != '`' {
+                escape = true;
+            } else if c == q {
+                quote = None;
+            }
+            end = i + c.len_utf8();
+            continue;
+        }
+        match c {
+            ';' => break,
+            '-' if chars.peek().map(|&(_, c)| c) == Some('-') => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek().map(|&(_, c)| c) == Some('*') => {
+                chars.next();
+                let mut prev = ' ';
+                for (_, c) in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                if matches!(c, '\'' | '"' | '`') {
+                    quote = Some(c);
+                }
+                end = i + c.len_utf8();
+            }
+        }
+    }
+    &text[..end]
+}
+
+/// Tracks just enough lexical state to find statement terminators.
+#[derive(Debug, Default)]
+struct Lexer {
+    quote: Option<char>,
+    block_comment: bool,
+    terminated: bool,
+}
+
+impl Lexer {
+    fn scan(&mut self, line: &str) {
+        let mut chars = line.chars().peekable();
+        let mut escape = false;
+        while let Some(c) = chars.next() {
+            if self.block_comment {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    self.block_comment = false;
+                }
+                continue;
+            }
+            if let Some(quote) = self.quote {
+                if escape {
+                    escape = false;
+                } else if c == '\\' && quote != '`' {
+                    escape = true;
+                } else if c == quote {
+                    self.quote = None;
+                }
+                continue;
+            }
+            match c {
+                '\'' | '"' | '`' => self.quote = Some(c),
+                '-' if chars.peek() == Some(&'-') => break,
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    self.block_comment = true;
+                }
+                ';' => self.terminated = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.terminated && self.quote.is_none() && !self.block_comment
+    }
+}
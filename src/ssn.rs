@@ -0,0 +1,36 @@
+//! Fake US Social Security numbers that can never belong to anyone.
+//!
+//! The SSA never issues area numbers (the first three digits) 000, 666 or
+//! 900-999. The 900-999 range is used by IRS individual taxpayer
+//! identification numbers instead, but only with group numbers (the middle
+//! two digits) 50-65, 70-88, 90-92 and 94-99. So an area in 900-999 with a
+//! group in 01-49 is neither a valid SSN nor an ITIN, and a fake built that
+//! way can't collide with a real person's number.
+
+use rand::Rng;
+
+/// Nine digits: an area in 900-999, a group in 01-49 and a serial in
+/// 0001-9999 (0000 is never issued either).
+pub fn generate<R: Rng>(rng: &mut R) -> String {
+    format!(
+        "{:03}{:02}{:04}",
+        rng.gen_range(900..=999),
+        rng.gen_range(1..=49),
+        rng.gen_range(1..=9999)
+    )
+}
+
+/// A fake SSN written like `original`: its digits are replaced in place
+/// when it has exactly nine (keeping `-`, spaces or no separators at all),
+/// otherwise the result is `9XX-XX-XXXX`.
+pub fn fake_like<R: Rng>(original: &str, rng: &mut R) -> String {
+    let digits = generate(rng);
+    if original.chars().filter(char::is_ascii_digit).count() != 9 {
+        return format!("{}-{}-{}", &digits[..3], &digits[3..5], &digits[5..]);
+    }
+    let mut fake = digits.chars();
+    original
+        .chars()
+        .map(|c| if c.is_ascii_digit() { fake.next().unwrap_or(c) } else { c })
+        .collect()
+}
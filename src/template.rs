@@ -0,0 +1,173 @@
+//! Templates for the `template` strategy: literal text with placeholders
+//! filled from the value's seed, e.g. `EMP-{seq:5}` or
+//! `{firstname:lower}.{lastname:lower}@corp.test`.
+//!
+//! | Placeholder | Fills in |
+//! | :--- | :--- |
+//! | `{seq}`, `{seq:N}` | The value's number in order of first appearance in the column, zero-padded to `N` digits |
+//! | `{rand:N}` | `N` seeded digits (1 to 32) |
+//! | `{hex:N}` | `N` seeded lowercase hex digits (1 to 32) |
+//! | `{firstname}`, `{lastname}`, `{name}`, `{word}` | A seeded fake first name, last name, full name or lorem word; add `:lower` or `:upper` to change its case |
+//!
+//! `{{` and `}}` stand for literal braces.
+
+use fake::faker::lorem::en::Word;
+use fake::faker::name::en::{FirstName, LastName, Name};
+use fake::Fake;
+use rand::Rng;
+
+/// Most digits `{rand:N}` and `{hex:N}` accept.
+const MAX_RANDOM_DIGITS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Faked {
+    FirstName,
+    LastName,
+    Name,
+    Word,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    AsIs,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Seq { width: usize },
+    Digits(usize),
+    Hex(usize),
+    Fake(Faked, Case),
+}
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Parses `template`, or says what is wrong with it.
+    pub fn parse(template: &str) -> Result<Template, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut inner = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => inner.push(c),
+                            None => return Err(format!("has an unclosed placeholder `{{{}`", inner)),
+                        }
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(placeholder(&inner)?);
+                }
+                '}' => return Err("has a `}` without `{` (write `}}` for a literal brace)".to_string()),
+                '\'' | '\\' => return Err("contains a quote or backslash, which breaks SQL".to_string()),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        if parts.iter().all(|p| matches!(p, Part::Text(_))) {
+            return Err("has no placeholders; use `fixed` for a constant".to_string());
+        }
+        Ok(Template { parts })
+    }
+
+    /// Whether the template numbers values with `{seq}`, which makes its
+    /// output depend on input order.
+    pub fn uses_seq(&self) -> bool {
+        self.parts.iter().any(|p| matches!(p, Part::Seq { .. }))
+    }
+
+    /// Fills in the placeholders, with `seq` for `{seq}` (1 if `None`) and
+    /// `rng` for everything else.
+    pub fn render<R: Rng>(&self, seq: Option<usize>, rng: &mut R) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Seq { width } => out.push_str(&format!("{:0width$}", seq.unwrap_or(1), width = *width)),
+                Part::Digits(n) => out.extend((0..*n).map(|_| char::from(b'0' + rng.gen_range(0..10)))),
+                Part::Hex(n) => out.extend((0..*n).map(|_| {
+                    char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0')
+                })),
+                Part::Fake(faked, case) => {
+                    let fake: String = match faked {
+                        Faked::FirstName => FirstName().fake_with_rng(rng),
+                        Faked::LastName => LastName().fake_with_rng(rng),
+                        Faked::Name => Name().fake_with_rng(rng),
+                        Faked::Word => Word().fake_with_rng(rng),
+                    };
+                    out.push_str(&match case {
+                        Case::AsIs => fake,
+                        Case::Lower => fake.to_lowercase(),
+                        Case::Upper => fake.to_uppercase(),
+                    });
+                }
+            }
+        }
+        out
+    }
+}
+
+/// The part for the placeholder `{inner}`.
+fn placeholder(inner: &str) -> Result<Part, String> {
+    let (name, arg) = match inner.split_once(':') {
+        Some((name, arg)) => (name.trim(), Some(arg.trim())),
+        None => (inner.trim(), None),
+    };
+    let digits = |arg: Option<&str>| -> Result<usize, String> {
+        match arg.map(str::parse::<usize>) {
+            Some(Ok(n)) if (1..=MAX_RANDOM_DIGITS).contains(&n) => Ok(n),
+            _ => Err(format!("`{{{}}}` needs a digit count from 1 to {}, e.g. `{{{}:6}}`", inner, MAX_RANDOM_DIGITS, name)),
+        }
+    };
+    let faked = match name {
+        "seq" => {
+            return match arg.map(str::parse::<usize>) {
+                None => Ok(Part::Seq { width: 0 }),
+                Some(Ok(width)) if width <= MAX_RANDOM_DIGITS => Ok(Part::Seq { width }),
+                _ => Err(format!("`{{{}}}` needs a width from 0 to {}", inner, MAX_RANDOM_DIGITS)),
+            }
+        }
+        "rand" => return Ok(Part::Digits(digits(arg)?)),
+        "hex" => return Ok(Part::Hex(digits(arg)?)),
+        "firstname" => Faked::FirstName,
+        "lastname" => Faked::LastName,
+        "name" => Faked::Name,
+        "word" => Faked::Word,
+        _ => {
+            return Err(format!(
+                "has an unknown placeholder `{{{}}}`; use seq, rand:N, hex:N, firstname, lastname, name or word",
+                inner
+            ))
+        }
+    };
+    let case = match arg {
+        None => Case::AsIs,
+        Some("lower") => Case::Lower,
+        Some("upper") => Case::Upper,
+        Some(_) => return Err(format!("`{{{}}}`: the only modifiers are `lower` and `upper`", inner)),
+    };
+    Ok(Part::Fake(faked, case))
+}
@@ -1,23 +1,421 @@
-use crate::config::ColumnStrategy;
-use fake::faker::internet::en::SafeEmail;
+use crate::baseline::Incremental;
+use crate::binary;
+use crate::cache::LruCache;
+use crate::checksum;
+use crate::command::CommandProcess;
+use crate::config::{ColumnOptions, ColumnStrategy, GeoAxis, OnError, MAX_HASH_LEN};
+use crate::dates;
+use crate::detect;
+use crate::failure::Failure;
+use crate::json;
+use crate::names::{self, Gender};
+use crate::pgarray::{self, ArrayItem};
+use crate::pseudonym::pseudonym;
+use crate::ssn;
+use crate::template::Template;
+use crate::vin;
+use anyhow::{anyhow, Context, Result};
+use fake::faker::internet::en::{DomainSuffix, SafeEmail};
+use fake::faker::job::en::Title as JobTitle;
+use fake::faker::lorem::en::Word;
 use fake::faker::name::en::{FirstName, LastName, Name};
 use fake::faker::phone_number::en::PhoneNumber;
 use fake::Fake;
 use rand::rngs::StdRng;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Approximate length of one degree of latitude, in meters.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// How many seeded candidates to try before truncating to `max_len`.
+const MAX_LEN_ATTEMPTS: usize = 10;
+
+/// How many re-seeded candidates a `unique` column tries before giving up.
+const MAX_UNIQUE_ATTEMPTS: u32 = 100;
+
+/// How many re-seeded candidates a `must_match` column tries before giving
+/// up.
+const MAX_MATCH_ATTEMPTS: u32 = 100;
+
+/// Rough per-entry cost of a `HashMap`/`HashSet` entry of `String`s
+/// (headers, hash and spare capacity) on top of the text itself, for
+/// `--max-memory`.
+const ENTRY_OVERHEAD: usize = 64;
+
+/// The resolved strategy and options for one column of a statement.
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnRule<'a> {
+    pub strategy: &'a ColumnStrategy,
+    pub options: &'a ColumnOptions,
+    /// Members of the column's `ENUM(...)` type, if the schema declares one.
+    pub enum_values: &'a [String],
+}
+
+/// Pseudonyms handed out so far for one `unique` column. Holds every
+/// distinct original and its output, so memory grows with the column's
+/// cardinality.
+#[derive(Default)]
+struct UniqueColumn {
+    assigned: HashMap<String, String>,
+    used: HashSet<String>,
+}
+
+/// Recent faker outputs, keyed by a hash of the seed, strategy, options and
+/// original value. The original is stored alongside the output to rule out
+/// hash collisions.
+type FakeCache = LruCache<u64, (String, String)>;
 
 pub struct Transformer {
     global_seed: u64,
+    commands: Mutex<HashMap<(String, Vec<String>), CommandProcess>>,
+    unique: Mutex<HashMap<(String, String), UniqueColumn>>,
+    tokens: Mutex<HashMap<(String, String), HashMap<String, usize>>>,
+    cache: Option<Mutex<FakeCache>>,
+    /// Values `date_shift` and `geo_fuzz` couldn't parse.
+    unparseable: AtomicUsize,
+    /// Budget in bytes for `unique` and `tokenize` state, if any.
+    memory_limit: Option<usize>,
+    /// Estimated bytes held by `unique` and `tokenize` state.
+    state_bytes: AtomicUsize,
+    incremental: Option<Incremental>,
+    /// Compiled `must_match` patterns, by pattern.
+    patterns: Mutex<HashMap<String, Regex>>,
 }
 
 impl Transformer {
     pub fn new(seed: u64) -> Self {
-        Self { global_seed: seed }
+        Self {
+            global_seed: seed,
+            commands: Mutex::new(HashMap::new()),
+            unique: Mutex::new(HashMap::new()),
+            tokens: Mutex::new(HashMap::new()),
+            cache: None,
+            unparseable: AtomicUsize::new(0),
+            memory_limit: None,
+            state_bytes: AtomicUsize::new(0),
+            incremental: None,
+            patterns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remembers the outputs of the faker-backed strategies for up to
+    /// `capacity` recent values, so repeated values (`country`, `status`)
+    /// skip the faker. Outputs are identical with or without the cache;
+    /// a `capacity` of 0 disables it.
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache = (capacity > 0).then(|| Mutex::new(LruCache::new(capacity)));
+        self
+    }
+
+    /// Fails the run, naming the column and strategy, once the state kept by
+    /// `unique` columns and `tokenize` (which grows with each column's
+    /// number of distinct values) is estimated to exceed `bytes`. `None`
+    /// lets it grow without bound.
+    pub fn with_memory_limit(mut self, bytes: Option<usize>) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// Reuses the values of unchanged rows from a baseline, and records
+    /// the rows of tables with a `key` column in a mapping file (see
+    /// `baseline.rs`).
+    pub fn with_incremental(mut self, incremental: Option<Incremental>) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    pub fn incremental(&self) -> Option<&Incremental> {
+        self.incremental.as_ref()
+    }
+
+    /// Estimated bytes held by the state of `unique` columns and `tokenize`.
+    pub fn state_bytes(&self) -> usize {
+        self.state_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Accounts for `bytes` more of state for `table.column`, or fails if
+    /// that would exceed the memory limit.
+    fn charge(&self, bytes: usize, table: &str, column: &str, strategy: &str) -> Result<()> {
+        let total = self.state_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        match self.memory_limit {
+            Some(limit) if total > limit => Err(anyhow!(
+                "{}.{} ({}) pushed the state of stateful strategies past --max-memory ({} bytes). \
+                 Raise the limit, or use a stateless strategy such as `hash` for this column.",
+                table,
+                column,
+                strategy,
+                limit
+            )
+            .context(Failure::MemoryLimit)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.global_seed
+    }
+
+    /// How many values so far `date_shift` or `geo_fuzz` couldn't parse and
+    /// replaced as their `on_error` says.
+    pub fn unparseable_values(&self) -> usize {
+        self.unparseable.load(Ordering::Relaxed)
+    }
+
+    /// Transforms a whole row, giving strategies that need it access to
+    /// sibling columns. `rules` is parallel to `columns` and `values`.
+    /// `key` is the table's primary key column, for incremental runs.
+    pub fn transform_row(
+        &self,
+        table: &str,
+        columns: &[String],
+        values: &[String],
+        rules: &[ColumnRule],
+        seed_override: Option<u64>,
+        key: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let (Some(incremental), Some(key)) = (&self.incremental, key) else {
+            return self.anonymize_row(table, columns, values, rules, seed_override);
+        };
+        let Some(id) = incremental.row_id(table, columns, values, key) else {
+            return self.anonymize_row(table, columns, values, rules, seed_override);
+        };
+        let result = match incremental.reuse(table, &id, values.len()) {
+            Some(previous) => previous,
+            None => self.anonymize_row(table, columns, values, rules, seed_override)?,
+        };
+        incremental.record(table, id, &result)?;
+        Ok(result)
+    }
+
+    fn anonymize_row(
+        &self,
+        table: &str,
+        columns: &[String],
+        values: &[String],
+        rules: &[ColumnRule],
+        seed_override: Option<u64>,
+    ) -> Result<Vec<String>> {
+        let seed = seed_override.unwrap_or(self.global_seed);
+
+        // All geo columns of a row share one seed so lat/lon move together.
+        let mut geo_hasher = DefaultHasher::new();
+        seed.hash(&mut geo_hasher);
+        let mut lat_hint = None;
+        for (i, rule) in rules.iter().enumerate() {
+            if let ColumnStrategy::GeoFuzz { axis, .. } = rule.strategy {
+                values[i].hash(&mut geo_hasher);
+                let axis = axis.unwrap_or_else(|| geo_axis_for(&columns[i]));
+                if axis == GeoAxis::Lat && lat_hint.is_none() {
+                    lat_hint = unquote(&values[i]).trim().parse::<f64>().ok();
+                }
+            }
+        }
+        let geo_seed = geo_hasher.finish();
+
+        let mut result = Vec::with_capacity(values.len());
+        for (i, rule) in rules.iter().enumerate() {
+            let seed = match row_key(columns, values, rule.options.seed_from.as_deref()) {
+                Some(key) => {
+                    let mut hasher = DefaultHasher::new();
+                    seed.hash(&mut hasher);
+                    columns[i].hash(&mut hasher);
+                    key.hash(&mut hasher);
+                    hasher.finish()
+                }
+                None => seed,
+            };
+            let new_val = match rule.strategy {
+                ColumnStrategy::GeoFuzz { radius_m, axis, on_error } => {
+                    let axis = axis.unwrap_or_else(|| geo_axis_for(&columns[i]));
+                    self.geo_fuzz(&values[i], *radius_m, axis, on_error, geo_seed, lat_hint)
+                }
+                ColumnStrategy::Enum => enum_member(&values[i], rule.enum_values, seed),
+                ColumnStrategy::GenderedFirstName { gender_column } => {
+                    let gender = row_gender(columns, values, gender_column.as_deref());
+                    gendered_first_name(&values[i], gender, rule.options, seed)
+                }
+                ColumnStrategy::Tokenize { prefix } => {
+                    self.tokenize(table, &columns[i], &values[i], prefix)?
+                }
+                ColumnStrategy::Template(template)
+                    if Template::parse(template).is_ok_and(|t| t.uses_seq()) =>
+                {
+                    self.template(table, &columns[i], &values[i], template, seed)?
+                }
+                // Filled in below, once the name columns are done.
+                ColumnStrategy::CompanyEmail { .. } => String::new(),
+                // The global seed alone, and never re-seeded for `unique`, so
+                // every column in the namespace agrees.
+                strategy @ ColumnStrategy::Pseudonym { .. } => {
+                    self.transform_seeded(&values[i], strategy, rule.options, self.global_seed)?
+                }
+                strategy if rule.options.unique => self.transform_unique(
+                    table,
+                    &columns[i],
+                    &values[i],
+                    strategy,
+                    rule.options,
+                    seed,
+                )?,
+                strategy => self.transform_seeded(&values[i], strategy, rule.options, seed)?,
+            };
+            result.push(new_val);
+        }
+
+        // Company emails read the row's names once they are anonymized.
+        for (i, rule) in rules.iter().enumerate() {
+            if let ColumnStrategy::CompanyEmail { domain, format } = rule.strategy {
+                let names = row_names(rules, &result);
+                result[i] = company_email(&values[i], names, domain, format, seed);
+            }
+        }
+
+        // Then linked addresses take the domain of the row's company.
+        for (i, rule) in rules.iter().enumerate() {
+            let Some(company) = &rule.options.domain_from else {
+                continue;
+            };
+            if *rule.strategy == ColumnStrategy::Keep || result[i].eq_ignore_ascii_case("NULL") {
+                continue;
+            }
+            let Some(name) = columns
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(company))
+                .map(|j| &result[j])
+                .filter(|name| !name.eq_ignore_ascii_case("NULL") && !unquote(name).is_empty())
+            else {
+                continue;
+            };
+            let domain = company_domain(&unescape_literal(unquote(name)), seed);
+            result[i] = with_domain(&result[i], &domain);
+        }
+        Ok(result)
     }
 
-    pub fn transform(&self, value: &str, strategy: &ColumnStrategy) -> String {
+    /// Transforms a single value with the global seed and default options.
+    pub fn transform(&self, value: &str, strategy: &ColumnStrategy) -> Result<String> {
+        self.transform_seeded(value, strategy, &ColumnOptions::default(), self.global_seed)
+    }
+
+    /// Transforms a single value, seeded by `seed` and the value itself.
+    /// With `must_match`, candidates are re-seeded until one matches.
+    pub fn transform_seeded(
+        &self,
+        value: &str,
+        strategy: &ColumnStrategy,
+        options: &ColumnOptions,
+        seed: u64,
+    ) -> Result<String> {
+        let Some(pattern) = &options.must_match else {
+            return self.generate_cached(value, strategy, options, seed);
+        };
+        if *strategy == ColumnStrategy::Keep {
+            return Ok(value.to_string());
+        }
+        let regex = self.pattern(pattern)?;
+        let mut candidate = String::new();
+        for attempt in 0..MAX_MATCH_ATTEMPTS {
+            candidate = self.generate_cached(value, strategy, options, attempt_seed(seed, attempt))?;
+            if candidate.eq_ignore_ascii_case("NULL") || regex.is_match(&unescape_literal(unquote(&candidate))) {
+                return Ok(candidate);
+            }
+        }
+        Err(anyhow!(
+            "No `{}` value matched must_match {:?} in {} attempts (last: {})",
+            strategy.name(),
+            pattern,
+            MAX_MATCH_ATTEMPTS,
+            candidate
+        ))
+    }
+
+    /// `pattern` compiled to match whole values.
+    fn pattern(&self, pattern: &str) -> Result<Regex> {
+        let mut patterns = self.patterns.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(regex) = patterns.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Regex::new(&format!("^(?:{})$", pattern))
+            .with_context(|| format!("Invalid must_match pattern: {:?}", pattern))
+            .context(Failure::Config)?;
+        patterns.insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+
+    /// [`Self::generate`] through the fake cache, if there is one.
+    fn generate_cached(
+        &self,
+        value: &str,
+        strategy: &ColumnStrategy,
+        options: &ColumnOptions,
+        seed: u64,
+    ) -> Result<String> {
+        let (Some(cache), Some(key)) = (&self.cache, cache_key(value, strategy, options, seed)) else {
+            return self.generate(value, strategy, options, seed);
+        };
+        let lock = || cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((original, output)) = lock().get(&key) {
+            if original == value {
+                return Ok(output.clone());
+            }
+        }
+        let output = self.generate(value, strategy, options, seed)?;
+        lock().insert(key, (value.to_string(), output.clone()));
+        Ok(output)
+    }
+
+    fn generate(
+        &self,
+        value: &str,
+        strategy: &ColumnStrategy,
+        options: &ColumnOptions,
+        seed: u64,
+    ) -> Result<String> {
+        // SQL `NULL` is missing data, not a value to fake. (The string
+        // `'NULL'` is data; see `QuotedNull`.) Only `fixed` replaces it, as
+        // a constant is meant for every row.
+        if value.eq_ignore_ascii_case("NULL") {
+            return Ok(match strategy {
+                ColumnStrategy::Fixed(s) => fixed_literal(s),
+                _ => value.to_string(),
+            });
+        }
+        // A national character string (`N'...'`, as SQL Server writes
+        // Unicode text) is a string literal like any other; the fake keeps
+        // the prefix if it is one too.
+        if let Some(literal) = value
+            .strip_prefix(['N', 'n'])
+            .filter(|l| l.len() >= 2 && l.starts_with('\'') && l.ends_with('\''))
+        {
+            let fake = self.generate(literal, strategy, options, seed)?;
+            return Ok(if fake.starts_with('\'') { format!("N{}", fake) } else { fake });
+        }
+        // Only the literal of a date conversion (`TO_DATE('2024-01-15',
+        // 'YYYY-MM-DD')`) is shifted, keeping the call around it.
+        if let ColumnStrategy::DateShift { .. } = strategy {
+            if let Some(literal) = date_conversion(value) {
+                let shifted = self.generate(&value[literal.clone()], strategy, options, seed)?;
+                return Ok(format!("{}{}{}", &value[..literal.start], shifted, &value[literal.end..]));
+            }
+        }
+
+        // Faking binary data as text would corrupt it (and usually the
+        // statement), so only strategies meant for it touch binary literals.
+        if binary::is_literal(value)
+            && !matches!(strategy, ColumnStrategy::RandomBytes | ColumnStrategy::RandomNull { .. })
+        {
+            return Ok(value.to_string());
+        }
+
         let is_quoted = value.starts_with('\'') && value.ends_with('\'');
         let clean_val = if is_quoted {
             &value[1..value.len() - 1]
@@ -25,61 +423,481 @@ impl Transformer {
             value
         };
 
+        let base_seed = seed;
         let mut hasher = DefaultHasher::new();
-        self.global_seed.hash(&mut hasher);
+        seed.hash(&mut hasher);
         clean_val.hash(&mut hasher);
         let seed = hasher.finish();
         let mut rng = StdRng::seed_from_u64(seed);
 
         let new_val = match strategy {
-            ColumnStrategy::FirstName => FirstName().fake_with_rng(&mut rng),
-            ColumnStrategy::LastName => LastName().fake_with_rng(&mut rng),
-            ColumnStrategy::FullName => Name().fake_with_rng(&mut rng),
+            ColumnStrategy::FirstName => {
+                fit_len(&mut rng, options.max_len, |rng| FirstName().fake_with_rng(rng))
+            }
+            ColumnStrategy::LastName => {
+                fit_len(&mut rng, options.max_len, |rng| LastName().fake_with_rng(rng))
+            }
+            ColumnStrategy::FullName => {
+                fit_len(&mut rng, options.max_len, |rng| Name().fake_with_rng(rng))
+            }
+            ColumnStrategy::JobTitle => JobTitle().fake_with_rng(&mut rng),
+            ColumnStrategy::NamePrefix => name_prefix(clean_val, &mut rng),
             ColumnStrategy::Email => SafeEmail().fake_with_rng(&mut rng),
             ColumnStrategy::Phone => PhoneNumber().fake_with_rng(&mut rng),
-            ColumnStrategy::Mask => {
-                if clean_val.contains('@') {
-                    let parts: Vec<&str> = clean_val.split('@').collect();
-                    if parts.len() == 2 {
-                        let name = parts[0];
-                        let domain = parts[1];
-                        let masked_name = if name.len() > 1 {
-                            format!("{}***", &name[0..1])
-                        } else {
-                            "***".to_string()
-                        };
-                        format!("{}@{}", masked_name, domain)
-                    } else {
-                        "***@unknown.com".to_string()
-                    }
-                } else {
-                    if clean_val.len() > 1 {
-                        format!("{}***", &clean_val[0..1])
-                    } else {
-                        "*".to_string()
+            ColumnStrategy::Mask { char, reveal_start, reveal_end } => {
+                match clean_val.split_once('@') {
+                    Some((name, domain)) if !domain.contains('@') => {
+                        let name = mask(name, *char, *reveal_start, *reveal_end)
+                            .unwrap_or_else(|| char.to_string().repeat(3));
+                        format!("{}@{}", name, domain)
                     }
+                    Some(_) => "***@unknown.com".to_string(),
+                    None => mask(clean_val, *char, *reveal_start, *reveal_end)
+                        .unwrap_or_else(|| char.to_string()),
                 }
             }
             ColumnStrategy::Fixed(s) => s.clone(),
-            ColumnStrategy::Keep => return value.to_string(),
+            ColumnStrategy::Keep => return Ok(value.to_string()),
+            ColumnStrategy::DigitScramble => digit_scramble(clean_val, base_seed),
+            ColumnStrategy::Hostname => hostname(clean_val, base_seed),
+            ColumnStrategy::Url { keep_path } => url(clean_val, *keep_path, base_seed, &mut rng),
+            ColumnStrategy::ZipCode { keep_prefix } => zip_code(clean_val, *keep_prefix, &mut rng),
+            ColumnStrategy::Vin => {
+                if clean_val.chars().count() == 17 {
+                    vin::generate(&mut rng)
+                } else {
+                    vin::scramble(clean_val, &mut rng)
+                }
+            }
+            ColumnStrategy::Ssn => ssn::fake_like(clean_val, &mut rng),
+            ColumnStrategy::RandomBytes => {
+                return Ok(binary::randomize(value, &mut rng).unwrap_or_else(|| value.to_string()));
+            }
+            ColumnStrategy::ArrayOf(inner) => match pgarray::parse(clean_val) {
+                Some(items) => {
+                    let items = self.transform_array(&items, inner, options, seed)?;
+                    pgarray::serialize(&items)
+                }
+                None => return Ok(value.to_string()),
+            },
+            ColumnStrategy::RandomNull { probability } => {
+                let probability = probability.clamp(0.0, 1.0);
+                return if rng.gen::<f64>() < probability {
+                    Ok("NULL".to_string())
+                } else {
+                    Ok(value.to_string())
+                };
+            }
+            // A number even where the original was quoted.
+            ColumnStrategy::RandomInt { min, max } => {
+                return Ok(rng.gen_range(*min..=(*max).max(*min)).to_string());
+            }
+            ColumnStrategy::DateShift { max_days, on_error } => {
+                let max_days = max_days.abs();
+                if max_days == 0 {
+                    return Ok(value.to_string());
+                }
+                let magnitude = rng.gen_range(1..=max_days);
+                let days = if rng.gen::<bool>() { magnitude } else { -magnitude };
+                match dates::shift_days(clean_val, days) {
+                    Some(shifted) => shifted,
+                    None => return Ok(self.unparseable(value, on_error)),
+                }
+            }
+            ColumnStrategy::GeoFuzz { radius_m, axis, on_error } => {
+                let axis = axis.unwrap_or(GeoAxis::Lat);
+                return Ok(self.geo_fuzz(value, *radius_m, axis, on_error, seed, None));
+            }
+            ColumnStrategy::Command { program, args } => {
+                self.run_command(program, args, clean_val)?
+            }
+            // The enum members come from the schema, see `transform_row`.
+            ColumnStrategy::Enum => return Ok(value.to_string()),
+            // Without a column to number within, values share one sequence
+            // per prefix.
+            ColumnStrategy::Tokenize { prefix } => return self.tokenize("", "", value, prefix),
+            // Always quoted: a hex digest isn't a number, even for numeric keys.
+            ColumnStrategy::PrefixedHash { prefix, len } => {
+                return Ok(format!("'{}'", prefixed_hash(clean_val, prefix, *len, base_seed)));
+            }
+            // Quoted even where the original wasn't, for the same reason.
+            ColumnStrategy::Hash => {
+                return Ok(format!("'{}'", prefixed_hash(clean_val, "", MAX_HASH_LEN, base_seed)));
+            }
+            ColumnStrategy::Pseudonym { namespace } => pseudonym(clean_val, namespace, base_seed),
+            ColumnStrategy::Template(template) => return self.template("", "", value, template, base_seed),
+            ColumnStrategy::JsonPii => return self.json_pii(value, options, base_seed),
+            ColumnStrategy::ScrubText => return self.scrub_text(value, options, base_seed),
+            // Without the row there are no names to build on.
+            ColumnStrategy::CompanyEmail { domain, format } => {
+                return Ok(company_email(value, (None, None), domain, format, base_seed));
+            }
+            // Without the row there is no gender to match.
+            ColumnStrategy::GenderedFirstName { .. } => {
+                return Ok(gendered_first_name(value, Gender::Neutral, options, base_seed));
+            }
+        };
+
+        if is_quoted {
+            Ok(format!("'{}'", escape_quotes(&new_val)))
+        } else {
+            Ok(new_val)
+        }
+    }
+
+    /// Like `transform_seeded`, but re-seeds with an attempt counter until
+    /// the output hasn't been given to a different original in this column.
+    /// NULLs are exempt, as SQL allows several in a `UNIQUE` column.
+    fn transform_unique(
+        &self,
+        table: &str,
+        column: &str,
+        value: &str,
+        strategy: &ColumnStrategy,
+        options: &ColumnOptions,
+        seed: u64,
+    ) -> Result<String> {
+        if value.eq_ignore_ascii_case("NULL") {
+            return self.transform_seeded(value, strategy, options, seed);
+        }
+        let mut unique = self.unique.lock().unwrap_or_else(|e| e.into_inner());
+        let state = unique
+            .entry((table.to_string(), column.to_string()))
+            .or_default();
+        if let Some(existing) = state.assigned.get(value) {
+            return Ok(existing.clone());
+        }
+
+        for attempt in 0..MAX_UNIQUE_ATTEMPTS {
+            let candidate = self.transform_seeded(value, strategy, options, attempt_seed(seed, attempt))?;
+            if candidate == "NULL" || !state.used.contains(&candidate) {
+                let bytes = value.len() + 2 * candidate.len() + 2 * ENTRY_OVERHEAD;
+                self.charge(bytes, table, column, &format!("unique {}", strategy.name()))?;
+                if candidate != "NULL" {
+                    state.used.insert(candidate.clone());
+                }
+                state.assigned.insert(value.to_string(), candidate.clone());
+                return Ok(candidate);
+            }
+        }
+        Err(anyhow!(
+            "Could not find a unique value for {}.{} after {} attempts",
+            table,
+            column,
+            MAX_UNIQUE_ATTEMPTS
+        ))
+    }
+
+    /// The placeholder for `value` in `table.column`: `prefix_N`, where N
+    /// counts the distinct values seen in the column so far (just `N` with
+    /// an empty prefix). NULLs are kept.
+    fn tokenize(&self, table: &str, column: &str, value: &str, prefix: &str) -> Result<String> {
+        if value.eq_ignore_ascii_case("NULL") {
+            return Ok(value.to_string());
+        }
+        let n = self.sequence_number(table, column, value, "tokenize")?;
+        let token = if prefix.is_empty() {
+            n.to_string()
+        } else {
+            format!("{}_{}", prefix, n)
+        };
+        if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+            Ok(format!("'{}'", token))
+        } else {
+            Ok(token)
+        }
+    }
+
+    /// `value`'s number in order of first appearance in `table.column`,
+    /// for `tokenize` and a template's `{seq}`.
+    fn sequence_number(&self, table: &str, column: &str, value: &str, strategy: &str) -> Result<usize> {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        let seen = tokens
+            .entry((table.to_string(), column.to_string()))
+            .or_default();
+        if let Some(&n) = seen.get(value) {
+            return Ok(n);
+        }
+        self.charge(value.len() + ENTRY_OVERHEAD, table, column, strategy)?;
+        let n = seen.len() + 1;
+        seen.insert(value.to_string(), n);
+        Ok(n)
+    }
+
+    /// Fills `template` for `value`. Numeric results stay bare for unquoted
+    /// values; everything else is written as a string. NULLs are kept.
+    fn template(&self, table: &str, column: &str, value: &str, template: &str, seed: u64) -> Result<String> {
+        if value.eq_ignore_ascii_case("NULL") {
+            return Ok(value.to_string());
+        }
+        let template = Template::parse(template).map_err(|e| anyhow!("Invalid template: {}", e))?;
+        let seq = if template.uses_seq() {
+            Some(self.sequence_number(table, column, value, "template")?)
+        } else {
+            None
+        };
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        unquote(value).hash(&mut hasher);
+        let mut rng = StdRng::seed_from_u64(hasher.finish());
+        let filled = template.render(seq, &mut rng);
+        let is_quoted = value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'');
+        if !is_quoted && !filled.is_empty() && filled.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(filled)
+        } else {
+            Ok(format!("'{}'", filled.replace('\'', "''")))
+        }
+    }
+
+    fn transform_array(
+        &self,
+        items: &[ArrayItem],
+        inner: &ColumnStrategy,
+        options: &ColumnOptions,
+        seed: u64,
+    ) -> Result<Vec<ArrayItem>> {
+        items
+            .iter()
+            .map(|item| {
+                Ok(match item {
+                    ArrayItem::Null => ArrayItem::Null,
+                    ArrayItem::Nested(nested) => {
+                        ArrayItem::Nested(self.transform_array(nested, inner, options, seed)?)
+                    }
+                    ArrayItem::Value(element) => {
+                        let new_val = self.transform_seeded(element, inner, options, seed)?;
+                        // Null-producing strategies emit a bare NULL. The
+                        // array itself is in a string literal, so elements
+                        // lose their own quotes and escape any inside.
+                        if new_val == "NULL" {
+                            ArrayItem::Null
+                        } else {
+                            ArrayItem::Value(escape_quotes(unquote(&new_val)).into_owned())
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Fakes the PII-looking leaves of a quoted JSON document, each seeded
+    /// like a column value of its detected strategy, so an email gets the
+    /// same pseudonym inside a document as in an `email` column.
+    fn json_pii(&self, value: &str, options: &ColumnOptions, seed: u64) -> Result<String> {
+        let Some(body) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) else {
+            return Ok(self.unparseable(value, &OnError::Keep));
+        };
+        let (document, escaping) = json::decode_literal(body);
+        let refaked = json::refake(&document, |leaf, strategy| {
+            self.transform_seeded(leaf, strategy, options, seed)
+        })?;
+        match refaked {
+            Some(document) => Ok(format!("'{}'", json::encode_literal(&document, escaping))),
+            None => Ok(self.unparseable(value, &OnError::Keep)),
+        }
+    }
+
+    /// Replaces the PII found inside a text value, each match seeded like
+    /// a column value of its strategy. The literal is decoded first, so
+    /// escapes can't run into a match, and re-escaped the same way.
+    fn scrub_text(&self, value: &str, options: &ColumnOptions, seed: u64) -> Result<String> {
+        let Some(body) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) else {
+            return Ok(value.to_string());
+        };
+        let (text, escaping) = json::decode_literal(body);
+        let found = detect::find_in_text(&text);
+        if found.is_empty() {
+            return Ok(value.to_string());
+        }
+        let mut scrubbed = String::with_capacity(text.len());
+        let mut copied = 0;
+        for (range, strategy) in found {
+            scrubbed.push_str(&text[copied..range.start]);
+            scrubbed.push_str(&self.transform_seeded(&text[range.clone()], strategy, options, seed)?);
+            copied = range.end;
+        }
+        scrubbed.push_str(&text[copied..]);
+        Ok(format!("'{}'", json::encode_literal(&scrubbed, escaping)))
+    }
+
+    /// Moves a coordinate by a random offset uniformly distributed within a
+    /// disc of `radius_m`, keeping the original number of decimal places.
+    /// Values that don't parse as numbers are returned unchanged.
+    fn geo_fuzz(
+        &self,
+        value: &str,
+        radius_m: f64,
+        axis: GeoAxis,
+        on_error: &OnError,
+        seed: u64,
+        lat_hint: Option<f64>,
+    ) -> String {
+        let is_quoted = value.starts_with('\'') && value.ends_with('\'');
+        let clean_val = unquote(value).trim();
+        let coord: f64 = match clean_val.parse() {
+            Ok(c) => c,
+            Err(_) => return self.unparseable(value, on_error),
+        };
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let distance = radius_m * rng.gen::<f64>().sqrt();
+        let bearing = rng.gen::<f64>() * std::f64::consts::TAU;
+
+        let shifted = match axis {
+            GeoAxis::Lat => {
+                (coord + distance * bearing.cos() / METERS_PER_DEGREE).clamp(-90.0, 90.0)
+            }
+            GeoAxis::Lon => {
+                let lat = lat_hint.unwrap_or(0.0).to_radians();
+                let meters_per_degree = METERS_PER_DEGREE * lat.cos().max(1e-6);
+                let lon = coord + distance * bearing.sin() / meters_per_degree;
+                (lon + 180.0).rem_euclid(360.0) - 180.0
+            }
         };
 
+        let decimals = clean_val.split_once('.').map(|(_, f)| f.len()).unwrap_or(0);
+        let formatted = format!("{:.*}", decimals, shifted);
         if is_quoted {
-            format!("'{}'", new_val)
+            format!("'{}'", formatted)
         } else {
-            new_val
+            formatted
         }
     }
 
+    /// What replaces `value` when it couldn't be parsed. `NULL` isn't an
+    /// error and is kept.
+    fn unparseable(&self, value: &str, on_error: &OnError) -> String {
+        if value.eq_ignore_ascii_case("NULL") {
+            return value.to_string();
+        }
+        self.unparseable.fetch_add(1, Ordering::Relaxed);
+        match on_error {
+            OnError::Keep => value.to_string(),
+            OnError::Null => "NULL".to_string(),
+            OnError::Fixed(fixed) if value.starts_with('\'') && value.ends_with('\'') => format!("'{}'", fixed),
+            OnError::Fixed(fixed) => fixed.clone(),
+        }
+    }
+
+    fn run_command(&self, program: &str, args: &[String], value: &str) -> Result<String> {
+        let mut commands = self.commands.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (program.to_string(), args.to_vec());
+        if !commands.contains_key(&key) {
+            let process = CommandProcess::spawn(program, args)?;
+            commands.insert(key.clone(), process);
+        }
+        commands.get_mut(&key).expect("just inserted").call(value)
+    }
+
+    /// Splits a comma-separated value list, respecting quotes, escapes and
+    /// the parentheses of function calls (`TO_DATE('...', '...')`).
+    /// Comments between values (`/* ... */`, `-- ...` up to the line end)
+    /// are dropped.
     pub fn parse_values(values_str: &str) -> Vec<String> {
+        Self::split_values(values_str).into_iter().map(|(value, _)| value).collect()
+    }
+
+    /// Byte ranges of the values [`parse_values`](Self::parse_values)
+    /// returns, in `values_str`. A range spans from the value's first to its
+    /// last character, so it also covers any comment in between.
+    pub fn value_spans(values_str: &str) -> Vec<Range<usize>> {
+        Self::split_values(values_str).into_iter().map(|(_, span)| span).collect()
+    }
+
+    /// Byte ranges of the tuples of a multi-row `VALUES` list, given the
+    /// text between its first `(` and last `)`: for `1, 'a'), (2, 'b'` the
+    /// ranges of `1, 'a'` and `2, 'b'`. A single-row list is one range over
+    /// all of `values_str`. Quotes, escapes, comments and the parentheses of
+    /// function calls are respected. `None` if anything but a comma (and
+    /// whitespace or comments) separates two tuples, or parentheses don't
+    /// balance.
+    pub fn tuple_spans(values_str: &str) -> Option<Vec<Range<usize>>> {
+        let mut tuples = Vec::new();
+        // `Some(start)` inside a tuple, `None` between two.
+        let mut start = Some(0);
+        let mut depth = 0usize;
+        let mut in_quotes = false;
+        let mut after_comma = false;
+        let mut chars = values_str.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if in_quotes {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '\'' => in_quotes = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '/' if chars.peek().map(|&(_, c)| c) == Some('*') => {
+                    chars.next();
+                    let mut prev = ' ';
+                    for (_, c) in chars.by_ref() {
+                        if prev == '*' && c == '/' {
+                            break;
+                        }
+                        prev = c;
+                    }
+                }
+                '-' if chars.peek().map(|&(_, c)| c) == Some('-') => {
+                    for (_, c) in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                _ if start.is_none() => match c {
+                    c if c.is_whitespace() => {}
+                    ',' if !after_comma => after_comma = true,
+                    '(' if after_comma => {
+                        start = Some(i + 1);
+                        after_comma = false;
+                    }
+                    _ => return None,
+                },
+                '\'' => in_quotes = true,
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                ')' => tuples.push(start.take()?..i),
+                _ => {}
+            }
+        }
+        match start {
+            Some(start) if depth == 0 && !in_quotes => {
+                tuples.push(start..values_str.len());
+                Some(tuples)
+            }
+            _ => None,
+        }
+    }
+
+    fn split_values(values_str: &str) -> Vec<(String, Range<usize>)> {
         let mut result = Vec::new();
         let mut current = String::new();
+        // Where the current value's non-whitespace characters start and end.
+        let mut span: Option<Range<usize>> = None;
         let mut in_quotes = false;
         let mut escape = false;
+        let mut depth = 0usize;
+        let mut chars = values_str.char_indices().peekable();
+        let mut finish = |current: &mut String, span: &mut Option<Range<usize>>| {
+            result.push((current.trim().to_string(), span.take().unwrap_or(0..0)));
+            current.clear();
+        };
+        let push = |current: &mut String, span: &mut Option<Range<usize>>, i: usize, c: char| {
+            current.push(c);
+            if !c.is_whitespace() {
+                let end = i + c.len_utf8();
+                let start = span.as_ref().map_or(i, |s| s.start);
+                *span = Some(start..end);
+            }
+        };
 
-        for c in values_str.chars() {
+        while let Some((i, c)) = chars.next() {
             if escape {
-                current.push(c);
+                push(&mut current, &mut span, i, c);
                 escape = false;
                 continue;
             }
@@ -87,24 +905,541 @@ impl Transformer {
             match c {
                 '\'' => {
                     in_quotes = !in_quotes;
-                    current.push(c);
+                    push(&mut current, &mut span, i, c);
                 }
                 '\\' => {
                     escape = true;
-                    current.push(c);
+                    push(&mut current, &mut span, i, c);
+                }
+                // Commas inside a function call's parentheses don't end the value.
+                '(' if !in_quotes => {
+                    depth += 1;
+                    push(&mut current, &mut span, i, c);
+                }
+                ')' if !in_quotes => {
+                    depth = depth.saturating_sub(1);
+                    push(&mut current, &mut span, i, c);
                 }
-                ',' if !in_quotes => {
-                    result.push(current.trim().to_string());
-                    current.clear();
+                ',' if !in_quotes && depth == 0 => finish(&mut current, &mut span),
+                '/' if !in_quotes && chars.peek().map(|&(_, c)| c) == Some('*') => {
+                    chars.next();
+                    let mut prev = ' ';
+                    for (_, c) in chars.by_ref() {
+                        if prev == '*' && c == '/' {
+                            break;
+                        }
+                        prev = c;
+                    }
                 }
-                _ => {
-                    current.push(c);
+                '-' if !in_quotes && chars.peek().map(|&(_, c)| c) == Some('-') => {
+                    for (_, c) in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
                 }
+                _ => push(&mut current, &mut span, i, c),
             }
         }
         if !current.is_empty() {
-            result.push(current.trim().to_string());
+            finish(&mut current, &mut span);
         }
         result
     }
+
+    /// Parses a `col = value, col = value` assignment list (as used by
+    /// `INSERT ... SET` and `UPDATE`). Returns an empty list if any item
+    /// isn't an assignment.
+    pub fn parse_assignments(assignments_str: &str) -> Vec<(String, String)> {
+        let mut result = Vec::new();
+        for item in Self::parse_values(assignments_str) {
+            match item.split_once('=') {
+                Some((col, val)) => result.push((col.trim().to_string(), val.trim().to_string())),
+                None => return Vec::new(),
+            }
+        }
+        result
+    }
+}
+
+/// The range of the string literal in a date conversion call such as
+/// `TO_DATE('2024-01-15', 'YYYY-MM-DD')`, `TO_TIMESTAMP` or
+/// `TO_TIMESTAMP_TZ`, whose first argument it is.
+fn date_conversion(value: &str) -> Option<Range<usize>> {
+    static CALL: OnceLock<Regex> = OnceLock::new();
+    let call = CALL.get_or_init(|| {
+        Regex::new(r"(?is)^TO_(?:DATE|TIMESTAMP|TIMESTAMP_TZ)\s*\(\s*('(?:[^'\\]|''|\\.)*')\s*(?:,.*)?\)$")
+            .expect("Invalid regex pattern")
+    });
+    call.captures(value)?.get(1).map(|m| m.range())
+}
+
+fn unquote(value: &str) -> &str {
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+/// Draws candidates from `generate` until one fits in `max_len` characters,
+/// truncating the last candidate if none does.
+fn fit_len(
+    rng: &mut StdRng,
+    max_len: Option<usize>,
+    generate: impl Fn(&mut StdRng) -> String,
+) -> String {
+    let Some(max_len) = max_len else {
+        return generate(rng);
+    };
+    let mut candidate = String::new();
+    for _ in 0..MAX_LEN_ATTEMPTS {
+        candidate = generate(rng);
+        if candidate.chars().count() <= max_len {
+            return candidate;
+        }
+    }
+    candidate.chars().take(max_len).collect::<String>().trim_end().to_string()
+}
+
+/// Picks another member of `members` for a quoted enum value, seeded by
+/// `seed` and the value. Values that aren't members (including `NULL`) and
+/// single-member enums are returned unchanged.
+fn enum_member(value: &str, members: &[String], seed: u64) -> String {
+    let current = unescape_literal(unquote(value));
+    let Some(index) = members.iter().position(|m| *m == current) else {
+        return value.to_string();
+    };
+    if members.len() < 2 || !value.starts_with('\'') {
+        return value.to_string();
+    }
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    current.hash(&mut hasher);
+    let mut rng = StdRng::seed_from_u64(hasher.finish());
+    let mut pick = rng.gen_range(0..members.len() - 1);
+    if pick >= index {
+        pick += 1;
+    }
+    format!("'{}'", members[pick].replace('\'', "''"))
+}
+
+/// The gender in the row's `column`, or in a column named `gender` or `sex`.
+fn row_gender(columns: &[String], values: &[String], column: Option<&str>) -> Gender {
+    let is_gender_column = |c: &String| match column {
+        Some(name) => c.eq_ignore_ascii_case(name),
+        None => c.eq_ignore_ascii_case("gender") || c.eq_ignore_ascii_case("sex"),
+    };
+    match columns.iter().position(is_gender_column) {
+        Some(i) => Gender::parse(&unescape_literal(unquote(&values[i]))),
+        None => Gender::Neutral,
+    }
+}
+
+/// The original value of the row's `key` column, unless the row lacks it
+/// or it is `NULL`.
+fn row_key<'v>(columns: &[String], values: &'v [String], key: Option<&str>) -> Option<&'v str> {
+    let key = key?;
+    let i = columns.iter().position(|c| c.eq_ignore_ascii_case(key))?;
+    let value = values.get(i)?;
+    (!value.eq_ignore_ascii_case("NULL")).then(|| unquote(value))
+}
+
+/// Picks a first name for `gender`, seeded by `seed` and the original name
+/// so the same person keeps the same pseudonym. `NULL` is kept.
+fn gendered_first_name(value: &str, gender: Gender, options: &ColumnOptions, seed: u64) -> String {
+    if value.eq_ignore_ascii_case("NULL") {
+        return value.to_string();
+    }
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    unquote(value).hash(&mut hasher);
+    let mut rng = StdRng::seed_from_u64(hasher.finish());
+    let name = fit_len(&mut rng, options.max_len, |rng| names::first_name(gender, rng).to_string());
+    if value.starts_with('\'') {
+        format!("'{}'", name)
+    } else {
+        name
+    }
+}
+
+/// The anonymized first and last name of a row, from its name columns.
+/// A `full_name` is split at its first space and only fills in what the
+/// `first_name`/`last_name` columns don't.
+fn row_names(rules: &[ColumnRule], new_values: &[String]) -> (Option<String>, Option<String>) {
+    let mut first = None;
+    let mut last = None;
+    let mut full = None;
+    for (rule, value) in rules.iter().zip(new_values) {
+        if value.is_empty() || value.eq_ignore_ascii_case("NULL") {
+            continue;
+        }
+        let value = unescape_literal(unquote(value));
+        match rule.strategy {
+            ColumnStrategy::FirstName | ColumnStrategy::GenderedFirstName { .. } => {
+                first.get_or_insert(value);
+            }
+            ColumnStrategy::LastName => {
+                last.get_or_insert(value);
+            }
+            ColumnStrategy::FullName => {
+                full.get_or_insert(value);
+            }
+            _ => {}
+        }
+    }
+    if let Some(full) = full {
+        let (full_first, full_last) = match full.split_once(' ') {
+            Some((first, last)) => (first.to_string(), Some(last.to_string())),
+            None => (full, None),
+        };
+        first.get_or_insert(full_first);
+        if let Some(full_last) = full_last {
+            last.get_or_insert(full_last);
+        }
+    }
+    (first, last)
+}
+
+/// An address at `domain` for the person named by `names`, following
+/// `format`. Without any name, the local part is a seeded fake. `NULL` is
+/// kept.
+fn company_email(
+    value: &str,
+    names: (Option<String>, Option<String>),
+    domain: &str,
+    format: &str,
+    seed: u64,
+) -> String {
+    if value.eq_ignore_ascii_case("NULL") {
+        return value.to_string();
+    }
+    // Only letters and digits survive into the local part: `O'Brien` is `obrien`.
+    let clean = |name: &Option<String>| -> String {
+        name.as_deref()
+            .unwrap_or("")
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let (first, last) = (clean(&names.0), clean(&names.1));
+    let local = if first.is_empty() && last.is_empty() {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        unquote(value).hash(&mut hasher);
+        let mut rng = StdRng::seed_from_u64(hasher.finish());
+        let email: String = SafeEmail().fake_with_rng(&mut rng);
+        email.split('@').next().unwrap_or_default().to_string()
+    } else {
+        let initial = |name: &str| name.chars().next().map(String::from).unwrap_or_default();
+        let local = format
+            .replace("{first}", &first)
+            .replace("{last}", &last)
+            .replace("{f}", &initial(&first))
+            .replace("{l}", &initial(&last));
+        // Separators left dangling by a missing name.
+        local.trim_matches(|c: char| !c.is_alphanumeric()).to_string()
+    };
+    let email = format!("{}@{}", local, domain);
+    if value.starts_with('\'') {
+        format!("'{}'", email)
+    } else {
+        email
+    }
+}
+
+/// The fake domain of the company whose anonymized name is `company`.
+/// Seeded by the name alone, so all rows of one company agree.
+fn company_domain(company: &str, seed: u64) -> String {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    company.to_lowercase().hash(&mut hasher);
+    let mut rng = StdRng::seed_from_u64(hasher.finish());
+    let first: String = Word().fake_with_rng(&mut rng);
+    let second: String = Word().fake_with_rng(&mut rng);
+    let suffix: String = DomainSuffix().fake_with_rng(&mut rng);
+    format!("{}-{}.{}", first, second, suffix)
+}
+
+/// Moves an email, URL or hostname to `domain`, keeping the local part, or
+/// the scheme, userinfo, a leading `www.`, port, path and query.
+fn with_domain(value: &str, domain: &str) -> String {
+    let inner = unquote(value);
+    let linked = match inner.split_once("://") {
+        None if inner.contains('@') => {
+            let local = inner.rsplit_once('@').map_or("", |(local, _)| local);
+            format!("{}@{}", local, domain)
+        }
+        split => {
+            let (scheme, rest) = match split {
+                Some((scheme, rest)) => (format!("{}://", scheme), rest),
+                None => (String::new(), inner),
+            };
+            let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+            let (authority, tail) = rest.split_at(authority_end);
+            let (userinfo, host_port) = match authority.rsplit_once('@') {
+                Some((userinfo, host)) => (&authority[..userinfo.len() + 1], host),
+                None => ("", authority),
+            };
+            let (host, port) = split_port(host_port);
+            let www = if host.to_ascii_lowercase().starts_with("www.") { "www." } else { "" };
+            format!("{}{}{}{}{}{}", scheme, userinfo, www, domain, port, tail)
+        }
+    };
+    if value.starts_with('\'') {
+        format!("'{}'", linked)
+    } else {
+        linked
+    }
+}
+
+/// `prefix_` and the first `len` hex digits of SHA-256 over `seed` and
+/// `value`. Without a prefix, just the digits.
+fn prefixed_hash(value: &str, prefix: &str, len: usize, seed: u64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.to_le_bytes());
+    hasher.update(value.as_bytes());
+    let mut digest = checksum::to_hex(&hasher.finalize());
+    digest.truncate(len.min(MAX_HASH_LEN));
+    if prefix.is_empty() {
+        digest
+    } else {
+        format!("{}_{}", prefix, digest)
+    }
+}
+
+/// Doubles each `'` that would end a string literal early in its contents.
+/// Quotes already escaped (`''`, `\'`) are left alone, so this serves fakes
+/// and text carried over from the escaped original alike.
+fn escape_quotes(s: &str) -> Cow<'_, str> {
+    if !s.contains('\'') {
+        return Cow::Borrowed(s);
+    }
+    let mut out = String::with_capacity(s.len() + 2);
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        out.push(c);
+        match c {
+            '\\' => out.extend(chars.next()),
+            '\'' => {
+                out.push('\'');
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Resolves `''` and `\'` escapes in the contents of a string literal.
+pub(crate) fn unescape_literal(s: &str) -> String {
+    s.replace("''", "'").replace("\\'", "'")
+}
+
+/// Replaces a URL's host via [`hostname`], so a host gets the same
+/// pseudonym in every URL and `hostname` column. See `ColumnStrategy::Url`.
+fn url(value: &str, keep_path: bool, seed: u64, rng: &mut StdRng) -> String {
+    let (scheme, rest) = match value.split_once("://") {
+        Some((scheme, rest)) => (Some(scheme), rest),
+        None => (None, value),
+    };
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (authority, tail) = rest.split_at(authority_end);
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+
+    if keep_path {
+        let scheme = scheme.map(|s| format!("{}://", s)).unwrap_or_default();
+        format!("{}{}{}", scheme, hostname(host_port, seed), tail)
+    } else {
+        let host = split_port(host_port).0;
+        let path: String = Word().fake_with_rng(rng);
+        format!("https://{}/{}", hostname(host, seed), path)
+    }
+}
+
+/// A fake hostname for `value`, keeping a leading `www.` and a trailing
+/// `:port`. Seeded by the host alone (case-insensitively), not the value's
+/// position, so equal hosts always get equal pseudonyms.
+fn hostname(value: &str, seed: u64) -> String {
+    let (host, port) = split_port(value);
+    if host.is_empty() {
+        return value.to_string();
+    }
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    host.to_ascii_lowercase().hash(&mut hasher);
+    let mut rng = StdRng::seed_from_u64(hasher.finish());
+    let first: String = Word().fake_with_rng(&mut rng);
+    let second: String = Word().fake_with_rng(&mut rng);
+    let suffix: String = DomainSuffix().fake_with_rng(&mut rng);
+    let www = if host.to_ascii_lowercase().starts_with("www.") { "www." } else { "" };
+    format!("{}{}-{}.{}{}", www, first, second, suffix, port)
+}
+
+/// Splits a numeric `:port` off a host (`db.local:5432` -> `db.local`,
+/// `:5432`). Bracketed IPv6 hosts keep their colons.
+fn split_port(host: &str) -> (&str, &str) {
+    match host.rfind(':') {
+        Some(i)
+            if !host[i + 1..].is_empty()
+                && host[i + 1..].bytes().all(|b| b.is_ascii_digit())
+                && (host.starts_with('[') || !host[..i].contains(':')) =>
+        {
+            host.split_at(i)
+        }
+        _ => (host, ""),
+    }
+}
+
+/// The seed of a retry: `seed` itself for the first attempt, so values that
+/// need no retry are unaffected.
+fn attempt_seed(seed: u64, attempt: u32) -> u64 {
+    if attempt == 0 {
+        return seed;
+    }
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    attempt.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Honorifics `name_prefix` picks from.
+const NAME_PREFIXES: &[&str] = &["Mr", "Mrs", "Ms", "Miss", "Mx", "Dr", "Prof"];
+
+/// A seeded honorific, abbreviated with a `.` if `original` was (`Mr.`).
+/// "Miss" is a word, not an abbreviation, so it never gets one.
+fn name_prefix(original: &str, rng: &mut StdRng) -> String {
+    let prefix = NAME_PREFIXES[rng.gen_range(0..NAME_PREFIXES.len())];
+    if original.trim_end().ends_with('.') && prefix != "Miss" {
+        format!("{}.", prefix)
+    } else {
+        prefix.to_string()
+    }
+}
+
+/// Cache key for strategies whose cost is dominated by the faker, `None`
+/// for the rest (cheap, stateful or external ones).
+fn cache_key(
+    value: &str,
+    strategy: &ColumnStrategy,
+    options: &ColumnOptions,
+    seed: u64,
+) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    std::mem::discriminant(strategy).hash(&mut hasher);
+    match strategy {
+        ColumnStrategy::FirstName
+        | ColumnStrategy::LastName
+        | ColumnStrategy::FullName
+        | ColumnStrategy::JobTitle
+        | ColumnStrategy::Email
+        | ColumnStrategy::Phone
+        | ColumnStrategy::Vin
+        | ColumnStrategy::Ssn
+        | ColumnStrategy::Hostname => {}
+        ColumnStrategy::Url { keep_path } => keep_path.hash(&mut hasher),
+        ColumnStrategy::ZipCode { keep_prefix } => keep_prefix.hash(&mut hasher),
+        _ => return None,
+    }
+    seed.hash(&mut hasher);
+    options.max_len.hash(&mut hasher);
+    value.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Keeps `reveal_start` leading and `reveal_end` trailing characters around
+/// three `fill`s, or `None` when that would reveal the whole value.
+fn mask(value: &str, fill: char, reveal_start: usize, reveal_end: usize) -> Option<String> {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= reveal_start.saturating_add(reveal_end) {
+        return None;
+    }
+    let start: String = chars[..reveal_start].iter().collect();
+    let end: String = chars[chars.len() - reveal_end..].iter().collect();
+    Some(format!("{}{}{}", start, fill.to_string().repeat(3), end))
+}
+
+/// Maps every digit through one permutation of 0-9 drawn from `seed`
+/// alone (not the value), leaving all other characters in place. The same
+/// seed always yields the same mapping, so equal inputs stay equal and the
+/// format is untouched.
+fn digit_scramble(value: &str, seed: u64) -> String {
+    let mut digits: [u8; 10] = *b"0123456789";
+    let mut rng = StdRng::seed_from_u64(seed);
+    for i in (1..digits.len()).rev() {
+        digits.swap(i, rng.gen_range(0..=i));
+    }
+    value
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(d) if c.is_ascii_digit() => char::from(digits[d as usize]),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Keeps the first `keep_prefix` digits of a 5-digit ZIP or ZIP+4 code and
+/// draws the rest; anything else goes through `scramble_alnum`.
+fn zip_code(value: &str, keep_prefix: usize, rng: &mut StdRng) -> String {
+    let bytes = value.as_bytes();
+    let is_zip = matches!(bytes.len(), 5 | 10)
+        && bytes[..5].iter().all(u8::is_ascii_digit)
+        && (bytes.len() == 5 || (bytes[5] == b'-' && bytes[6..].iter().all(u8::is_ascii_digit)));
+    if !is_zip {
+        return scramble_alnum(value, rng);
+    }
+    let keep = keep_prefix.min(5);
+    value
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if i < keep || c == '-' {
+                c
+            } else {
+                char::from(b'0' + rng.gen_range(0..10u8))
+            }
+        })
+        .collect()
+}
+
+/// `fixed` in place of a `NULL`, which says nothing about the column's
+/// type: bare if it is a number, otherwise a quoted string.
+fn fixed_literal(fixed: &str) -> String {
+    let digits = fixed.strip_prefix('-').unwrap_or(fixed);
+    if digits.parse::<f64>().is_ok() && digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        fixed.to_string()
+    } else {
+        format!("'{}'", fixed.replace('\'', "''"))
+    }
+}
+
+/// Replaces digits with random digits and letters with random letters of
+/// the same case, leaving spaces and punctuation where they are.
+fn scramble_alnum(value: &str, rng: &mut StdRng) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                char::from(b'0' + rng.gen_range(0..10u8))
+            } else if c.is_ascii_uppercase() {
+                char::from(b'A' + rng.gen_range(0..26u8))
+            } else if c.is_ascii_lowercase() {
+                char::from(b'a' + rng.gen_range(0..26u8))
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn geo_axis_for(column: &str) -> GeoAxis {
+    let lower = column.to_lowercase();
+    if lower.contains("lon") || lower.contains("lng") {
+        GeoAxis::Lon
+    } else {
+        GeoAxis::Lat
+    }
 }
@@ -0,0 +1,70 @@
+use rand::Rng;
+
+/// Characters allowed in a VIN: digits and capital letters except I, O and Q.
+const VIN_CHARS: &[u8] = b"0123456789ABCDEFGHJKLMNPRSTUVWXYZ";
+
+/// Per-position weights for the ISO 3779 / FMVSS 115 check digit.
+const WEIGHTS: [u32; 17] = [8, 7, 6, 5, 4, 3, 2, 10, 0, 9, 8, 7, 6, 5, 4, 3, 2];
+
+/// Index of the check digit (position 9).
+const CHECK_POS: usize = 8;
+
+fn transliterate(c: char) -> Option<u32> {
+    Some(match c {
+        '0'..='9' => c.to_digit(10)?,
+        'A' | 'J' => 1,
+        'B' | 'K' | 'S' => 2,
+        'C' | 'L' | 'T' => 3,
+        'D' | 'M' | 'U' => 4,
+        'E' | 'N' | 'V' => 5,
+        'F' | 'W' => 6,
+        'G' | 'P' | 'X' => 7,
+        'H' | 'Y' => 8,
+        'R' | 'Z' => 9,
+        _ => return None,
+    })
+}
+
+/// Computes the check digit for a 17-character VIN (the character at
+/// position 9 is ignored). Returns `None` for invalid lengths or characters.
+pub fn check_digit(vin: &str) -> Option<char> {
+    let chars: Vec<char> = vin.chars().collect();
+    if chars.len() != 17 {
+        return None;
+    }
+    let mut sum = 0;
+    for (i, c) in chars.iter().enumerate() {
+        if i == CHECK_POS {
+            continue;
+        }
+        sum += transliterate(*c)? * WEIGHTS[i];
+    }
+    Some(match sum % 11 {
+        10 => 'X',
+        d => char::from_digit(d, 10).expect("remainder is a digit"),
+    })
+}
+
+/// Whether `vin` is 17 valid characters with a correct check digit.
+pub fn is_valid(vin: &str) -> bool {
+    check_digit(vin).is_some_and(|c| vin.chars().nth(CHECK_POS) == Some(c))
+}
+
+/// Generates a random VIN with a valid check digit.
+pub fn generate<R: Rng>(rng: &mut R) -> String {
+    let mut chars: Vec<char> = (0..17)
+        .map(|_| VIN_CHARS[rng.gen_range(0..VIN_CHARS.len())] as char)
+        .collect();
+    let vin: String = chars.iter().collect();
+    chars[CHECK_POS] = check_digit(&vin).expect("generated from valid characters");
+    chars.into_iter().collect()
+}
+
+/// Replaces each character with a random alphanumeric from the VIN set,
+/// keeping the length. Used for values that aren't 17 characters long.
+pub fn scramble<R: Rng>(value: &str, rng: &mut R) -> String {
+    value
+        .chars()
+        .map(|_| VIN_CHARS[rng.gen_range(0..VIN_CHARS.len())] as char)
+        .collect()
+}
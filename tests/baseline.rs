@@ -0,0 +1,112 @@
+mod common;
+
+use common::{config, temp_path};
+use ghostdb::failure::Failure;
+use ghostdb::processor::{run_processing, RunOptions};
+use std::fs;
+
+const USERS: &str = "tables:\n  users:\n    key: id\n    columns:\n      id: keep\n      email: email\n";
+
+fn insert(id: u32, email: &str) -> String {
+    format!("INSERT INTO users (id, email) VALUES ({}, '{}');\n", id, email)
+}
+
+#[test]
+fn unchanged_rows_keep_the_values_of_the_baseline() {
+    let config = config(USERS);
+    let input = temp_path("baseline_in.sql");
+    let output = temp_path("baseline_out.sql");
+    let mapping = temp_path("baseline_1.jsonl");
+    let next_mapping = temp_path("baseline_2.jsonl");
+
+    fs::write(&input, insert(1, "ann@work.com") + &insert(2, "bob@work.com")).unwrap();
+    let options = RunOptions {
+        mapping_out: Some(mapping.clone()),
+        ..Default::default()
+    };
+    run_processing(&input, &output, &config, 42, &options).unwrap();
+    let first = fs::read_to_string(&output).unwrap();
+    let contents = fs::read_to_string(&mapping).unwrap();
+    assert!(!contents.contains("ann@work.com"), "mapping leaks originals: {}", contents);
+
+    // Mark the stored pseudonyms, to see which rows are taken from the
+    // mapping rather than anonymized again.
+    let fakes: Vec<String> = first
+        .lines()
+        .map(|l| l.rsplit('\'').nth(1).unwrap().to_string())
+        .collect();
+    let marked = contents
+        .replace(&fakes[0], "kept-1@example.com")
+        .replace(&fakes[1], "kept-2@example.com");
+    fs::write(&mapping, marked).unwrap();
+
+    // Row 2 changed, row 3 is new.
+    fs::write(
+        &input,
+        insert(1, "ann@work.com") + &insert(2, "bob@home.com") + &insert(3, "cy@work.com"),
+    )
+    .unwrap();
+    let options = RunOptions {
+        baseline: Some(mapping.clone()),
+        mapping_out: Some(next_mapping.clone()),
+        overwrite: true,
+        ..Default::default()
+    };
+    run_processing(&input, &output, &config, 42, &options).unwrap();
+    let second = fs::read_to_string(&output).unwrap();
+    let next = fs::read_to_string(&next_mapping).unwrap();
+    for path in [&input, &output, &mapping, &next_mapping] {
+        let _ = fs::remove_file(path);
+    }
+
+    let lines: Vec<&str> = second.lines().collect();
+    assert!(lines[0].contains("'kept-1@example.com'"), "{}", second);
+    assert!(!lines[1].contains("kept-2"), "{}", second);
+    assert!(!second.contains("@work.com") && !second.contains("@home.com"));
+    assert_eq!(next.lines().count(), 4, "header and three rows: {}", next);
+    assert!(next.contains("kept-1@example.com"));
+}
+
+#[test]
+fn baseline_of_another_seed_is_ignored() {
+    let config = config(USERS);
+    let input = temp_path("baseline_seed_in.sql");
+    let output = temp_path("baseline_seed_out.sql");
+    let mapping = temp_path("baseline_seed.jsonl");
+    fs::write(&input, insert(1, "ann@work.com")).unwrap();
+
+    let options = RunOptions {
+        mapping_out: Some(mapping.clone()),
+        ..Default::default()
+    };
+    run_processing(&input, &output, &config, 42, &options).unwrap();
+    let options = RunOptions {
+        baseline: Some(mapping.clone()),
+        overwrite: true,
+        ..Default::default()
+    };
+    run_processing(&input, &output, &config, 7, &options).unwrap();
+    let reseeded = fs::read_to_string(&output).unwrap();
+    let options = RunOptions {
+        overwrite: true,
+        ..Default::default()
+    };
+    run_processing(&input, &output, &config, 7, &options).unwrap();
+    let fresh = fs::read_to_string(&output).unwrap();
+    for path in [&input, &output, &mapping] {
+        let _ = fs::remove_file(path);
+    }
+    assert_eq!(reseeded, fresh);
+}
+
+#[test]
+fn baseline_is_refused_for_tokenized_columns() {
+    let config = config("tables:\n  users:\n    key: id\n    columns:\n      email: !tokenize\n        prefix: user\n");
+    let options = RunOptions {
+        baseline: Some(temp_path("unused.jsonl")),
+        ..Default::default()
+    };
+    let err = run_processing(&temp_path("unused.sql"), &temp_path("unused_out.sql"), &config, 42, &options)
+        .unwrap_err();
+    assert_eq!(Failure::of(&err), Some(Failure::Config));
+}
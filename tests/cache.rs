@@ -0,0 +1,52 @@
+mod common;
+
+use common::config;
+use ghostdb::cache::LruCache;
+use ghostdb::processor::{process_stream, RunOptions};
+use ghostdb::transformer::Transformer;
+
+fn run(sql: &str, transformer: Transformer) -> Vec<u8> {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      email: email\n      country: full_name\n      zip: !zip_code {keep_prefix: 2}\n      note: mask\n    options:\n      country:\n        max_len: 12\n",
+    );
+    let mut out = Vec::new();
+    process_stream(&transformer, sql.as_bytes(), &mut out, &config, &RunOptions::default()).unwrap();
+    out
+}
+
+#[test]
+fn cached_output_is_byte_identical() {
+    let sql: String = (0..500)
+        .map(|i| {
+            format!(
+                "INSERT INTO users (email, country, zip, note) VALUES ('u{}@x.com', 'C{}', '{:05}', 'n{}');\n",
+                i % 37,
+                i % 5,
+                i % 11 * 1000,
+                i
+            )
+        })
+        .collect();
+    let plain = run(&sql, Transformer::new(7));
+    // A tiny capacity keeps evicting, exercising misses after eviction too.
+    for capacity in [3, 10_000] {
+        assert!(run(&sql, Transformer::new(7).with_cache(capacity)) == plain);
+    }
+}
+
+#[test]
+fn lru_evicts_least_recently_used() {
+    let mut cache = LruCache::new(2);
+    cache.insert("a", 1);
+    cache.insert("b", 2);
+    assert_eq!(cache.get(&"a"), Some(&1));
+    cache.insert("c", 3);
+    assert_eq!(cache.get(&"b"), None);
+    assert_eq!(cache.get(&"a"), Some(&1));
+    assert_eq!(cache.get(&"c"), Some(&3));
+    cache.insert("a", 4);
+    cache.insert("d", 5);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&"c"), None);
+    assert_eq!(cache.get(&"a"), Some(&4));
+}
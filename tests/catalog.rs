@@ -0,0 +1,38 @@
+use ghostdb::catalog::strategies;
+use ghostdb::config::ColumnStrategy;
+use ghostdb::transformer::Transformer;
+use std::collections::HashSet;
+
+#[test]
+fn every_strategy_is_listed_once_with_a_working_example() {
+    let transformer = Transformer::new(42);
+    let catalog = strategies();
+    let names: Vec<String> = catalog.iter().map(|info| info.name()).collect();
+    assert_eq!(names.iter().collect::<HashSet<_>>().len(), names.len(), "{:?}", names);
+    for name in ["keep", "mask", "zip_code", "url", "array_of", "command"] {
+        assert!(names.iter().any(|n| n == name), "{} missing from {:?}", name, names);
+    }
+    for info in &catalog {
+        info.example_output(&transformer).unwrap();
+        // Unit variants can be written as their plain name in configs.
+        if let Ok(parsed) = serde_yaml::from_str::<ghostdb::config::ColumnStrategy>(&info.name()) {
+            assert_eq!(std::mem::discriminant(&parsed), std::mem::discriminant(&info.strategy));
+        }
+    }
+}
+
+#[test]
+fn changing_the_seed_changes_every_seeded_strategy() {
+    for info in strategies() {
+        // `enum` needs the schema; `random_null` at probability 1 is always NULL.
+        if !info.strategy.is_seeded()
+            || matches!(info.strategy, ColumnStrategy::Enum | ColumnStrategy::RandomNull { .. })
+        {
+            continue;
+        }
+        let outputs: HashSet<String> = (0..8)
+            .map(|seed| Transformer::new(seed).transform(info.example, &info.strategy).unwrap())
+            .collect();
+        assert!(outputs.len() > 1, "{} ignores the seed: {:?}", info.name(), outputs);
+    }
+}
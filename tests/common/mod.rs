@@ -0,0 +1,29 @@
+#![allow(dead_code)]
+
+use ghostdb::config::AppConfig;
+use ghostdb::processor::{process_stream, RunOptions};
+use ghostdb::report::RunReport;
+use ghostdb::transformer::Transformer;
+use std::path::PathBuf;
+
+/// A unique path in the system temp dir for this test process.
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ghostdb_test_{}_{}", std::process::id(), name))
+}
+
+pub fn config(yaml: &str) -> AppConfig {
+    serde_yaml::from_str(yaml).expect("invalid test config")
+}
+
+/// Runs `sql` through the processor in memory with seed 42.
+pub fn process_sql(sql: &str, config: &AppConfig, options: &RunOptions) -> (String, RunReport) {
+    let transformer = Transformer::new(42);
+    let mut output = Vec::new();
+    let report = process_stream(&transformer, sql.as_bytes(), &mut output, config, options)
+        .expect("processing failed");
+    (String::from_utf8(output).expect("output is not UTF-8"), report)
+}
+
+pub fn run_sql(sql: &str, config: &AppConfig) -> String {
+    process_sql(sql, config, &RunOptions::default()).0
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{config, process_sql, run_sql, temp_path};
+use ghostdb::config::ColumnStrategy;
+use ghostdb::processor::{process_stream_pipelined, run_processing, RunOptions};
+use ghostdb::scanner::scan_file_with_samples;
+use ghostdb::transformer::Transformer;
+
+const DUMP: &str = "COPY public.users (id, email, notes) FROM stdin;\n\
+1\tann@work.com\tline one\\nline two\\ttabbed\n\
+2\t\\N\tINSERT INTO users (id) VALUES (9);\n\
+3\tbob@work.com\t\\N\n\
+\\.\n\
+INSERT INTO public.users (id, email, notes) VALUES (4, 'cy@work.com', 'x');\n";
+
+#[test]
+fn copy_rows_are_anonymized_until_the_terminator() {
+    let config = config("tables:\n  public.users:\n    rename_columns:\n      email: contact\n    columns:\n      id: keep\n      email: email\n      notes: keep\n");
+    let (out, report) = process_sql(DUMP, &config, &RunOptions::default());
+    let lines: Vec<&str> = out.lines().collect();
+
+    assert_eq!(lines[0], "COPY public.users (id, contact, notes) FROM stdin;");
+    let row: Vec<&str> = lines[1].split('\t').collect();
+    assert_eq!(row[0], "1");
+    assert!(row[1].contains('@') && row[1] != "ann@work.com", "{}", lines[1]);
+    // Kept fields are copied as written, escapes included.
+    assert_eq!(row[2], "line one\\nline two\\ttabbed");
+    // `\N` stays NULL, and data that looks like SQL is still data.
+    assert_eq!(lines[2], "2\t\\N\tINSERT INTO users (id) VALUES (9);");
+    assert!(lines[3].ends_with("\t\\N") && !lines[3].contains("bob@work.com"), "{}", lines[3]);
+    assert_eq!(lines[4], "\\.");
+    // INSERTs after the block are handled as before.
+    assert!(lines[5].starts_with("INSERT INTO public.users (id, contact, notes) VALUES (4, '") && !lines[5].contains("cy@work.com"));
+    assert_eq!(report.tables["public.users"].statements, 4);
+
+    // The same emails get the same pseudonyms as in INSERTs.
+    let insert = run_sql("INSERT INTO public.users (id, email, notes) VALUES (1, 'ann@work.com', 'x');\n", &config);
+    assert!(insert.contains(&format!("'{}'", row[1])), "{} vs {}", insert, row[1]);
+
+    // The pipeline keeps each row with its own block.
+    let transformer = Transformer::new(42);
+    let mut piped = Vec::new();
+    let twice = format!("{}{}", DUMP, DUMP.replace("public.users (id, email, notes)", "public.users (id, notes, email)"));
+    process_stream_pipelined(&transformer, twice.as_bytes(), &mut piped, &config, &RunOptions::default(), 3).unwrap();
+    assert_eq!(String::from_utf8(piped).unwrap(), run_sql(&twice, &config));
+}
+
+#[test]
+fn copy_rows_that_do_not_fit_the_header_are_skipped() {
+    let config = config("tables:\n  users:\n    columns:\n      email: email\n");
+    let sql = "COPY users (id, email) FROM stdin;\n1\ta@b.com\textra\n\\.\nCOPY users FROM stdin;\n2\tc@d.com\n\\.\nCOPY users (email) FROM '/tmp/users.tsv';\n";
+    let (out, report) = process_sql(sql, &config, &RunOptions::default());
+    // Without a column list or CREATE TABLE, the block is left alone.
+    assert_eq!(out, sql);
+    assert_eq!(report.skipped_statements, 2);
+
+    let with_schema = format!("CREATE TABLE users (id int, email text);\n{}", sql);
+    let out = run_sql(&with_schema, &config);
+    assert!(!out.contains("c@d.com") && out.contains("1\ta@b.com\textra"), "{}", out);
+}
+
+#[test]
+fn diff_lists_only_the_rewritten_copy_rows() {
+    let config = config("tables:\n  public.users:\n    columns:\n      email: email\n");
+    let input = temp_path("copy_diff_in.sql");
+    let output = temp_path("copy_diff_out.sql");
+    let diff = temp_path("copy_diff.patch");
+    std::fs::write(&input, DUMP).unwrap();
+    let options = RunOptions {
+        diff: Some(diff.clone()),
+        ..Default::default()
+    };
+    run_processing(&input, &output, &config, 42, &options).unwrap();
+    let patch = std::fs::read_to_string(&diff).unwrap();
+    let anonymized = std::fs::read_to_string(&output).unwrap();
+    for path in [&input, &output, &diff] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    let lines: Vec<&str> = anonymized.lines().collect();
+    // The header, the row without an email and the terminator are unchanged.
+    let expected = format!(
+        "--- {}\n+++ {}\n@@ -2 +2 @@\n-{}\n+{}\n@@ -4 +4 @@\n-{}\n+{}\n@@ -6 +6 @@\n-{}\n+{}\n",
+        input.display(),
+        output.display(),
+        DUMP.lines().nth(1).unwrap(),
+        lines[1],
+        DUMP.lines().nth(3).unwrap(),
+        lines[3],
+        DUMP.lines().nth(5).unwrap(),
+        lines[5],
+    );
+    assert_eq!(patch, expected);
+}
+
+#[test]
+fn scan_learns_columns_and_samples_from_copy_blocks() {
+    let path = temp_path("scan_copy.sql");
+    std::fs::write(&path, format!("{}COPY \"orders\" (ref, contact) FROM stdin;\n\\.\n", DUMP)).unwrap();
+    let (config, samples) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let users = &config.tables["public.users"].columns;
+    assert_eq!(users["email"], ColumnStrategy::Email);
+    assert_eq!(users["id"], ColumnStrategy::Keep);
+    assert_eq!(samples["public.users"]["email"], vec!["'ann@work.com'", "'bob@work.com'", "'cy@work.com'"]);
+    let mut orders: Vec<&String> = config.tables["\"orders\""].columns.keys().collect();
+    orders.sort();
+    assert_eq!(orders, ["contact", "ref"]);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::{config, run_sql};
+use ghostdb::config::ColumnStrategy;
+use ghostdb::schema::{fitted_type, parse_create_table};
+
+const FITTED: &str = "\
+fit_create_table: true
+tables:
+  users:
+    columns:
+      id: !prefixed_hash {prefix: user, len: 12}
+      code: !prefixed_hash {prefix: '', len: 8}
+      status: !fixed redacted
+      email: email
+";
+
+#[test]
+fn types_are_widened_to_fit_the_output() {
+    let config = config(FITTED);
+    let sql = "\
+CREATE TABLE `users` (
+  `id` bigint unsigned NOT NULL,
+  code CHAR(4) NOT NULL,
+  status varchar(3) DEFAULT 'x',
+  email varchar(255),
+  PRIMARY KEY (`id`)
+);
+";
+    assert_eq!(
+        run_sql(sql, &config),
+        "\
+CREATE TABLE `users` (
+  `id` VARCHAR(17) NOT NULL,
+  code CHAR(8) NOT NULL,
+  status varchar(8) DEFAULT 'x',
+  email varchar(255),
+  PRIMARY KEY (`id`)
+);
+"
+    );
+}
+
+#[test]
+fn fitted_and_renamed_output_is_self_consistent() {
+    let config = config(&format!(
+        "rename_create_table: true\n{}    rename: t1\n    rename_columns:\n      id: c1\n",
+        FITTED
+    ));
+    let sql = "\
+CREATE TABLE users (
+  id int NOT NULL,
+  code varchar(64),
+  status char,
+  email text
+);
+INSERT INTO users (id, code, status, email) VALUES (42, 'abc', 'active', 'a@b.com');
+";
+    let out = run_sql(sql, &config);
+    let (create, insert) = out.split_once(";\n").unwrap();
+    let schema = parse_create_table(create).unwrap();
+    assert_eq!(schema.name, "t1");
+
+    let header = insert.split_once(" VALUES ").unwrap().0;
+    assert_eq!(header, "INSERT INTO t1 (c1, code, status, email)");
+    let id = schema.column("c1").unwrap();
+    assert_eq!(id.sql_type, "VARCHAR(17)");
+    assert!(insert.contains("VALUES ('user_"), "{}", insert);
+    assert_eq!(schema.column("code").unwrap().sql_type, "varchar(64)");
+    assert_eq!(schema.column("status").unwrap().sql_type, "char(8)");
+    assert_eq!(schema.column("email").unwrap().sql_type, "text");
+}
+
+#[test]
+fn type_preserving_strategies_leave_types_alone() {
+    for strategy in [ColumnStrategy::Email, ColumnStrategy::Keep, ColumnStrategy::DigitScramble] {
+        assert_eq!(fitted_type(&strategy, "int"), None);
+        assert_eq!(fitted_type(&strategy, "varchar(5)"), None);
+    }
+    let hash = ColumnStrategy::PrefixedHash { prefix: "k".to_string(), len: 8 };
+    assert_eq!(fitted_type(&hash, ""), None);
+    assert_eq!(fitted_type(&hash, "varchar(10)"), None);
+    assert_eq!(fitted_type(&hash, "character varying"), None);
+    assert_eq!(fitted_type(&hash, "mediumtext"), None);
+    assert_eq!(fitted_type(&hash, "uuid").as_deref(), Some("VARCHAR(10)"));
+    assert_eq!(fitted_type(&hash, "varchar(9) binary").as_deref(), Some("varchar(10) binary"));
+    assert_eq!(fitted_type(&hash, "char binary").as_deref(), Some("char(10) binary"));
+}
+
+#[test]
+fn create_table_is_untouched_without_the_flag() {
+    let config = config(&FITTED.replace("fit_create_table: true\n", ""));
+    let sql = "CREATE TABLE users (\n  id int NOT NULL\n);\n";
+    assert_eq!(run_sql(sql, &config), sql);
+}
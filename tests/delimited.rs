@@ -0,0 +1,74 @@
+mod common;
+
+use common::{config, temp_path};
+use ghostdb::delimited::process_delimited;
+use ghostdb::processor::{run_processing, InputFormat, RunOptions};
+use ghostdb::transformer::Transformer;
+
+const USERS: &str = "tables:\n  users:\n    columns:\n      email: email\n      name: mask\n      note: !fixed \"it's, \\\"fixed\\\"\"\n    rename_columns:\n      email: contact\n";
+
+fn run_csv(input: &str, delimiter: u8) -> (String, ghostdb::report::RunReport) {
+    let transformer = Transformer::new(42);
+    let mut out = Vec::new();
+    let report = process_delimited(
+        &transformer,
+        input.as_bytes(),
+        &mut out,
+        &config(USERS),
+        &RunOptions::default(),
+        "users",
+        delimiter,
+    )
+    .unwrap();
+    (String::from_utf8(out).unwrap(), report)
+}
+
+#[test]
+fn csv_cells_are_anonymized_with_quoting_intact() {
+    let input = "id,email,name,note\n1,alice@work.com,\"O'Brien, Pat\",\"line one\nline two\"\n2,,Bob,x\n";
+    let (out, report) = run_csv(input, b',');
+    let mut reader = csv::Reader::from_reader(out.as_bytes());
+    assert_eq!(reader.headers().unwrap(), vec!["id", "contact", "name", "note"]);
+    let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+    assert_eq!(rows.len(), 2);
+
+    assert_eq!(&rows[0][0], "1");
+    assert!(rows[0][1].contains('@') && &rows[0][1] != "alice@work.com", "{:?}", rows[0]);
+    assert_eq!(&rows[0][2], "O***");
+    assert_eq!(&rows[0][3], "it's, \"fixed\"");
+    // Empty cells stay empty.
+    assert_eq!(&rows[1][1], "");
+    assert_eq!(report.anonymized_statements, 2);
+    assert_eq!(report.unconfigured_columns.iter().collect::<Vec<_>>(), vec!["users.id"]);
+}
+
+#[test]
+fn tsv_and_short_rows() {
+    let (out, report) = run_csv("email\tname\na@b.com\tAnn\nonly-one-field\n", b'\t');
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "contact\tname");
+    assert!(lines[1].ends_with("\tA***") && !lines[1].contains("a@b.com"), "{}", out);
+    assert_eq!(lines[2], "only-one-field");
+    assert_eq!(report.skipped_statements, 1);
+}
+
+#[test]
+fn csv_table_defaults_to_file_stem() {
+    let input = temp_path("users.csv");
+    let output = temp_path("users_out.csv");
+    std::fs::write(&input, "email,name\nalice@work.com,Alice").unwrap();
+    let stem = input.file_stem().unwrap().to_str().unwrap().to_string();
+    let config = config(&USERS.replace("users:", &format!("{}:", stem)));
+    let options = RunOptions {
+        format: InputFormat::Csv,
+        ..Default::default()
+    };
+    let result = run_processing(&input, &output, &config, 42, &options);
+    let out = std::fs::read_to_string(&output).unwrap();
+    for path in [&input, &output] {
+        let _ = std::fs::remove_file(path);
+    }
+    result.unwrap();
+    assert!(out.starts_with("contact,name\n") && out.contains(",A***"), "{}", out);
+    assert!(!out.contains("alice@work.com"), "{}", out);
+}
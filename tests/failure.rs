@@ -0,0 +1,118 @@
+mod common;
+
+use common::{config, temp_path};
+use ghostdb::config::AppConfig;
+use ghostdb::failure::Failure;
+use ghostdb::processor::{run_processing, RunOptions, STDIO};
+use std::path::Path;
+
+#[test]
+fn errors_carry_their_exit_code_category() {
+    let err = AppConfig::load(temp_path("missing.yaml")).unwrap_err();
+    assert_eq!(Failure::exit_code_of(&err), 3);
+
+    let config = config("tables:\n  users:\n    columns:\n      email: email\n");
+    let missing = temp_path("missing.sql");
+    let err = run_processing(&missing, &temp_path("out.sql"), &config, 42, &RunOptions::default())
+        .unwrap_err();
+    assert_eq!(Failure::of(&err), Some(Failure::Io));
+    assert!(err.to_string().starts_with("input/output error"));
+    assert!(format!("{:#}", err).contains("Failed to open input file"));
+
+    let input = temp_path("unconfigured.sql");
+    let output = temp_path("unconfigured_out.sql");
+    std::fs::write(&input, "INSERT INTO users (id) VALUES (1);\n").unwrap();
+    let options = RunOptions {
+        fail_on_unconfigured: true,
+        ..Default::default()
+    };
+    let err = run_processing(&input, &output, &config, 42, &options).unwrap_err();
+    let _ = std::fs::remove_file(&input);
+    assert_eq!(Failure::exit_code_of(&err), 5);
+    assert!(!output.exists());
+}
+
+#[test]
+fn max_memory_names_the_column_that_outgrew_it() {
+    let config = config("tables:\n  users:\n    columns:\n      email: !tokenize\n        prefix: user\n");
+    let input = temp_path("max_memory.sql");
+    let output = temp_path("max_memory_out.sql");
+    let rows: String = (0..100)
+        .map(|i| format!("INSERT INTO users (email) VALUES ('user{}@example.com');\n", i))
+        .collect();
+    std::fs::write(&input, rows).unwrap();
+
+    let options = RunOptions {
+        max_memory: Some(1024),
+        ..Default::default()
+    };
+    let err = run_processing(&input, &output, &config, 42, &options).unwrap_err();
+    assert_eq!(Failure::exit_code_of(&err), 7);
+    assert!(format!("{:#}", err).contains("users.email (tokenize)"));
+
+    let options = RunOptions {
+        max_memory: Some(1 << 20),
+        overwrite: true,
+        ..Default::default()
+    };
+    let result = run_processing(&input, &output, &config, 42, &options);
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+    result.unwrap();
+}
+
+#[test]
+fn existing_output_is_kept_without_overwrite() {
+    let config = config("tables:\n  users:\n    columns:\n      email: email\n");
+    let input = temp_path("existing_in.sql");
+    let output = temp_path("existing_out.sql");
+    std::fs::write(&input, "INSERT INTO users (email) VALUES ('ann@work.com');\n").unwrap();
+    std::fs::write(&output, "precious\n").unwrap();
+
+    let err = run_processing(&input, &output, &config, 42, &RunOptions::default()).unwrap_err();
+    assert_eq!(Failure::of(&err), Some(Failure::Io));
+    assert!(format!("{:#}", err).contains("--force"));
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "precious\n");
+
+    // Not even with `overwrite` when the output is the input.
+    let options = RunOptions {
+        overwrite: true,
+        ..Default::default()
+    };
+    let err = run_processing(&input, &input, &config, 42, &options).unwrap_err();
+    assert!(format!("{:#}", err).contains("is also an input"));
+    assert!(std::fs::read_to_string(&input).unwrap().contains("ann@work.com"));
+
+    let result = run_processing(&input, &output, &config, 42, &options);
+    let out = std::fs::read_to_string(&output).unwrap();
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+    result.unwrap();
+    assert!(out.starts_with("INSERT INTO users"), "{}", out);
+}
+
+#[test]
+fn stdio_runs_refuse_what_needs_files() {
+    let config = config("tables:\n  users:\n    columns:\n      email: email\n");
+    let stdio = Path::new(STDIO);
+    let input = temp_path("stdio_in.sql");
+    for options in [
+        RunOptions {
+            checksum: true,
+            ..Default::default()
+        },
+        RunOptions {
+            resume: true,
+            ..Default::default()
+        },
+    ] {
+        let err = run_processing(&input, stdio, &config, 42, &options).unwrap_err();
+        assert_eq!(Failure::of(&err), Some(Failure::Config), "{:#}", err);
+    }
+    let options = RunOptions {
+        resume: true,
+        ..Default::default()
+    };
+    let err = run_processing(stdio, &temp_path("stdio_out.sql"), &config, 42, &options).unwrap_err();
+    assert!(format!("{:#}", err).contains("not stdin or stdout"), "{:#}", err);
+}
@@ -0,0 +1,6 @@
+-- Originals whose seeded fakes contain an apostrophe (O'Conner, O'Connell,
+-- O'Keefe), inside plain literals and array elements.
+INSERT INTO customers (id, surname, aliases) VALUES (38, 'Schuppe', '{O''Conner,Marks}');
+INSERT INTO customers (id, surname, aliases) VALUES (87, 'O''Connell', '{Wuckert,Johnston}');
+INSERT INTO customers (id, surname, aliases) VALUES (147, 'Bechtelar', '{O''Keefe,Kiehn}');
+INSERT INTO customers (id, surname, aliases) VALUES (1, 'Wintheiser', '{Metz,NULL}');
@@ -0,0 +1,6 @@
+-- Originals whose seeded fakes contain an apostrophe (O'Conner, O'Connell,
+-- O'Keefe), inside plain literals and array elements.
+INSERT INTO customers (id, surname, aliases) VALUES (38, 'Name38', '{Alias38,Other38}');
+INSERT INTO customers (id, surname, aliases) VALUES (87, 'Name87', '{Alias87,Other87}');
+INSERT INTO customers (id, surname, aliases) VALUES (147, 'Name147', '{Alias147,Other147}');
+INSERT INTO customers (id, surname, aliases) VALUES (1, 'O''Brien', '{"D''Arcy",NULL}');
@@ -0,0 +1,2 @@
+INSERT INTO users (id, email) VALUES (1, 'mollie@example.com'), (2, 'willy@example.com'), (3, 'francis@example.com');
+INSERT INTO users (id, email) VALUES (4, 'savion@example.org');
@@ -0,0 +1,2 @@
+INSERT INTO users (id, email) VALUES (1, 'alice@work.com'), (2, 'bob@home.net'), (3, 'carol@corp.org');
+INSERT INTO users (id, email) VALUES (4, 'dave@work.com');
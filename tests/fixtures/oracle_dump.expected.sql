@@ -0,0 +1,4 @@
+REM INSERTING into HR.EMPLOYEES
+SET DEFINE OFF;
+INSERT INTO HR.EMPLOYEES (ID,EMAIL,COMPANY,HIRED) VALUES (1, 'dayne@example.org', 'AT'||chr(38)||'T', to_date('2024-01-25','YYYY-MM-DD'));
+INSERT INTO HR.EMPLOYEES (ID,EMAIL,COMPANY,HIRED) VALUES (2, 'kadin@example.com', 'Acme', to_date('2023-07-30','YYYY-MM-DD'));
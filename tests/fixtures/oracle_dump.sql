@@ -0,0 +1,4 @@
+REM INSERTING into HR.EMPLOYEES
+SET DEFINE OFF;
+Insert into HR.EMPLOYEES (ID,EMAIL,COMPANY,HIRED) values (1,'ann@example.com','AT'||chr(38)||'T',to_date('2024-01-15','YYYY-MM-DD'));
+Insert into HR.EMPLOYEES (ID,EMAIL,COMPANY,HIRED) values (2,'bob@example.com','Acme',to_date('2023-06-30','YYYY-MM-DD'));
@@ -0,0 +1,3 @@
+INSERT INTO notes (id, author, body, secret) VALUES (1, 'Virginia Beer', 'H***', 'REDACTED');
+INSERT INTO notes (id, author, body, secret) VALUES (2, 'Nella Hilpert', 'I***', 'REDACTED');
+INSERT INTO notes (id, author, body, secret) VALUES (3, 'Anderson Purdy', '*', 'REDACTED');
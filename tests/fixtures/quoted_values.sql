@@ -0,0 +1,3 @@
+INSERT INTO notes (id, author, body, secret) VALUES (1, 'O''Brien', 'Hello, world', 'hunter2');
+INSERT INTO notes (id, author, body, secret) VALUES (2, 'D\'Angelo', 'It\'s a note; with punctuation', 'p@ss,word');
+INSERT INTO notes (id, author, body, secret) VALUES (3, 'Zoë', '', 'x');
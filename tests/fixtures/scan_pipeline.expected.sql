@@ -0,0 +1,3 @@
+CREATE TABLE customers (id integer, full_name text, email text, mobile text, notes text, balance numeric);
+INSERT INTO customers (id, full_name, email, mobile, notes, balance) VALUES (1, 'Elijah Johnson', 'mollie@example.com', '1-341-002-1688 x3817', 'V***', 10.50);
+INSERT INTO customers (id, full_name, email, mobile, notes, balance) VALUES (2, 'Cassie Graham', 'willy@example.com', '1-621-506-1461 x330', 'P***', 0.00);
@@ -0,0 +1,3 @@
+CREATE TABLE customers (id integer, full_name text, email text, mobile text, notes text, balance numeric);
+INSERT INTO customers (id, full_name, email, mobile, notes, balance) VALUES (1, 'Alice Smith', 'alice@work.com', '555-0100', 'VIP customer', 10.50);
+INSERT INTO customers (id, full_name, email, mobile, notes, balance) VALUES (2, 'Bob Jones', 'bob@home.net', '555-0101', 'Prefers email', 0.00);
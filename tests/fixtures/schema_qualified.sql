@@ -0,0 +1,3 @@
+INSERT INTO public.users (id, email) VALUES (1, 'alice@work.com');
+INSERT INTO public.orders (id, user_id, shipping_address) VALUES (10, 1, '1 Main St');
+INSERT INTO audit.orders (id, user_id, shipping_address) VALUES (11, 1, '2 Side St');
@@ -0,0 +1,4 @@
+-- Users table
+INSERT INTO users (id, first_name, last_name, email, phone, created_at) VALUES (1, 'Alexander', 'Willms', 'mollie@example.com', '486.287.3099 x972', '2023-01-01');
+INSERT INTO users (id, first_name, last_name, email, phone, created_at) VALUES (2, 'Queenie', 'Mayer', 'willy@example.com', '383.192.5682 x74583', '2023-02-14');
+INSERT INTO users (id, first_name, last_name, email, phone, created_at) VALUES (3, 'Alexander', 'Mayert', 'mollie@example.com', NULL, '2023-03-30');
@@ -0,0 +1,4 @@
+-- Users table
+INSERT INTO users (id, first_name, last_name, email, phone, created_at) VALUES (1, 'Alice', 'Smith', 'alice@work.com', '+1-555-0199', '2023-01-01');
+INSERT INTO users (id, first_name, last_name, email, phone, created_at) VALUES (2, 'Bob', 'Jones', 'bob@home.net', '+1-555-0142', '2023-02-14');
+INSERT INTO users (id, first_name, last_name, email, phone, created_at) VALUES (3, 'Alice', 'Brown', 'alice@work.com', NULL, '2023-03-30');
@@ -0,0 +1,11 @@
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE users(id INTEGER PRIMARY KEY, first_name TEXT, email TEXT NOT NULL, phone TEXT);
+INSERT INTO users VALUES (1, 'Alexander', 'mollie@example.com', '624-518-3989 x53931');
+INSERT INTO users VALUES (2, 'Queenie', 'jadyn@example.net', '1-660-875-1115 x076');
+CREATE TABLE IF NOT EXISTS "order items"("id" INTEGER PRIMARY KEY, "user_id" INTEGER, "email" TEXT, "note" TEXT);
+INSERT INTO "order items" VALUES (10, 1, 'mollie@example.com', 'i***');
+INSERT INTO "order items" ("id","user_id","email","note") VALUES (11, 2, 'jadyn@example.net', 'l***');
+CREATE INDEX users_email ON users(email);
+DELETE FROM sqlite_sequence;
+COMMIT;
@@ -0,0 +1,11 @@
+PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE users(id INTEGER PRIMARY KEY, first_name TEXT, email TEXT NOT NULL, phone TEXT);
+INSERT INTO users VALUES(1,'Alice','alice@work.com','+1-555-0100');
+INSERT INTO users VALUES(2,'Bob','bob@home.org','(555) 010-0199');
+CREATE TABLE IF NOT EXISTS "order items"("id" INTEGER PRIMARY KEY, "user_id" INTEGER, "email" TEXT, "note" TEXT);
+INSERT INTO "order items" VALUES(10,1,'alice@work.com','it''s a gift');
+INSERT INTO "order items"("id","user_id","email","note") VALUES(11,2,'bob@home.org','leave at door');
+CREATE INDEX users_email ON users(email);
+DELETE FROM sqlite_sequence;
+COMMIT;
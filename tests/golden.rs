@@ -0,0 +1,97 @@
+//! Golden-file tests: each `tests/fixtures/<name>.sql` is anonymized with
+//! seed 42 and compared to `<name>.expected.sql`. The config comes from
+//! `<name>.yaml` when present, otherwise from scanning the input, which
+//! exercises the full scan -> run pipeline.
+//!
+//! Run with `GHOSTDB_BLESS=1` to regenerate the expected files after an
+//! intentional output change, and review the diff before committing.
+
+mod common;
+
+use common::temp_path;
+use ghostdb::config::AppConfig;
+use ghostdb::processor::{run_processing, RunOptions, SqlDialect};
+use ghostdb::scanner::scan_file;
+use std::path::Path;
+
+fn check_fixture(name: &str) {
+    check_fixture_result(name).unwrap();
+}
+
+/// Compares the output of fixture `name` and returns the run's result,
+/// for fixtures that are expected to end with an error after writing.
+fn check_fixture_result(name: &str) -> anyhow::Result<()> {
+    check_fixture_with(name, &RunOptions::default())
+}
+
+/// Like [`check_fixture_result`], with non-default run options.
+fn check_fixture_with(name: &str, options: &RunOptions) -> anyhow::Result<()> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let input = dir.join(format!("{}.sql", name));
+    let config_path = dir.join(format!("{}.yaml", name));
+    let expected_path = dir.join(format!("{}.expected.sql", name));
+
+    let config = if config_path.exists() {
+        AppConfig::load(&config_path).unwrap()
+    } else {
+        scan_file(&input).unwrap()
+    };
+
+    let output = temp_path(&format!("golden_{}.sql", name));
+    let result = run_processing(&input, &output, &config, 42, options);
+    let actual = std::fs::read_to_string(&output).unwrap();
+    let _ = std::fs::remove_file(&output);
+
+    if std::env::var_os("GHOSTDB_BLESS").is_some() {
+        std::fs::write(&expected_path, &actual).unwrap();
+        return result;
+    }
+    let expected = std::fs::read_to_string(&expected_path)
+        .unwrap_or_else(|_| panic!("missing golden file {:?}", expected_path));
+    assert_eq!(actual, expected, "output of fixture '{}' changed", name);
+    result
+}
+
+#[test]
+fn single_row_inserts() {
+    check_fixture("single_row");
+}
+
+#[test]
+fn multi_row_inserts() {
+    check_fixture("multi_row");
+}
+
+#[test]
+fn quoted_and_escaped_values() {
+    check_fixture("quoted_values");
+}
+
+#[test]
+fn fakes_with_apostrophes_stay_quoted() {
+    check_fixture("apostrophe_fakes");
+}
+
+#[test]
+fn schema_qualified_tables() {
+    check_fixture("schema_qualified");
+}
+
+#[test]
+fn scan_then_run_pipeline() {
+    check_fixture("scan_pipeline");
+}
+
+#[test]
+fn sqlite_dump_with_positional_inserts() {
+    check_fixture("sqlite_dump");
+}
+
+#[test]
+fn oracle_dump_with_uppercase_names_and_to_date() {
+    let options = RunOptions {
+        dialect: SqlDialect::Oracle,
+        ..RunOptions::default()
+    };
+    check_fixture_with("oracle_dump", &options).unwrap();
+}
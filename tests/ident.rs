@@ -0,0 +1,71 @@
+mod common;
+
+use common::{config, run_sql, temp_path};
+use ghostdb::ident::{requote, split_column_list, unquote_identifier, unquote_qualified};
+use ghostdb::scanner::scan_file;
+
+#[test]
+fn identifiers_are_unquoted_in_every_style() {
+    assert_eq!(unquote_identifier("\"col\""), "col");
+    assert_eq!(unquote_identifier("`col`"), "col");
+    assert_eq!(unquote_identifier("[col]"), "col");
+    assert_eq!(unquote_identifier(" col "), "col");
+    assert_eq!(unquote_identifier("\"say \"\"hi\"\"\""), "say \"hi\"");
+    assert_eq!(unquote_identifier("`a``b`"), "a`b");
+    assert_eq!(unquote_identifier("[a]]b]"), "a]b");
+    // Mismatched quotes aren't quotes.
+    assert_eq!(unquote_identifier("[col\""), "[col\"");
+}
+
+#[test]
+fn qualified_names_and_column_lists_respect_quotes() {
+    assert_eq!(unquote_qualified("[dbo].[users]"), "dbo.users");
+    assert_eq!(unquote_qualified("\"my.schema\".\"users\""), "my.schema.users");
+    assert_eq!(unquote_qualified("public.users"), "public.users");
+    assert_eq!(
+        split_column_list("id, \"first,name\", `e-mail`, [zip code]"),
+        vec!["id", "first,name", "e-mail", "zip code"]
+    );
+}
+
+#[test]
+fn requote_keeps_the_style_and_escapes_the_new_name() {
+    assert_eq!(requote("[col]", "c1"), "[c1]");
+    assert_eq!(requote("`col`", "c1"), "`c1`");
+    assert_eq!(requote("\"col\"", "a\"b"), "\"a\"\"b\"");
+    assert_eq!(requote("col", "c1"), "c1");
+}
+
+#[test]
+fn sql_server_inserts_are_anonymized() {
+    let config = config("tables:\n  users:\n    columns:\n      email: email\n");
+    let out = run_sql(
+        "INSERT [dbo].[users] ([id], [email]) VALUES (1, N'ann@work.com');\n",
+        &config,
+    );
+    assert!(!out.contains("ann@work.com"), "{}", out);
+    assert!(out.starts_with("INSERT [dbo].[users] ([id], [email]) VALUES (1, N'"), "{}", out);
+}
+
+#[test]
+fn quoted_names_match_config_entries_in_every_style() {
+    let config = config("tables:\n  users:\n    columns:\n      email: email\n");
+    for sql in [
+        "INSERT INTO `users` (`id`, `email`) VALUES (1, 'ann@work.com');\n",
+        "INSERT INTO [users] ([id], [email]) VALUES (1, 'ann@work.com');\n",
+        "INSERT INTO \"users\" (\"id\", \"email\") VALUES (1, 'ann@work.com');\n",
+    ] {
+        let out = run_sql(sql, &config);
+        assert!(!out.contains("ann@work.com"), "{}", out);
+        assert_eq!(out[..out.find("VALUES").unwrap()], sql[..sql.find("VALUES").unwrap()]);
+    }
+
+    // A scanned config keeps names as written, and they match the dump.
+    let path = temp_path("scan_quoted_names.sql");
+    let sql = "INSERT INTO `users` (`id`, `email`) VALUES (1, 'ann@work.com');\nINSERT INTO [dbo].[orders] ([id], [phone]) VALUES (1, '555-123-4567');\n";
+    std::fs::write(&path, sql).unwrap();
+    let scanned = scan_file(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let out = run_sql(sql, &scanned);
+    assert!(!out.contains("ann@work.com") && !out.contains("555-123-4567"), "{}", out);
+}
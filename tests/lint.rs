@@ -0,0 +1,80 @@
+mod common;
+
+use common::config;
+use ghostdb::lint::lint_config;
+
+fn messages(yaml: &str) -> Vec<String> {
+    lint_config(&config(yaml)).iter().map(|i| i.to_string()).collect()
+}
+
+#[test]
+fn clean_config_has_no_issues() {
+    let issues = messages(
+        "tables:\n  users:\n    columns:\n      id: keep\n      email: email\n      first_name: first_name\n    options:\n      first_name:\n        max_len: 8\n",
+    );
+    assert!(issues.is_empty(), "{:?}", issues);
+}
+
+#[test]
+fn dead_and_conflicting_rules_are_reported() {
+    let issues = messages(
+        "case_insensitive_tables: true\ntables:\n  users:\n    columns:\n      Email: email\n      email: mask\n      code: !fixed X\n    options:\n      code:\n        unique: true\n      phone:\n        max_len: 5\n  USERS:\n    columns: {}\n",
+    );
+    assert_eq!(
+        issues,
+        vec![
+            "users: differs from table `USERS` only by case; with case_insensitive_tables, names matching neither exactly use `USERS`",
+            "users.code: `unique` cannot be satisfied by `fixed`; the run fails on the second distinct value",
+            "users.email: differs from column `Email` only by case; names matching neither exactly use `Email`",
+            "users.phone: has options but no strategy, so the options are never used",
+        ]
+    );
+}
+
+#[test]
+fn strategies_that_do_not_fit_the_column_are_reported() {
+    let issues = messages(
+        "tables:\n  orders:\n    columns:\n      user_id: email\n      status: !date_shift {max_days: 30}\n      point: !geo_fuzz {radius_m: 100}\n",
+    );
+    assert_eq!(issues.len(), 3, "{:?}", issues);
+    assert!(issues[0].starts_with("orders.point: `geo_fuzz`"));
+    assert!(issues[1].starts_with("orders.status: `date_shift`"));
+    assert!(issues[2].starts_with("orders.user_id: looks like a key column"));
+}
+
+#[test]
+fn company_email_without_names_or_placeholders_is_reported() {
+    let issues = messages(
+        "tables:\n  contacts:\n    columns:\n      email: company_email\n  staff:\n    columns:\n      name: full_name\n      email: !company_email {format: info}\n",
+    );
+    assert_eq!(
+        issues,
+        vec![
+            "contacts.email: `company_email` in a table without name columns; every address gets a random local part",
+            "staff.email: `company_email` `format` has no name placeholder, so every row gets the same address",
+        ]
+    );
+}
+
+#[test]
+fn pattern_keys_that_never_apply_are_reported() {
+    let issues = messages(
+        "tables:\n  users:\n    columns:\n      email: email\n      phone: mask\n      \"/email|phone/\": !fixed X\n      \"glob:*_name\": first_name\n      \"glob:last_name\": last_name\n      \"glob:nick_*\": mask\n      \"glob:pre?_name\": full_name\n      \"/.*_notes/\": !fixed N\n      \"glob:*_notes\": !fixed M\n",
+    );
+    assert_eq!(
+        issues,
+        vec![
+            "users./email|phone/: every column this pattern matches has its own entry, so it never applies",
+            "users.glob:last_name: every column this pattern matches goes to `glob:*_name` first, so it never applies",
+            "users.glob:pre?_name: `glob:*_name` is tried first and matches every column this pattern does, so it never applies",
+        ]
+    );
+}
+
+#[test]
+fn pattern_keys_skip_the_column_name_checks() {
+    let issues = messages(
+        "tables:\n  orders:\n    columns:\n      \"glob:*_id\": email\n      \"/.*_(status|kind)/\": !date_shift {max_days: 30}\n      \"glob:pos_*\": !geo_fuzz {radius_m: 100}\n",
+    );
+    assert!(issues.is_empty(), "{:?}", issues);
+}
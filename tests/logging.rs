@@ -0,0 +1,34 @@
+use ghostdb::logging::record_json;
+use log::kv::Value;
+use log::{Level, Record};
+use serde_json::json;
+
+#[test]
+fn records_become_json_objects_with_typed_fields() {
+    let fields = [
+        ("event", Value::from("skipped_statement")),
+        ("line", Value::from(42u64)),
+        ("table", Value::from("users")),
+        ("resumed", Value::from(false)),
+    ];
+    let object = record_json(
+        &Record::builder()
+            .args(format_args!("Column count mismatch. Skipping line {}", 42))
+            .level(Level::Warn)
+            .target("ghostdb::processor")
+            .key_values(&fields)
+            .build(),
+    );
+    assert_eq!(
+        serde_json::Value::Object(object),
+        json!({
+            "level": "WARN",
+            "target": "ghostdb::processor",
+            "message": "Column count mismatch. Skipping line 42",
+            "event": "skipped_statement",
+            "line": 42,
+            "table": "users",
+            "resumed": false,
+        })
+    );
+}
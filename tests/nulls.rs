@@ -0,0 +1,78 @@
+mod common;
+
+use common::{config, process_sql, run_sql};
+use ghostdb::config::ColumnStrategy;
+use ghostdb::processor::RunOptions;
+use ghostdb::transformer::Transformer;
+
+const STRATEGIES: &str = "      a: email\n      b: first_name\n      c: mask\n      d: url\n      e: !fixed X\n      f: ssn\n      g: !prefixed_hash {prefix: k}\n";
+
+fn table(extra: &str) -> String {
+    format!("{}tables:\n  t:\n    columns:\n{}", extra, STRATEGIES)
+}
+
+#[test]
+fn sql_null_is_only_replaced_by_fixed() {
+    let config = config(&table(""));
+    let sql = "INSERT INTO t (a, b, c, d, e, f, g) VALUES (NULL, null, NULL, NULL, NULL, NULL, NULL);\n";
+    assert_eq!(
+        run_sql(sql, &config),
+        "INSERT INTO t (a, b, c, d, e, f, g) VALUES (NULL, null, NULL, NULL, 'X', NULL, NULL);\n"
+    );
+}
+
+#[test]
+fn transform_keeps_bare_null_in_any_case() {
+    let transformer = Transformer::new(42);
+    let mask = ColumnStrategy::Mask { char: '*', reveal_start: 0, reveal_end: 0 };
+    for strategy in [ColumnStrategy::Email, mask, ColumnStrategy::FirstName] {
+        assert_eq!(transformer.transform("NULL", &strategy).unwrap(), "NULL");
+        assert_eq!(transformer.transform("null", &strategy).unwrap(), "null");
+        assert_ne!(transformer.transform("'NULL'", &strategy).unwrap(), "'NULL'");
+        assert_ne!(transformer.transform("'alice'", &strategy).unwrap(), "'alice'");
+    }
+
+    let fixed = |s: &str| ColumnStrategy::Fixed(s.to_string());
+    assert_eq!(transformer.transform("NULL", &fixed("O'Hara")).unwrap(), "'O''Hara'");
+    assert_eq!(transformer.transform("null", &fixed("-1")).unwrap(), "-1");
+    assert_eq!(transformer.transform("'alice'", &fixed("X")).unwrap(), "'X'");
+}
+
+#[test]
+fn quoted_null_is_data_by_default() {
+    let config = config(&table(""));
+    let sql = "INSERT INTO t (a, b, c, d, e, f, g) VALUES ('NULL', 'NULL', 'NULL', 'NULL', 'NULL', 'NULL', 'NULL');\n";
+    let out = run_sql(sql, &config);
+    let values: Vec<&str> = out.split('\'').skip(1).step_by(2).collect();
+    assert_eq!(values.len(), 7, "{}", out);
+    assert!(values.iter().all(|v| *v != "NULL"), "{}", out);
+    assert!(!out.contains(", NULL"), "{}", out);
+}
+
+#[test]
+fn quoted_null_can_be_kept_or_normalized() {
+    let sql = "INSERT INTO t (a, b, c, d, e, f, g) VALUES ('NULL', 'null', 'NULL', 'NULL', 'NULL', 'NULL', 'x@y.com');\n";
+
+    let keep = config(&table("quoted_null: keep\n"));
+    let out = run_sql(sql, &keep);
+    assert!(out.starts_with("INSERT INTO t (a, b, c, d, e, f, g) VALUES ('NULL', 'null', 'NULL', 'NULL', 'NULL', 'NULL', 'k_"), "{}", out);
+
+    let normalize = config(&table("quoted_null: normalize\n"));
+    let out = run_sql(sql, &normalize);
+    assert!(out.starts_with("INSERT INTO t (a, b, c, d, e, f, g) VALUES (NULL, NULL, NULL, NULL, NULL, NULL, 'k_"), "{}", out);
+
+    // Strings that merely contain NULL are data either way.
+    let out = run_sql("INSERT INTO t (a) VALUES ('NULLS');\n", &normalize);
+    assert!(!out.contains("NULL"), "{}", out);
+}
+
+#[test]
+fn normalize_keeps_quoted_null_in_where_clauses() {
+    let config = config(&table("quoted_null: normalize\n"));
+    let sql = "DELETE FROM t WHERE a = 'NULL' OR a IS NULL;\n";
+    let options = RunOptions {
+        scrub_where: true,
+        ..Default::default()
+    };
+    assert_eq!(process_sql(sql, &config, &options).0, sql);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::{config, run_sql, temp_path};
+use ghostdb::preview::dry_run;
+use ghostdb::processor::RunOptions;
+
+#[test]
+fn dry_run_samples_the_replacements_a_real_run_writes() {
+    let config = config("tables:\n  users:\n    columns:\n      id: keep\n      email: email\n");
+    let mut sql = String::new();
+    for i in 0..10 {
+        sql.push_str(&format!("INSERT INTO users (id, email) VALUES ({}, 'user{}@work.com');\n", i, i % 4));
+    }
+    let input = temp_path("preview.sql");
+    std::fs::write(&input, &sql).unwrap();
+    let report = dry_run(std::slice::from_ref(&input), &config, 42, &RunOptions::default(), 3).unwrap();
+    let _ = std::fs::remove_file(&input);
+
+    assert_eq!(report.anonymized_statements, 10);
+    assert_eq!(report.tables["users"].columns["email"].transformed, 10);
+    let samples = report.pseudonyms.unwrap();
+    // Unchanged `keep` values aren't samples; the first 3 emails are.
+    assert!(samples.get("users.id").is_none_or(|s| s.is_empty()));
+    let emails = &samples["users.email"];
+    assert_eq!(
+        emails.keys().collect::<Vec<_>>(),
+        ["'user0@work.com'", "'user1@work.com'", "'user2@work.com'"]
+    );
+    // Same seed, same pseudonyms as a real run.
+    let real = run_sql(&sql, &config);
+    for (original, fake) in emails {
+        let line = sql.lines().position(|l| l.contains(original.as_str())).unwrap();
+        assert!(real.lines().nth(line).unwrap().contains(fake.as_str()), "{}", fake);
+    }
+}
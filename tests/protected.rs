@@ -0,0 +1,61 @@
+mod common;
+
+use common::{config, run_sql};
+use ghostdb::processor::RunOptions;
+use ghostdb::transformer::Transformer;
+
+const CONFIG: &str = "\
+protected_columns:
+  - id
+  - orders.customer_id
+  - \".*_uuid\"
+tables:
+  users:
+    columns:
+      id: !fixed 0
+      email: !fixed hidden
+      user_uuid: mask
+  orders:
+    columns:
+      customer_id: !fixed 0
+";
+
+#[test]
+fn protected_columns_override_table_rules() {
+    let config = config(CONFIG);
+    let out = run_sql(
+        "INSERT INTO users (id, email, user_uuid) VALUES (7, 'a@b.com', 'abc-123');\n\
+         INSERT INTO public.orders (id, customer_id) VALUES (1, 7);\n",
+        &config,
+    );
+    assert_eq!(
+        out,
+        "INSERT INTO users (id, email, user_uuid) VALUES (7, 'hidden', 'abc-123');\n\
+         INSERT INTO public.orders (id, customer_id) VALUES (1, 7);\n"
+    );
+}
+
+#[test]
+fn table_qualified_entries_only_protect_that_table() {
+    let config = config(
+        "protected_columns: [orders.customer_id]\n\
+         tables:\n  users:\n    columns:\n      customer_id: !fixed 0\n",
+    );
+    let out = run_sql("INSERT INTO users (customer_id) VALUES (7);\n", &config);
+    assert!(out.ends_with("VALUES (0);\n"), "{}", out);
+}
+
+#[test]
+fn invalid_protected_pattern_is_an_error() {
+    let config = config("protected_columns: [\"id(\"]\ntables: {}\n");
+    let transformer = Transformer::new(42);
+    let err = ghostdb::processor::process_stream(
+        &transformer,
+        "SELECT 1;\n".as_bytes(),
+        &mut Vec::new(),
+        &config,
+        &RunOptions::default(),
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("protected_columns"), "{:#}", err);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{config, process_sql, run_sql};
+use ghostdb::processor::RunOptions;
+
+const RENAMES: &str = "\
+rename_create_table: true
+tables:
+  users:
+    columns:
+      id: keep
+      email: email
+    rename: t1
+    rename_columns:
+      email: c1
+      created: c2
+  orders:
+    columns:
+      user_id: keep
+    rename: t2
+    rename_columns:
+      user_id: c3
+";
+
+#[test]
+fn insert_headers_use_pseudonyms() {
+    let config = config(RENAMES);
+    let out = run_sql(
+        "INSERT INTO public.users (id, \"email\") VALUES (1, 'a@b.com');\nINSERT INTO orders SET user_id = 1;\n",
+        &config,
+    );
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("INSERT INTO public.t1 (id, \"c1\") VALUES (1, '"), "{}", out);
+    assert_eq!(lines[1], "INSERT INTO t2 SET c3 = 1;");
+}
+
+#[test]
+fn preserved_formatting_renames_in_place() {
+    let config = config(RENAMES);
+    let options = RunOptions {
+        preserve_formatting: true,
+        ..Default::default()
+    };
+    let (out, _) = process_sql(
+        "insert into users(id,email) values (1,'a@b.com'); -- note\n",
+        &config,
+        &options,
+    );
+    assert!(out.starts_with("insert into t1(id,c1) values (1, '"), "{}", out);
+    assert!(out.ends_with("'); -- note\n"), "{}", out);
+}
+
+#[test]
+fn create_table_is_renamed_consistently() {
+    let config = config(RENAMES);
+    let sql = "\
+CREATE TABLE `users` (
+  `id` int NOT NULL,
+  `email` varchar(255) DEFAULT 'email',
+  created date,
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `email_idx` (`email`)
+);
+CREATE TABLE orders (
+  user_id int,
+  CONSTRAINT fk FOREIGN KEY (user_id) REFERENCES users (email)
+);
+";
+    let out = run_sql(sql, &config);
+    assert_eq!(
+        out,
+        "\
+CREATE TABLE `t1` (
+  `id` int NOT NULL,
+  `c1` varchar(255) DEFAULT 'email',
+  c2 date,
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `email_idx` (`c1`)
+);
+CREATE TABLE t2 (
+  c3 int,
+  CONSTRAINT fk FOREIGN KEY (c3) REFERENCES t1 (c1)
+);
+"
+    );
+}
+
+#[test]
+fn create_table_is_untouched_without_the_flag() {
+    let config = config(&RENAMES.replace("rename_create_table: true\n", ""));
+    let sql = "CREATE TABLE users (\n  email text\n);\n";
+    assert_eq!(run_sql(sql, &config), sql);
+}
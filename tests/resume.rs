@@ -0,0 +1,150 @@
+mod common;
+
+use common::{config, temp_path};
+use ghostdb::processor::{process_file, RunOptions};
+use ghostdb::resume::{self, Checkpoint};
+use ghostdb::transformer::Transformer;
+use std::fs;
+
+const USERS: &str = "tables:\n  users:\n    columns:\n      email: email\n      name: first_name\n";
+
+fn input_sql() -> String {
+    let mut sql = String::from("CREATE TABLE users (id int, name text, email text);\n");
+    for i in 0..20 {
+        sql.push_str(&format!(
+            "INSERT INTO users (id, name, email) VALUES ({}, 'Name{}', 'user{}@work.com');\n",
+            i, i, i
+        ));
+    }
+    sql
+}
+
+/// Byte length of the first `n` lines of `text`.
+fn prefix_len(text: &str, n: usize) -> u64 {
+    text.split_inclusive('\n').take(n).map(str::len).sum::<usize>() as u64
+}
+
+#[test]
+fn resumed_run_matches_an_uninterrupted_one() {
+    let config = config(USERS);
+    let transformer = Transformer::new(42);
+    let input = temp_path("resume_in.sql");
+    let output = temp_path("resume_out.sql");
+    let sql = input_sql();
+    fs::write(&input, &sql).unwrap();
+
+    process_file(&transformer, &input, &output, &config, &RunOptions::default(), None).unwrap();
+    let reference = fs::read_to_string(&output).unwrap();
+    assert!(!resume::state_path(&output).exists());
+
+    // Simulate a crash after 8 lines, with a half-written statement after
+    // the checkpoint.
+    let lines = 8;
+    let output_offset = prefix_len(&reference, lines);
+    let mut partial = reference[..output_offset as usize].to_string();
+    partial.push_str("INSERT INTO users (id, name, em");
+    fs::write(&output, partial).unwrap();
+    Checkpoint {
+        fingerprint: resume::fingerprint(&input, &config, 42).unwrap(),
+        input_offset: prefix_len(&sql, lines),
+        output_offset,
+        lines,
+    }
+    .save(&resume::state_path(&output))
+    .unwrap();
+
+    let options = RunOptions {
+        resume: true,
+        ..Default::default()
+    };
+    let report = process_file(&transformer, &input, &output, &config, &options, None).unwrap();
+    assert_eq!(fs::read_to_string(&output).unwrap(), reference);
+    assert_eq!(report.processed_lines, 21);
+    assert!(!resume::state_path(&output).exists());
+
+    let _ = fs::remove_file(&input);
+    let _ = fs::remove_file(&output);
+}
+
+#[test]
+fn parallel_runs_record_checkpoints_to_resume_from() {
+    // The `command` column fails while the marker exists, interrupting the
+    // run at its last statement, past the first checkpoint.
+    let marker = temp_path("resume_parallel_marker");
+    fs::write(&marker, "").unwrap();
+    let config = config(&format!(
+        "tables:\n  users:\n    columns:\n      email: email\n  notes:\n    columns:\n      body: !command {{program: sh, args: [-c, 'test ! -e {} && exec cat']}}\n",
+        marker.display()
+    ));
+    // A fresh transformer for each run, as each would be a new process.
+    let input = temp_path("resume_parallel_in.sql");
+    let output = temp_path("resume_parallel_out.sql");
+    let filler = format!("-- {}\n", "x".repeat(1021));
+    let mut sql = String::new();
+    for i in 0..70_000 {
+        if i % 1000 == 0 {
+            sql.push_str(&format!("INSERT INTO users (id, email) VALUES ({}, 'user{}@work.com');\n", i, i));
+        } else {
+            sql.push_str(&filler);
+        }
+    }
+    sql.push_str("INSERT INTO notes (body) VALUES ('hello');\n");
+    fs::write(&input, &sql).unwrap();
+
+    let parallel = RunOptions {
+        threads_io: Some(2),
+        ..Default::default()
+    };
+    assert!(process_file(&Transformer::new(42), &input, &output, &config, &parallel, None).is_err());
+    let checkpoint = Checkpoint::load(&resume::state_path(&output)).unwrap().expect("no checkpoint");
+    assert!(checkpoint.input_offset >= resume::CHECKPOINT_INTERVAL);
+    assert_eq!(checkpoint.input_offset, prefix_len(&sql, checkpoint.lines));
+
+    fs::remove_file(&marker).unwrap();
+    let options = RunOptions {
+        resume: true,
+        ..Default::default()
+    };
+    let report = process_file(&Transformer::new(42), &input, &output, &config, &options, None).unwrap();
+    let resumed = fs::read_to_string(&output).unwrap();
+    assert_eq!(report.processed_lines, 70_001);
+    assert!(!resume::state_path(&output).exists());
+
+    process_file(&Transformer::new(42), &input, &output, &config, &parallel, None).unwrap();
+    assert_eq!(resumed, fs::read_to_string(&output).unwrap());
+    assert!(resumed.ends_with("INSERT INTO notes (body) VALUES ('hello');\n"));
+
+    let _ = fs::remove_file(&input);
+    let _ = fs::remove_file(&output);
+}
+
+#[test]
+fn checkpoint_from_another_run_is_rejected() {
+    let config = config(USERS);
+    let input = temp_path("resume_mismatch_in.sql");
+    let output = temp_path("resume_mismatch_out.sql");
+    fs::write(&input, input_sql()).unwrap();
+    fs::write(&output, "").unwrap();
+    let state = resume::state_path(&output);
+    Checkpoint {
+        // Taken with seed 7.
+        fingerprint: resume::fingerprint(&input, &config, 7).unwrap(),
+        input_offset: 0,
+        output_offset: 0,
+        lines: 0,
+    }
+    .save(&state)
+    .unwrap();
+
+    let options = RunOptions {
+        resume: true,
+        ..Default::default()
+    };
+    let err = process_file(&Transformer::new(42), &input, &output, &config, &options, None)
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("without --resume"), "{:#}", err);
+
+    let _ = fs::remove_file(&input);
+    let _ = fs::remove_file(&output);
+    let _ = fs::remove_file(&state);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{config, temp_path};
+use ghostdb::rotation::{compare_seeds, write_remapping};
+
+#[test]
+fn rotation_gives_seeded_columns_new_pseudonyms() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      id: keep\n      email: email\n      name: mask\n",
+    );
+    let input = temp_path("rotation.sql");
+    let mut sql = String::new();
+    for i in 0..20 {
+        sql.push_str(&format!(
+            "INSERT INTO users (id, email, name) VALUES ({}, 'user{}@work.com', 'Name{}');\n",
+            i, i, i
+        ));
+    }
+    std::fs::write(&input, &sql).unwrap();
+
+    let rotations = compare_seeds(&input, &config, 1, 2).unwrap();
+    let columns: Vec<&str> = rotations.iter().map(|r| r.column.as_str()).collect();
+    assert_eq!(columns, ["users.email", "users.name"]);
+
+    let email = &rotations[0];
+    assert!(email.seeded);
+    assert_eq!((email.values, email.unchanged), (20, 0));
+    assert_eq!(email.remapping.len(), 20);
+    let name = &rotations[1];
+    assert!(!name.seeded);
+    assert_eq!(name.unchanged, 20);
+    assert!(name.remapping.is_empty());
+
+    let remapping = temp_path("rotation.csv");
+    write_remapping(&remapping, &rotations).unwrap();
+    let csv = std::fs::read_to_string(&remapping).unwrap();
+    assert_eq!(csv.lines().count(), 21);
+    assert!(!csv.contains("@work.com"), "{}", csv);
+
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&remapping);
+}
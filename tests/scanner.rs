@@ -0,0 +1,231 @@
+mod common;
+
+use common::temp_path;
+use ghostdb::config::ColumnStrategy;
+use ghostdb::scanner::{coverage, filter_scan, merge_scan, scan_file_with_samples, SAMPLES_PER_COLUMN};
+
+#[test]
+fn scan_collects_a_few_distinct_samples_per_column() {
+    let path = temp_path("scan_samples.sql");
+    let mut sql = String::from("CREATE TABLE t (id int, note text);\n");
+    for i in 0..10 {
+        sql.push_str(&format!(
+            "INSERT INTO users (id, email) VALUES ({}, {});\n",
+            i,
+            if i < 2 { "NULL".to_string() } else { format!("'u{}@x.com'", i % 4) }
+        ));
+    }
+    sql.push_str("INSERT INTO t VALUES (1, 'it''s');\nINSERT INTO t SET id = 2, note = 'b';\n");
+    std::fs::write(&path, sql).unwrap();
+    let (config, samples) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(config.tables.len(), 2);
+    assert_eq!(samples["users"]["id"].len(), SAMPLES_PER_COLUMN);
+    assert_eq!(samples["users"]["email"], vec!["'u2@x.com'", "'u3@x.com'", "'u0@x.com'"]);
+    assert_eq!(samples["t"]["note"], vec!["'it''s'", "'b'"]);
+}
+
+#[test]
+fn scan_reads_inserts_spanning_lines_and_rows() {
+    let path = temp_path("scan_multiline.sql");
+    std::fs::write(
+        &path,
+        "INSERT INTO users (id,\n  email)\nVALUES (1, 'a@b.com;\n'),\n  (2, 'c@d.com');\nINSERT INTO logs (id) VALUES (1);\n",
+    )
+    .unwrap();
+    let (config, samples) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    // The `;` inside the literal doesn't end the statement early.
+    assert_eq!(config.tables.len(), 2);
+    assert_eq!(config.tables["users"].columns["email"], ColumnStrategy::Email);
+    assert_eq!(samples["users"]["email"], vec!["'a@b.com;\n'", "'c@d.com'"]);
+}
+
+#[test]
+fn scan_filter_limits_tables_and_keep_columns() {
+    let path = temp_path("scan_filter.sql");
+    std::fs::write(
+        &path,
+        "INSERT INTO public.\"users\" (id, email) VALUES (1, 'a@b.com');\n\
+         INSERT INTO orders (id, total) VALUES (1, 9.5);\n\
+         INSERT INTO logs (id, email) VALUES (1, 'a@b.com');\n",
+    )
+    .unwrap();
+    let (mut config, mut samples) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let tables = vec!["users".to_string(), "orders".to_string(), "nope".to_string()];
+    let unmatched = filter_scan(&mut config, &mut samples, &tables, true);
+    assert_eq!(unmatched, vec!["nope"]);
+    // `orders` only has keep columns, so nothing of it is left.
+    let names: Vec<&String> = config.tables.keys().collect();
+    assert_eq!(names, vec!["public.\"users\""]);
+    let columns: Vec<&String> = config.tables["public.\"users\""].columns.keys().collect();
+    assert_eq!(columns, vec!["email"]);
+    assert_eq!(samples.len(), 1);
+}
+
+#[test]
+fn scanned_config_serializes_in_sorted_order() {
+    let path = temp_path("scan_sorted.sql");
+    std::fs::write(
+        &path,
+        "INSERT INTO zeta (phone, id, email, name) VALUES ('1', 1, 'a@b.c', 'x');\n\
+         INSERT INTO alpha (zip, city, address) VALUES ('1', 'c', 'a');\n",
+    )
+    .unwrap();
+    let scans: Vec<String> = (0..2)
+        .map(|_| serde_yaml::to_string(&ghostdb::scanner::scan_file(&path).unwrap()).unwrap())
+        .collect();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(scans[0], scans[1]);
+    // Tables, `columns` and column names; strategy parameters are deeper.
+    let keys: Vec<&str> = scans[0]
+        .lines()
+        .filter(|line| line.len() - line.trim_start().len() <= 6)
+        .filter_map(|line| line.trim_start().split_once(':').map(|(key, _)| key))
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "tables", "alpha", "columns", "address", "city", "zip", "zeta", "columns", "email", "id",
+            "name", "phone",
+        ]
+    );
+}
+
+#[test]
+fn scan_guesses_from_values_when_the_name_says_nothing() {
+    let path = temp_path("scan_content.sql");
+    std::fs::write(
+        &path,
+        "INSERT INTO t (id, contact, tel, who, doc, mixed, label) VALUES (1, 'a@b.com', '(555) 123-4567', 'Ann Lee', '{\"owner\": {\"mail\": \"a@b.com\"}}', 'a@b.com', 'hello');\n\
+         INSERT INTO t (id, contact, tel, who, doc, mixed, label) VALUES (2, 'c@d.org', '+44 20 7946 0958', 'Bo Diaz', '[{\"id\": 2, \"who\": \"Bo Diaz\"}]', 'Bo Diaz', 'bye');\n",
+    )
+    .unwrap();
+    let (config, _) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let columns = &config.tables["t"].columns;
+    assert_eq!(columns["id"], ColumnStrategy::Keep);
+    assert_eq!(columns["contact"], ColumnStrategy::Email);
+    assert_eq!(columns["tel"], ColumnStrategy::Phone);
+    assert_eq!(columns["who"], ColumnStrategy::FullName);
+    assert_eq!(columns["doc"], ColumnStrategy::JsonPii);
+    assert_eq!(columns["mixed"], ColumnStrategy::Keep);
+    assert_eq!(columns["label"], ColumnStrategy::Keep);
+}
+
+#[test]
+fn values_override_the_name_unless_they_disagree() {
+    let path = temp_path("scan_override.sql");
+    let mut sql = String::new();
+    for i in 0..60 {
+        // Past the sampled values, `ref` stops looking like emails; the
+        // scan doesn't see that far.
+        let reference = if i < 50 { format!("'r{}@corp.test'", i) } else { "'n/a'".to_string() };
+        let note = if i == 3 { "'call me'".to_string() } else { format!("'n{}@corp.test'", i) };
+        sql.push_str(&format!(
+            "INSERT INTO t (notes, ref, comments, name_count, city, password) VALUES ('x{}@corp.test', {}, {}, {}, 'New York', 1234);\n",
+            i, reference, note, i
+        ));
+    }
+    std::fs::write(&path, sql).unwrap();
+    let (config, samples) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let columns = &config.tables["t"].columns;
+    assert_eq!(columns["notes"], ColumnStrategy::Email);
+    assert_eq!(columns["ref"], ColumnStrategy::Email);
+    // One value that isn't an email leaves it to the name.
+    assert_eq!(columns["comments"], ColumnStrategy::default_mask());
+    assert_eq!(columns["name_count"], ColumnStrategy::Keep);
+    // Place names look like people's names; the name wins.
+    assert_eq!(columns["city"], ColumnStrategy::Fixed("ANONYMIZED ADDRESS".to_string()));
+    assert_eq!(columns["password"], ColumnStrategy::Fixed("REDACTED_SECRET".to_string()));
+    assert_eq!(samples["t"]["notes"].len(), SAMPLES_PER_COLUMN);
+}
+
+#[test]
+fn declared_types_narrow_the_guesses() {
+    let path = temp_path("scan_types.sql");
+    std::fs::write(
+        &path,
+        "CREATE TABLE `people` (\n  `id` int NOT NULL,\n  `first_name` varchar(12),\n  `full_name` varchar(255),\n  \
+         `phone` bigint unsigned,\n  `street_number` int,\n  `api_key` numeric(20, 0),\n  `password` smallint\n);\n\
+         INSERT INTO `people` VALUES (1,'Ann','Ann Lee',5550142,12,48151623,1234);\n\
+         INSERT INTO contacts (phone) VALUES ('555-0142');\n",
+    )
+    .unwrap();
+    let (scanned, _) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let people = &scanned.tables["`people`"];
+    assert_eq!(people.columns["first_name"], ColumnStrategy::FirstName);
+    assert_eq!(people.columns["full_name"], ColumnStrategy::FullName);
+    // Numbers stay numbers: what the name would change gets its digits
+    // scrambled instead, redacted columns included, and only kept columns
+    // are kept.
+    assert_eq!(people.columns["phone"], ColumnStrategy::DigitScramble);
+    assert_eq!(people.columns["api_key"], ColumnStrategy::DigitScramble);
+    assert_eq!(people.columns["street_number"], ColumnStrategy::DigitScramble);
+    assert_eq!(people.columns["password"], ColumnStrategy::DigitScramble);
+    assert_eq!(people.columns["id"], ColumnStrategy::Keep);
+    // Short name columns bound their fakes; long ones don't need to.
+    assert_eq!(people.options["first_name"].max_len, Some(12));
+    assert_eq!(people.options.len(), 1);
+    // Without a CREATE TABLE, the guess stands.
+    assert_eq!(scanned.tables["contacts"].columns["phone"], ColumnStrategy::Phone);
+
+    // Merged columns bring their options along.
+    let mut existing = common::config("tables:\n  people:\n    columns:\n      id: keep\n");
+    merge_scan(&mut existing, scanned);
+    assert_eq!(existing.tables["people"].options["first_name"].max_len, Some(12));
+}
+
+#[test]
+fn merge_adds_only_new_tables_and_columns() {
+    let path = temp_path("scan_merge.sql");
+    std::fs::write(
+        &path,
+        "INSERT INTO public.\"users\" (id, email, phone) VALUES (1, 'a@b.com', '1');\n\
+         INSERT INTO orders (id, note) VALUES (1, 'x');\n",
+    )
+    .unwrap();
+    let (scanned, _) = scan_file_with_samples(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let mut existing = common::config("tables:\n  users:\n    columns:\n      email: !fixed KEEP_ME\n");
+    let merged = merge_scan(&mut existing, scanned);
+    assert_eq!(merged.tables, vec!["orders"]);
+    assert_eq!(
+        merged.columns,
+        vec![("users".to_string(), "id".to_string()), ("users".to_string(), "phone".to_string())]
+    );
+    let users = &existing.tables["users"].columns;
+    assert_eq!(users["email"], ColumnStrategy::Fixed("KEEP_ME".to_string()));
+    assert_eq!(users["phone"], ColumnStrategy::Phone);
+    assert_eq!(existing.tables["orders"].columns.len(), 2);
+    assert_eq!(existing.tables.len(), 2);
+}
+
+#[test]
+fn coverage_flags_kept_columns_with_personal_names() {
+    let config = common::config(
+        "tables:\n  users:\n    columns:\n      id: keep\n      email: email\n      birth_date: keep\n      username: keep\n      status: keep\n  logs:\n    columns:\n      ip: keep\n      payload: json_pii\n",
+    );
+    let coverage = coverage(&config);
+    assert_eq!(coverage.candidates, 5);
+    assert_eq!(coverage.anonymized, 2);
+    assert_eq!(
+        coverage.kept,
+        vec![
+            ("logs".to_string(), "ip".to_string()),
+            ("users".to_string(), "birth_date".to_string()),
+            ("users".to_string(), "username".to_string()),
+        ]
+    );
+}
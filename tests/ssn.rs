@@ -0,0 +1,52 @@
+mod common;
+
+use common::{config, run_sql};
+use ghostdb::config::ColumnStrategy;
+use ghostdb::scanner::guess_strategy;
+use ghostdb::transformer::Transformer;
+
+/// Area 900-999 with group 01-49: neither an SSN nor an ITIN.
+fn is_unassignable(digits: &str) -> bool {
+    let area: u32 = digits[..3].parse().unwrap();
+    let group: u32 = digits[3..5].parse().unwrap();
+    let serial: u32 = digits[5..].parse().unwrap();
+    (900..=999).contains(&area) && (1..=49).contains(&group) && serial != 0
+}
+
+#[test]
+fn generated_ssns_are_never_assignable() {
+    let transformer = Transformer::new(7);
+    for i in 0..500 {
+        let original = format!("'{:03}-{:02}-{:04}'", 100 + i % 600, 1 + i % 99, i);
+        let fake = transformer.transform(&original, &ColumnStrategy::Ssn).unwrap();
+        let fake = fake.trim_matches('\'');
+        assert_eq!(fake.len(), 11, "{}", fake);
+        assert_eq!((&fake[3..4], &fake[6..7]), ("-", "-"), "{}", fake);
+        assert!(is_unassignable(&fake.replace('-', "")), "{}", fake);
+    }
+}
+
+#[test]
+fn separators_are_kept_and_other_formats_normalized() {
+    let config = config("tables:\n  people:\n    columns:\n      ssn: ssn\n");
+    let sql: String = ["123456789", "123 45 6789", "12-345", "123-45-6789", "123-45-6789"]
+        .iter()
+        .map(|ssn| format!("INSERT INTO people (ssn) VALUES ('{}');\n", ssn))
+        .collect();
+    let out = run_sql(&sql, &config);
+    let values: Vec<&str> = out.split('\'').skip(1).step_by(2).collect();
+    assert_eq!(values.len(), 5);
+    assert!(values[0].chars().all(|c| c.is_ascii_digit()) && values[0].len() == 9);
+    assert_eq!(values[1].matches(' ').count(), 2);
+    assert_eq!(values[2].len(), 11);
+    assert_eq!(values[3], values[4]);
+    assert_ne!(values[3], "123-45-6789");
+}
+
+#[test]
+fn ssn_columns_are_detected() {
+    assert_eq!(guess_strategy("ssn"), ColumnStrategy::Ssn);
+    assert_eq!(guess_strategy("customer_ssn"), ColumnStrategy::Ssn);
+    assert_eq!(guess_strategy("social_security_number"), ColumnStrategy::Ssn);
+    assert_eq!(guess_strategy("lesson"), ColumnStrategy::Keep);
+}
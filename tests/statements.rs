@@ -0,0 +1,959 @@
+mod common;
+
+use common::{config, process_sql, run_sql, temp_path};
+use ghostdb::config::ColumnStrategy;
+use ghostdb::failure::Failure;
+use ghostdb::processor::{default_threads_io, InputFormat, RunOptions};
+use ghostdb::transformer::Transformer;
+use std::io::{BufReader, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const USERS: &str = "tables:\n  users:\n    columns:\n      id: keep\n      email: email\n";
+
+#[test]
+fn insert_modifiers_are_anonymized_and_preserved() {
+    let config = config(USERS);
+    for modifier in ["IGNORE", "LOW_PRIORITY", "DELAYED", "LOW_PRIORITY IGNORE"] {
+        let sql = format!(
+            "INSERT {} INTO users (id, email) VALUES (1, 'alice@work.com');\n",
+            modifier
+        );
+        let out = run_sql(&sql, &config);
+        let expected_prefix = format!("INSERT {} INTO users (id, email) VALUES (1, '", modifier);
+        assert!(out.starts_with(&expected_prefix), "{}", out);
+        assert!(!out.contains("alice@work.com"), "{}", out);
+    }
+}
+
+#[test]
+fn insert_set_form_is_anonymized() {
+    let config = config(USERS);
+    let out = run_sql(
+        "INSERT INTO users SET id = 1, email = 'alice@work.com';\n",
+        &config,
+    );
+    assert!(out.starts_with("INSERT INTO users SET id = 1, email = '"), "{}", out);
+    assert!(!out.contains("alice@work.com"), "{}", out);
+}
+
+#[test]
+fn insert_ignore_set_without_into_is_anonymized() {
+    let config = config(USERS);
+    let out = run_sql(
+        "INSERT IGNORE users SET id=1, email='a,b@work.com';\n",
+        &config,
+    );
+    assert!(out.starts_with("INSERT IGNORE users SET id = 1, email = '"), "{}", out);
+    assert!(!out.contains("a,b@work.com"), "{}", out);
+}
+
+#[test]
+fn parse_assignments_rejects_non_assignments() {
+    assert_eq!(
+        Transformer::parse_assignments("a = 1, b = 'x=y'"),
+        vec![("a".to_string(), "1".to_string()), ("b".to_string(), "'x=y'".to_string())]
+    );
+    assert!(Transformer::parse_assignments("a = 1, 2").is_empty());
+}
+
+#[test]
+fn report_counts_match_processed_statements() {
+    let config = config(USERS);
+    let sql = "-- header\nINSERT INTO users (id, email) VALUES (1, 'a@b.com');\nINSERT INTO users (id, email) VALUES (2, 'c@d.com');\n";
+    let (_, report) = process_sql(sql, &config, &RunOptions::default());
+    assert_eq!(report.processed_lines, 3);
+    assert_eq!(report.anonymized_statements, 2);
+    let email = &report.tables["users"].columns["email"];
+    assert_eq!((email.transformed, email.kept), (2, 0));
+}
+
+#[test]
+fn unique_columns_never_repeat_a_pseudonym() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      name: first_name\n    options:\n      name:\n        unique: true\n",
+    );
+    let mut sql = String::new();
+    for i in 0..200 {
+        sql.push_str(&format!("INSERT INTO users (name) VALUES ('user{}');\n", i));
+    }
+    sql.push_str("INSERT INTO users (name) VALUES ('user7');\n");
+    let out = run_sql(&sql, &config);
+    let names: Vec<&str> = out.lines().collect();
+    let distinct: std::collections::HashSet<&str> = names[..200].iter().copied().collect();
+    assert_eq!(distinct.len(), 200);
+    assert_eq!(names[200], names[7]);
+}
+
+#[test]
+fn multi_row_inserts_anonymize_every_tuple() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      id: keep\n      email: !tokenize {prefix: user}\n",
+    );
+    let sql = "\
+INSERT INTO users (id, email) VALUES (1,'a@x.com'),(2, 'it''s), (3, x'),
+  (LEAST(3, 5), 'c@x.com') /* last */, (4,'a@x.com');
+";
+    let expected = "\
+INSERT INTO users (id, email) VALUES (1, 'user_1'), (2, 'user_2'), (LEAST(3, 5), 'user_3'), (4, 'user_1');
+";
+    assert_eq!(run_sql(sql, &config), expected);
+
+    let options = RunOptions {
+        preserve_formatting: true,
+        ..Default::default()
+    };
+    let (out, _) = process_sql("INSERT INTO users (id, email) VALUES (1,'a@x.com'),\n  (2,'b@x.com');\n", &config, &options);
+    assert_eq!(out, "INSERT INTO users (id, email) VALUES (1, 'user_1'),\n  (2, 'user_2');\n");
+}
+
+#[test]
+fn mysqldump_extended_inserts_anonymize_every_tuple() {
+    let config = config("tables:\n  users:\n    columns:\n      name: full_name\n      email: email\n");
+    // One line per table, no spaces between tuples, backslash escapes, and
+    // a `;` and parentheses inside values.
+    let sql = "CREATE TABLE `users` (\n  `id` int NOT NULL,\n  `name` varchar(64),\n  `email` varchar(64)\n);\n\
+INSERT INTO `users` VALUES (1,'O\\'Brien','a@x.com'),(2,'Semi;colon','b@x.com'),(3,'Paren (x)','c@x.com');\n";
+    let (out, report) = process_sql(sql, &config, &RunOptions::default());
+    let insert = out.lines().last().unwrap();
+    assert!(insert.starts_with("INSERT INTO `users` VALUES (1, '"), "{}", insert);
+    assert_eq!(insert.matches("), (").count(), 2, "{}", insert);
+    for original in ["Brien", "Semi", "Paren", "a@x.com", "b@x.com", "c@x.com"] {
+        assert!(!insert.contains(original), "{} in {}", original, insert);
+    }
+    assert_eq!(report.tables["`users`"].statements, 1);
+    assert_eq!(report.tables["`users`"].columns["email"].transformed, 3);
+}
+
+#[test]
+fn mysqldump_layout_and_escapes_survive_no_header_rebuild() {
+    let config = config("tables:\n  users:\n    columns:\n      email: email\n      path: keep\n");
+    let options = RunOptions {
+        no_header_rebuild: true,
+        ..Default::default()
+    };
+    // A string ending in an escaped backslash, escaped double quotes and
+    // `\0`, all in kept values next to rewritten ones.
+    let sql = "INSERT INTO `users` (`id`,`path`,`email`) VALUES (1,'C:\\\\','a@x.com'),(2,'say \\\"hi\\\" \\0 it\\'s','b@x.com');\n";
+    let (out, report) = process_sql(sql, &config, &options);
+    assert_eq!(report.tables["`users`"].columns["email"].transformed, 2);
+    let (head, rest) = out.split_once("VALUES (1,'C:\\\\','").unwrap();
+    assert_eq!(head, "INSERT INTO `users` (`id`,`path`,`email`) ");
+    let (first, rest) = rest.split_once("'),(2,'say \\\"hi\\\" \\0 it\\'s','").unwrap();
+    assert!(first.contains('@') && first != "a@x.com", "{}", out);
+    assert!(rest.ends_with("');\n") && !rest.contains("b@x.com"), "{}", out);
+}
+
+#[test]
+fn malformed_multi_row_inserts_are_copied_with_a_warning() {
+    let config = config(USERS);
+    for sql in [
+        "INSERT INTO users (id, email) VALUES (1, 'a@x.com') (2, 'b@x.com');\n",
+        "INSERT INTO users (id, email) VALUES (1, 'a@x.com'), 2, 'b@x.com');\n",
+        "INSERT INTO users (id, email) VALUES (1, 'a@x.com'), (2);\n",
+    ] {
+        let (out, report) = process_sql(sql, &config, &RunOptions::default());
+        assert_eq!(out, sql);
+        assert_eq!(report.skipped_statements, 1, "{}", sql);
+    }
+}
+
+#[test]
+fn tokenize_numbers_distinct_values_per_column() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      email: !tokenize {prefix: user}\n      team: !tokenize {prefix: ''}\n",
+    );
+    let sql = "\
+INSERT INTO users (email, team) VALUES ('a@x.com', 'red');
+INSERT INTO users (email, team) VALUES ('b@x.com', 'red');
+INSERT INTO users (email, team) VALUES ('a@x.com', 'blue');
+INSERT INTO users (email, team) VALUES (NULL, 'green');
+";
+    let expected = "\
+INSERT INTO users (email, team) VALUES ('user_1', '1');
+INSERT INTO users (email, team) VALUES ('user_2', '1');
+INSERT INTO users (email, team) VALUES ('user_1', '2');
+INSERT INTO users (email, team) VALUES (NULL, '3');
+";
+    assert_eq!(run_sql(sql, &config), expected);
+}
+
+#[test]
+fn template_seq_numbers_distinct_values_per_column() {
+    let config = config("tables:\n  staff:\n    columns:\n      code: !template \"EMP-{seq:4}\"\n      badge: !template \"{seq}\"\n");
+    let sql = "\
+INSERT INTO staff (code, badge) VALUES ('x-17', 9001);
+INSERT INTO staff (code, badge) VALUES ('x-02', 9001);
+INSERT INTO staff (code, badge) VALUES ('x-17', NULL);
+";
+    let expected = "\
+INSERT INTO staff (code, badge) VALUES ('EMP-0001', 1);
+INSERT INTO staff (code, badge) VALUES ('EMP-0002', 1);
+INSERT INTO staff (code, badge) VALUES ('EMP-0001', NULL);
+";
+    assert_eq!(run_sql(sql, &config), expected);
+}
+
+#[test]
+fn unique_column_fails_when_strategy_cannot_vary() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      code: !fixed X\n    options:\n      code:\n        unique: true\n",
+    );
+    let sql = "INSERT INTO users (code) VALUES ('a');\nINSERT INTO users (code) VALUES ('b');\n";
+    let transformer = Transformer::new(42);
+    let mut out = Vec::new();
+    let result = ghostdb::processor::process_stream(
+        &transformer,
+        sql.as_bytes(),
+        &mut out,
+        &config,
+        &RunOptions::default(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn mysqldump_header_and_comments_pass_through_unchanged() {
+    let config = config(USERS);
+    let sql = "\
+-- MySQL dump 10.13  Distrib 8.0.36, for Linux (x86_64)
+--
+-- Host: localhost    Database: app; charset 'utf8'
+/*!40101 SET @OLD_CHARACTER_SET_CLIENT=@@CHARACTER_SET_CLIENT */;
+/*!40103 SET TIME_ZONE='+00:00' */;
+/*
+ * Not data: INSERT INTO users (id, email) VALUES (9, 'ghost@work.com');
+ */
+/*!40000 ALTER TABLE `users` DISABLE KEYS */;
+INSERT INTO users (id, email) VALUES (1, 'alice@work.com');
+/*!40000 ALTER TABLE `users` ENABLE KEYS */;
+";
+    let (out, report) = process_sql(sql, &config, &RunOptions::default());
+    let out_lines: Vec<&str> = out.lines().collect();
+    let in_lines: Vec<&str> = sql.lines().collect();
+    assert_eq!(out_lines.len(), in_lines.len());
+    for (i, (o, l)) in out_lines.iter().zip(&in_lines).enumerate() {
+        if i != 9 {
+            assert_eq!(o, l);
+        }
+    }
+    assert!(!out_lines[9].contains("alice@work.com"), "{}", out);
+    assert_eq!(report.anonymized_statements, 1);
+    assert_eq!(report.processed_lines, 11);
+}
+
+#[test]
+fn statements_spanning_lines_respect_quotes_and_comments() {
+    let config = config(USERS);
+    let sql = "\
+INSERT INTO users (id, email)
+VALUES (1, /* first; \"row' */ 'alice;@work.com' -- done?;
+);
+SELECT 1;
+";
+    let options = RunOptions {
+        preserve_formatting: true,
+        ..Default::default()
+    };
+    let (out, report) = process_sql(sql, &config, &options);
+    assert_eq!(report.anonymized_statements, 1);
+    assert!(!out.contains("alice;@work.com"), "{}", out);
+    // Comments inside the value list are dropped; values stay quoted.
+    assert!(out.starts_with("INSERT INTO users (id, email)\nVALUES (1, '"), "{}", out);
+    assert!(out.ends_with("');\nSELECT 1;\n"), "{}", out);
+}
+
+#[test]
+fn pipelined_run_matches_serial_output() {
+    let config = config(USERS);
+    let mut sql = String::from("-- header\n");
+    for i in 0..1000 {
+        sql.push_str(&format!(
+            "INSERT INTO users (id, email) VALUES ({}, 'user{}@work.com');\n",
+            i, i
+        ));
+    }
+    let (serial, serial_report) = process_sql(&sql, &config, &RunOptions::default());
+
+    let transformer = Transformer::new(42);
+    let mut out = Vec::new();
+    let report = ghostdb::processor::process_stream_pipelined(
+        &transformer,
+        sql.as_bytes(),
+        &mut out,
+        &config,
+        &RunOptions::default(),
+        4,
+    )
+    .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), serial);
+    assert_eq!(report.processed_lines, serial_report.processed_lines);
+    assert_eq!(report.anonymized_statements, 1000);
+
+    // A CREATE TABLE later in the same batch doesn't reach back to the
+    // statements before it.
+    let config = common::config("tables: {}\ntype_rules:\n  text: !fixed X\n");
+    let sql = "INSERT INTO t VALUES ('secret');\nCREATE TABLE t (a text);\nINSERT INTO t VALUES ('secret');\n";
+    let (serial, _) = process_sql(sql, &config, &RunOptions::default());
+    assert_eq!(serial.lines().next(), Some("INSERT INTO t VALUES ('secret');"), "{}", serial);
+    let mut out = Vec::new();
+    ghostdb::processor::process_stream_pipelined(&transformer, sql.as_bytes(), &mut out, &config, &RunOptions::default(), 2)
+        .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), serial);
+}
+
+/// A reader that counts the bytes handed out.
+struct CountingReader<'a> {
+    data: &'a [u8],
+    read: Arc<AtomicUsize>,
+}
+
+impl Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.data.read(buf)?;
+        self.read.fetch_add(n, Ordering::SeqCst);
+        Ok(n)
+    }
+}
+
+/// A writer that notes how much input had been read when it was first
+/// written to.
+struct FirstWrite {
+    read: Arc<AtomicUsize>,
+    read_at_first_write: Option<usize>,
+}
+
+impl Write for FirstWrite {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.read_at_first_write.get_or_insert(self.read.load(Ordering::SeqCst));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn pipelined_reader_waits_for_a_slow_batch() {
+    // The first statement takes a second; the rest are quick.
+    let config = config("tables:\n  notes:\n    columns:\n      body: !command {program: sh, args: [-c, 'sleep 1; exec cat']}\n");
+    let mut sql = String::from("INSERT INTO notes (body) VALUES ('slow');\n");
+    for i in 0..50_000 {
+        sql.push_str(&format!("-- line {}\n", i));
+    }
+    let read = Arc::new(AtomicUsize::new(0));
+    let reader = BufReader::new(CountingReader {
+        data: sql.as_bytes(),
+        read: Arc::clone(&read),
+    });
+    let mut writer = FirstWrite {
+        read,
+        read_at_first_write: None,
+    };
+    ghostdb::processor::process_stream_pipelined(&Transformer::new(42), reader, &mut writer, &config, &RunOptions::default(), 2)
+        .unwrap();
+    // Only a few batches get ahead of the one the writer waits for.
+    let read = writer.read_at_first_write.unwrap();
+    assert!(read < sql.len() / 4, "{} of {} bytes read", read, sql.len());
+}
+
+#[test]
+fn wrapped_multi_row_inserts_are_anonymized_serially_and_pipelined() {
+    let config = config(USERS);
+    let sql = "\
+INSERT INTO users
+  (id, email)
+VALUES
+  (1, 'alice@work.com'),
+  (2, 'ends);here@work.com'
+  );
+SELECT 1;
+";
+    let (serial, report) = process_sql(sql, &config, &RunOptions::default());
+    assert_eq!(report.anonymized_statements, 1);
+    assert!(!serial.contains("@work.com"), "{}", serial);
+    assert!(serial.ends_with("SELECT 1;\n"), "{}", serial);
+
+    let transformer = Transformer::new(42);
+    let mut out = Vec::new();
+    ghostdb::processor::process_stream_pipelined(
+        &transformer,
+        sql.as_bytes(),
+        &mut out,
+        &config,
+        &RunOptions::default(),
+        2,
+    )
+    .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), serial);
+}
+
+#[test]
+fn insert_without_column_list_needs_a_create_table() {
+    let config = config("tables:\n  users:\n    columns:\n      email: !fixed x\n");
+    let sql = "INSERT INTO users VALUES(1,'a@b.com');\n";
+    let (out, report) = process_sql(sql, &config, &RunOptions::default());
+    assert_eq!(out, sql);
+    assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+
+    let sql = format!("CREATE TABLE \"users\"(id INTEGER, email TEXT);\n{}", sql);
+    let (out, _) = process_sql(&sql, &config, &RunOptions::default());
+    assert!(out.ends_with("INSERT INTO users VALUES (1, 'x');\n"), "{}", out);
+}
+
+#[test]
+fn schema_file_gives_a_data_only_dump_its_columns() {
+    let config = config("tables:\n  users:\n    columns:\n      email: !fixed x\n");
+    let schema = temp_path("schema_only.sql");
+    std::fs::write(&schema, "SET search_path = public;\nCREATE TABLE public.users (\n  id integer,\n  email text\n);\n").unwrap();
+    let options = RunOptions {
+        schema: Some(schema.clone()),
+        ..Default::default()
+    };
+    let sql = "INSERT INTO users VALUES(1,'a@b.com');\nCOPY public.users FROM stdin;\n2\tc@d.com\n\\.\n";
+    let (out, report) = process_sql(sql, &config, &options);
+    assert_eq!(out, "INSERT INTO users VALUES (1, 'x');\nCOPY public.users FROM stdin;\n2\tx\n\\.\n");
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+    // A file without any CREATE TABLE is most likely the wrong one.
+    std::fs::write(&schema, "INSERT INTO users VALUES (1, 'a@b.com');\n").unwrap();
+    let input = temp_path("schema_only_in.sql");
+    let output = temp_path("schema_only_out.sql");
+    std::fs::write(&input, sql).unwrap();
+    let err = ghostdb::processor::run_processing(&input, &output, &config, 42, &options).unwrap_err();
+    for path in [&schema, &input, &output] {
+        let _ = std::fs::remove_file(path);
+    }
+    assert_eq!(Failure::of(&err), Some(Failure::Config));
+}
+
+#[test]
+fn diff_lists_only_rewritten_statements() {
+    let config = config(USERS);
+    let input = temp_path("diff_in.sql");
+    let output = temp_path("diff_out.sql");
+    let diff = temp_path("diff.patch");
+    std::fs::write(
+        &input,
+        "-- dump\nINSERT INTO users (id, email) VALUES (1,\n  'a@b.com');\nINSERT INTO logs (id) VALUES (1);\n",
+    )
+    .unwrap();
+    let options = RunOptions {
+        diff: Some(diff.clone()),
+        ..Default::default()
+    };
+    ghostdb::processor::run_processing(&input, &output, &config, 42, &options).unwrap();
+    let patch = std::fs::read_to_string(&diff).unwrap();
+    let anonymized = std::fs::read_to_string(&output).unwrap();
+    for path in [&input, &output, &diff] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    let new_line = anonymized.lines().nth(1).unwrap();
+    let expected = format!(
+        "--- {}\n+++ {}\n@@ -2,2 +2 @@\n-INSERT INTO users (id, email) VALUES (1,\n-  'a@b.com');\n+{}\n",
+        input.display(),
+        output.display(),
+        new_line
+    );
+    assert_eq!(patch, expected);
+}
+
+#[test]
+fn when_guards_apply_strategies_per_row() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      email: !fixed hidden\n      country: !fixed XX\n    options:\n      email:\n        when: {column: country, equals: \"EU\"}\n",
+    );
+    let sql = "INSERT INTO users (email, country) VALUES ('a@b.com', 'EU');\n\
+               INSERT INTO users (email, country) VALUES ('c@d.com', 'US');\n\
+               INSERT INTO users (email) VALUES ('g@h.com');\n";
+    let out: Vec<String> = run_sql(sql, &config).lines().map(String::from).collect();
+    // The guard sees the original country even though it is rewritten too.
+    assert!(out[0].ends_with("VALUES ('hidden', 'XX');"), "{}", out[0]);
+    assert!(out[1].ends_with("VALUES ('c@d.com', 'XX');"), "{}", out[1]);
+    assert!(out[2].ends_with("VALUES ('g@h.com');"), "{}", out[2]);
+}
+
+#[test]
+fn binary_values_stay_single_tokens() {
+    let config = config("tables:\n  files:\n    columns:\n      name: mask\n      data: mask\n      thumb: random_bytes\n");
+    let out = run_sql(
+        "INSERT INTO files (name, data, thumb) VALUES ('a.png', _binary 'x,\\'y', 0xFFD8FF);\n",
+        &config,
+    );
+    assert!(out.contains(", _binary 'x,\\'y', 0x"), "{}", out);
+    assert!(!out.contains("0xFFD8FF"), "{}", out);
+}
+
+#[test]
+fn last_statement_without_semicolon_or_newline_is_anonymized() {
+    let config = config(USERS);
+    let input = temp_path("unterminated_in.sql");
+    let output = temp_path("unterminated_out.sql");
+    std::fs::write(
+        &input,
+        "INSERT INTO users (id, email) VALUES (1, 'alice@work.com');\nINSERT INTO users (id, email) VALUES (2, 'bob@work.com')",
+    )
+    .unwrap();
+    let result = ghostdb::processor::run_processing(&input, &output, &config, 42, &RunOptions::default());
+    let out = std::fs::read_to_string(&output).unwrap();
+    for path in [&input, &output] {
+        let _ = std::fs::remove_file(path);
+    }
+    result.unwrap();
+
+    assert_eq!(out.lines().count(), 2, "{}", out);
+    assert!(!out.contains("alice@work.com") && !out.contains("bob@work.com"), "{}", out);
+    assert!(out.lines().nth(1).unwrap().starts_with("INSERT INTO users (id, email) VALUES (2, '"), "{}", out);
+}
+
+#[test]
+fn trailing_comments_after_terminator_are_tolerated() {
+    let config = config(USERS);
+    let sql = "INSERT INTO users (id, email) VALUES (1, 'alice@work.com');  -- see (#12);\nINSERT INTO users SET id = 2, email = 'bob@work.com' /* last */";
+    let out = run_sql(sql, &config);
+    assert!(!out.contains("alice@work.com") && !out.contains("bob@work.com"), "{}", out);
+
+    let options = RunOptions {
+        preserve_formatting: true,
+        ..Default::default()
+    };
+    let (out, _) = process_sql(sql, &config, &options);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].ends_with("');  -- see (#12);"), "{}", out);
+    assert!(lines[1].ends_with("' /* last */"), "{}", out);
+    assert!(!out.contains("alice@work.com") && !out.contains("bob@work.com"), "{}", out);
+
+    assert_eq!(
+        ghostdb::sql::statement_body("INSERT INTO t VALUES ('a;b') ; -- x\n"),
+        "INSERT INTO t VALUES ('a;b')"
+    );
+}
+
+#[test]
+fn gendered_first_names_follow_the_gender_column() {
+    use ghostdb::names::{pool, Gender};
+    let (male, female, neutral) = (pool(Gender::Male), pool(Gender::Female), pool(Gender::Neutral));
+    let config = config("tables:\n  people:\n    columns:\n      first_name: gendered_first_name\n      nick: !gendered_first_name {gender_column: pronoun_set}\n");
+    let out = run_sql(
+        "INSERT INTO people (first_name, Gender) VALUES ('Bob', 'M');\nINSERT INTO people (first_name, gender) VALUES ('Bob', 'f');\nINSERT INTO people (first_name) VALUES ('Bob');\nINSERT INTO people (nick, pronoun_set, gender) VALUES ('Bobby', 'male', 'f');\n",
+        &config,
+    );
+    let names: Vec<&str> = out.lines().map(|l| l.split('\'').nth(1).unwrap()).collect();
+    assert!(male.contains(&names[0]), "{}", out);
+    assert!(female.contains(&names[1]), "{}", out);
+    assert!(neutral.contains(&names[2]), "{}", out);
+    assert!(male.contains(&names[3]), "{}", out);
+    // Deterministic per original name and gender.
+    assert_eq!(run_sql("INSERT INTO people (first_name, gender) VALUES ('Bob', 'male');\n", &config).split('\'').nth(1), Some(names[0]));
+}
+
+#[test]
+fn unsafe_keep_sample_leaves_tagged_rows_unchanged() {
+    use ghostdb::processor::KEPT_SAMPLE_TAG;
+    let config = config(USERS);
+    let sql: String = (0..400)
+        .map(|i| format!("INSERT INTO users (id, email) VALUES ({}, 'user{}@work.com');\n", i, i))
+        .collect();
+    let options = RunOptions {
+        unsafe_keep_sample: Some(0.25),
+        ..Default::default()
+    };
+    let (out, report) = process_sql(&sql, &config, &options);
+    let kept: Vec<&str> = out.lines().filter(|l| l.ends_with(KEPT_SAMPLE_TAG)).collect();
+    assert!((60..140).contains(&kept.len()), "{}", kept.len());
+    assert_eq!(report.kept_sample_statements, kept.len());
+    for (input, output) in sql.lines().zip(out.lines()) {
+        if output.ends_with(KEPT_SAMPLE_TAG) {
+            assert_eq!(output, format!("{} {}", input, KEPT_SAMPLE_TAG));
+        } else {
+            assert!(!output.contains("@work.com"), "{}", output);
+        }
+    }
+    assert_eq!(process_sql(&sql, &config, &options).0, out);
+}
+
+#[test]
+fn scrub_where_rewrites_predicate_literals_consistently() {
+    let config = config(USERS);
+    let sql = "\
+INSERT INTO users (id, email) VALUES (1, 'alice@work.com');
+DELETE FROM users WHERE email = 'alice@work.com' AND id = 1;
+UPDATE `users` u SET u.id = 2
+  WHERE 'alice@work.com' <> u.`email` -- email = 'x@y.com'
+  AND note = 'email = ''bob@work.com''';
+DELETE FROM orders WHERE email = 'alice@work.com';
+";
+    let options = RunOptions {
+        scrub_where: true,
+        ..Default::default()
+    };
+    let (out, report) = process_sql(sql, &config, &options);
+    let lines: Vec<&str> = out.lines().collect();
+    let fake = lines[0]
+        .trim_start_matches("INSERT INTO users (id, email) VALUES (1, ")
+        .trim_end_matches(");");
+    assert_ne!(fake, "'alice@work.com'");
+    assert_eq!(lines[1], format!("DELETE FROM users WHERE email = {} AND id = 1;", fake));
+    assert_eq!(lines[2], "UPDATE `users` u SET u.id = 2");
+    assert_eq!(lines[3], format!("  WHERE {} <> u.`email` -- email = 'x@y.com'", fake));
+    // Literals inside strings, and unconfigured tables, are left alone.
+    assert_eq!(lines[4], "  AND note = 'email = ''bob@work.com''';");
+    assert_eq!(lines[5], "DELETE FROM orders WHERE email = 'alice@work.com';");
+    assert_eq!(report.scrubbed_predicates, 2);
+
+    // Without the flag, predicates are copied through.
+    assert_eq!(run_sql(sql, &config).lines().nth(1), Some(sql.lines().nth(1).unwrap()));
+}
+
+#[test]
+fn scrub_where_rewrites_in_lists() {
+    let config = config(USERS);
+    let sql = "\
+INSERT INTO users (id, email) VALUES (1, 'alice@work.com');
+INSERT INTO users (id, email) VALUES (2, 'o''hara@work.com');
+DELETE FROM users WHERE email IN ('alice@work.com','o''hara@work.com', NULL);
+DELETE FROM users WHERE id NOT IN (SELECT id FROM users WHERE email in ( 'alice@work.com' ));
+UPDATE users SET id = 3 WHERE (id, `email`) IN ((2, 'o''hara@work.com'), (1, 'alice@work.com'));
+DELETE FROM users WHERE email IN ('alice@work.com', lower('x'));
+";
+    let options = RunOptions {
+        scrub_where: true,
+        ..Default::default()
+    };
+    let (out, report) = process_sql(sql, &config, &options);
+    let lines: Vec<&str> = out.lines().collect();
+    let fake = |line: &str| line.rsplit_once(", ").unwrap().1.trim_end_matches(");").to_string();
+    let (alice, ohara) = (fake(lines[0]), fake(lines[1]));
+    assert!(!alice.contains("alice") && !ohara.contains("hara"));
+    assert_eq!(lines[2], format!("DELETE FROM users WHERE email IN ({},{}, NULL);", alice, ohara));
+    assert_eq!(
+        lines[3],
+        format!("DELETE FROM users WHERE id NOT IN (SELECT id FROM users WHERE email in ( {} ));", alice)
+    );
+    assert_eq!(
+        lines[4],
+        format!("UPDATE users SET id = 3 WHERE (id, `email`) IN ((2, {}), (1, {}));", ohara, alice)
+    );
+    // A list that isn't all literals is left alone.
+    assert_eq!(lines[5], sql.lines().nth(5).unwrap());
+    assert_eq!(report.scrubbed_predicates, 3);
+}
+
+#[test]
+fn seed_from_gives_equal_names_distinct_fakes_per_row() {
+    let config = config(
+        "tables:\n  people:\n    columns:\n      id: keep\n      name: full_name\n    options:\n      name:\n        seed_from: id\n",
+    );
+    let sql = "\
+INSERT INTO people (id, name) VALUES (1, 'John Smith');
+INSERT INTO people (id, name) VALUES (2, 'John Smith');
+INSERT INTO people (id, name) VALUES ('1', 'John Smith');
+INSERT INTO people (id, name) VALUES (NULL, 'John Smith');
+INSERT INTO people (name) VALUES ('John Smith');
+";
+    let out = run_sql(sql, &config);
+    assert_eq!(out, run_sql(sql, &config));
+    let names: Vec<&str> = out.lines().map(|line| line.rsplit('\'').nth(1).unwrap()).collect();
+    assert_ne!(names[0], names[1]);
+    // The key is compared unquoted, so `1` and `'1'` are the same row.
+    assert_eq!(names[0], names[2]);
+    // Without a key, the value alone seeds the fake.
+    let by_value = Transformer::new(42).transform("'John Smith'", &ColumnStrategy::FullName).unwrap();
+    assert_eq!(format!("'{}'", names[3]), by_value);
+    assert_eq!(format!("'{}'", names[4]), by_value);
+}
+
+#[test]
+fn must_match_retries_until_the_fake_fits() {
+    // About half of all first names start with A-L.
+    let config = config(
+        "tables:\n  people:\n    columns:\n      name: first_name\n    options:\n      name:\n        must_match: '[A-L].*'\n",
+    );
+    let sql: String = (0..20)
+        .map(|i| format!("INSERT INTO people (name) VALUES ('Person {}');\n", i))
+        .collect();
+    let out = run_sql(&sql, &config);
+    assert_eq!(out, run_sql(&sql, &config));
+    for line in out.lines() {
+        let name = line.rsplit('\'').nth(1).unwrap();
+        assert!(('A'..='L').contains(&name.chars().next().unwrap()), "{}", line);
+    }
+
+    // A strategy that can't produce a match fails the run.
+    let never = common::config(
+        "tables:\n  people:\n    columns:\n      code: !fixed 'X-1'\n    options:\n      code:\n        must_match: '[0-9]+'\n",
+    );
+    let transformer = Transformer::new(42);
+    let err = ghostdb::processor::process_stream(
+        &transformer,
+        "INSERT INTO people (code) VALUES ('A');\n".as_bytes(),
+        &mut Vec::new(),
+        &never,
+        &RunOptions::default(),
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", err).contains("must_match"), "{:#}", err);
+}
+
+#[test]
+fn domain_from_links_addresses_to_the_rows_company() {
+    let config = config(
+        "tables:\n  accounts:\n    columns:\n      company: full_name\n      email: email\n      website: url\n      host: hostname\n      other: email\n    options:\n      email:\n        domain_from: company\n      website:\n        domain_from: company\n      host:\n        domain_from: company\n",
+    );
+    let sql = "\
+INSERT INTO accounts (company, email, website, host, other) VALUES ('Acme', 'a@acme.com', 'https://www.acme.com/about', 'acme.com:8080', 'x@acme.com');
+INSERT INTO accounts (company, email, website, host, other) VALUES ('Acme', 'b@acme.com', 'http://acme.com', 'mail.acme.com', 'y@acme.com');
+INSERT INTO accounts (company, email, website, host, other) VALUES ('Initech', 'c@initech.com', 'initech.com', 'initech.com', 'z@initech.com');
+INSERT INTO accounts (email) VALUES ('a@acme.com');
+";
+    let out = run_sql(sql, &config);
+    let rows: Vec<Vec<&str>> = out
+        .lines()
+        .map(|line| line.split('\'').skip(1).step_by(2).collect())
+        .collect();
+    let domain = |email: &str| email.split_once('@').unwrap().1.to_string();
+    let acme = domain(rows[0][1]);
+    assert_eq!(domain(rows[1][1]), acme);
+    assert!(rows[0][2].starts_with(&format!("https://www.{}/", acme)), "{}", rows[0][2]);
+    assert_eq!(rows[0][3], format!("{}:8080", acme));
+    assert_eq!(rows[1][3], acme);
+    assert_ne!(domain(rows[2][1]), acme);
+    assert!(rows[2][2].starts_with(&format!("https://{}/", domain(rows[2][1]))), "{}", rows[2][2]);
+    assert_eq!(rows[2][3], domain(rows[2][1]));
+    // Unlinked columns, and rows without the company, get independent fakes.
+    assert_ne!(domain(rows[0][4]), acme);
+    assert_eq!(
+        rows[3][0],
+        Transformer::new(42).transform("'a@acme.com'", &ColumnStrategy::Email).unwrap().trim_matches('\'')
+    );
+}
+
+#[test]
+fn company_email_follows_the_rows_anonymized_names() {
+    let config = config(
+        "tables:\n  staff:\n    columns:\n      first: first_name\n      last: last_name\n      email: !company_email {domain: acme.test, format: '{f}{last}'}\n  contacts:\n    columns:\n      email: company_email\n",
+    );
+    let sql = "\
+INSERT INTO staff (first, last, email) VALUES ('Alice', 'Smith', 'alice.smith@real.com');
+INSERT INTO contacts (email) VALUES ('bob@real.com');
+";
+    let out = run_sql(sql, &config);
+    let lines: Vec<&str> = out.lines().collect();
+    let values = Transformer::parse_values(
+        lines[0].split_once("VALUES (").unwrap().1.trim_end_matches(");"),
+    );
+    let unquote = |v: &str| v.trim_matches('\'').to_lowercase().replace(['\'', ' ', '-'], "");
+    let expected = format!(
+        "'{}{}@acme.test'",
+        unquote(&values[0]).chars().next().unwrap(),
+        unquote(&values[1])
+    );
+    assert_eq!(values[2], expected);
+
+    // Without name columns the local part is a seeded fake.
+    assert!(lines[1].ends_with("@example.com');"), "{}", lines[1]);
+    assert!(!lines[1].contains("bob@real.com"));
+}
+
+#[test]
+fn parse_values_round_trips_commas_and_doubled_quotes() {
+    let row = "'O''Brien', 'a,b', 'it''s, ''quoted''', 42, NULL, ''''";
+    let values = Transformer::parse_values(row);
+    assert_eq!(values, vec!["'O''Brien'", "'a,b'", "'it''s, ''quoted'''", "42", "NULL", "''''"]);
+    assert_eq!(values.join(", "), row);
+
+    let config = config("tables:\n  people:\n    columns:\n      name: full_name\n");
+    let out = run_sql("INSERT INTO people (name, note) VALUES ('O''Brien', 'a,b');\n", &config);
+    assert!(!out.contains("Brien") && out.ends_with(", 'a,b');\n"), "{}", out);
+}
+
+#[test]
+fn no_header_rebuild_replaces_only_changed_values() {
+    let config = config(USERS);
+    let options = RunOptions {
+        no_header_rebuild: true,
+        ..Default::default()
+    };
+    let sql = "\
+insert into users (id,email,note) values (1,'alice@work.com' , 'keep,  me' /* c */);
+INSERT users SET id=2,   email = 'bob@work.com', note='x';
+";
+    let (out, _) = process_sql(sql, &config, &options);
+    let lines: Vec<&str> = out.lines().collect();
+    let (head, tail) = lines[0].split_once("(1,").unwrap();
+    assert_eq!(head, "insert into users (id,email,note) values ");
+    assert!(tail.ends_with("' , 'keep,  me' /* c */);"), "{}", lines[0]);
+    assert!(!tail.contains("alice@work.com"));
+
+    let (head, tail) = lines[1].split_once("email = '").unwrap();
+    assert_eq!(head, "INSERT users SET id=2,   ");
+    assert!(tail.ends_with("', note='x';"), "{}", lines[1]);
+    assert!(!tail.contains("bob@work.com"));
+}
+
+/// Counts flushes, and what had been written at each.
+#[derive(Default)]
+struct FlushLog {
+    written: Vec<u8>,
+    flushed_at: Vec<usize>,
+}
+
+impl std::io::Write for FlushLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flushed_at.push(self.written.len());
+        Ok(())
+    }
+}
+
+#[test]
+fn stream_flushes_each_batch_of_statements() {
+    let config = config(USERS);
+    let sql = "\
+INSERT INTO users (id, email) VALUES (1, 'a@x.com');
+INSERT INTO users (id, email) VALUES (2,
+  'b@x.com');
+INSERT INTO users (id, email) VALUES (3, 'c@x.com');
+";
+    let options = RunOptions {
+        flush_every: Some(2),
+        ..Default::default()
+    };
+    let mut out = FlushLog::default();
+    ghostdb::processor::run_stream(sql.as_bytes(), &mut out, &config, 42, &options).unwrap();
+    let text = String::from_utf8(out.written).unwrap();
+    assert_eq!(text, run_sql(sql, &config));
+    // After the second statement, and at the end.
+    let second = text.match_indices('\n').nth(1).unwrap().0 + 1;
+    assert_eq!(out.flushed_at, vec![second, text.len()]);
+}
+
+#[test]
+fn pattern_column_keys_apply_when_no_exact_entry_does() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      '/.*email.*/': !fixed A\n      'glob:*_email*': !fixed B\n      contact_email: !fixed C\n      'glob:phone_?': !fixed D\n",
+    );
+    let sql = "INSERT INTO users (billing_email, user_email_address, contact_email, phone_1, phone_10, note) VALUES ('a', 'b', 'c', 'd', 'e', 'f');\n";
+    // Exact entries win; otherwise the first matching key in sorted order
+    // (`/...` sorts before `glob:...`).
+    assert_eq!(
+        run_sql(sql, &config),
+        "INSERT INTO users (billing_email, user_email_address, contact_email, phone_1, phone_10, note) VALUES ('A', 'A', 'C', 'D', 'e', 'f');\n"
+    );
+}
+
+#[test]
+fn threads_io_output_matches_a_serial_run() {
+    let config = config(USERS);
+    let mut sql = String::from("-- users\nCREATE TABLE users (id INT, email TEXT);\n");
+    for i in 0..2000 {
+        sql.push_str(&format!("INSERT INTO users (id, email) VALUES ({}, 'user{}@work.com');\n", i, i % 300));
+        if i % 97 == 0 {
+            sql.push_str(&format!("-- checkpoint {}\nUPDATE audit SET n = {};\n", i, i));
+        }
+    }
+    let input = temp_path("threads_in.sql");
+    let output = temp_path("threads_out.sql");
+    std::fs::write(&input, &sql).unwrap();
+
+    let mut outputs = Vec::new();
+    for threads_io in [None, Some(1), Some(2), Some(4)] {
+        let options = RunOptions {
+            threads_io,
+            overwrite: true,
+            ..Default::default()
+        };
+        ghostdb::processor::run_processing(&input, &output, &config, 42, &options).unwrap();
+        outputs.push(std::fs::read_to_string(&output).unwrap());
+    }
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+
+    assert!(outputs.iter().all(|out| *out == outputs[0]));
+    assert!(!outputs[0].contains("@work.com"));
+    // Statements that aren't rewritten keep their place.
+    let lines: Vec<&str> = outputs[0].lines().collect();
+    for (i, line) in sql.lines().enumerate().filter(|(_, l)| !l.starts_with("INSERT")) {
+        assert_eq!(lines[i], line);
+    }
+}
+
+#[test]
+fn default_threads_use_every_cpu_unless_the_run_must_be_serial() {
+    let config = config(USERS);
+    let cpus = std::thread::available_parallelism().map_or(1, usize::from);
+    let default = default_threads_io(&config, &RunOptions::default());
+    assert_eq!(default, (cpus > 1).then_some(cpus));
+
+    let serial = [
+        RunOptions {
+            diff: Some(temp_path("unused.patch")),
+            ..Default::default()
+        },
+        RunOptions {
+            resume: true,
+            ..Default::default()
+        },
+        RunOptions {
+            format: InputFormat::Csv,
+            ..Default::default()
+        },
+    ];
+    for options in &serial {
+        assert_eq!(default_threads_io(&config, options), None, "{:?}", options);
+    }
+    let unique = common::config("tables:\n  users:\n    columns:\n      email: email\n    options:\n      email: {unique: true}\n");
+    assert_eq!(default_threads_io(&unique, &RunOptions::default()), None);
+}
+
+#[test]
+fn summary_json_reports_counts_strategies_and_unconfigured_columns() {
+    let config = config(USERS);
+    let input = temp_path("summary_in.sql");
+    let output = temp_path("summary_out.sql");
+    let summary = temp_path("summary.json");
+    std::fs::write(
+        &input,
+        "-- dump\n\
+         INSERT INTO users (id, email, note) VALUES (1, 'ann@work.com', 'hi');\n\
+         INSERT INTO users (id, email, note) VALUES (2, 'bob@work.com', 'yo');\n\
+         INSERT INTO logs (id, msg) VALUES (1, 'boot');\n",
+    )
+    .unwrap();
+    let options = RunOptions {
+        summary_json: Some(summary.clone()),
+        ..Default::default()
+    };
+    ghostdb::processor::run_processing(&input, &output, &config, 42, &options).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&summary).unwrap()).unwrap();
+    for path in [&input, &output, &summary] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    assert_eq!(json["processed_lines"], 4);
+    assert_eq!(json["insert_statements"], 3);
+    assert_eq!(json["anonymized_statements"], 2);
+    assert_eq!(json["tables"]["users"]["statements"], 2);
+    assert_eq!(json["tables"]["users"]["columns"]["email"]["strategy"], "email");
+    assert_eq!(json["tables"]["users"]["columns"]["email"]["transformed"], 2);
+    assert_eq!(
+        json["unconfigured_columns"],
+        serde_json::json!(["logs.id", "logs.msg", "users.note"])
+    );
+}
+
+#[test]
+fn keys_differing_only_by_case_resolve_the_same_way_every_run() {
+    let yaml = "case_insensitive_tables: true\ntables:\n  users:\n    columns:\n      email: !fixed lower\n  Users:\n    columns:\n      email: !fixed upper\n      Phone: !fixed first\n      phone: !fixed second\n";
+    // Each config gets a fresh `HashMap` iteration order.
+    for _ in 0..20 {
+        let out = run_sql("INSERT INTO USERS (email, PHONE) VALUES ('a@b.com', '555');\n", &config(yaml));
+        assert_eq!(out, "INSERT INTO USERS (email, PHONE) VALUES ('upper', 'first');\n");
+    }
+}
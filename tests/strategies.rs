@@ -0,0 +1,440 @@
+mod common;
+
+use ghostdb::config::ColumnStrategy;
+use ghostdb::processor::{process_stream, RunOptions};
+use ghostdb::scanner::{guess_strategy, guess_strategy_among};
+use ghostdb::transformer::Transformer;
+
+fn apply(value: &str, strategy: &ColumnStrategy) -> String {
+    Transformer::new(42).transform(value, strategy).unwrap()
+}
+
+#[test]
+fn zip_code_keeps_prefix_and_format() {
+    let strategy = ColumnStrategy::ZipCode { keep_prefix: 3 };
+    let zip = apply("'94107'", &strategy);
+    assert!(zip.starts_with("'941") && zip.len() == 7, "{}", zip);
+
+    let plus4 = apply("'94107-1234'", &strategy);
+    assert!(plus4.starts_with("'941") && plus4.as_bytes()[6] == b'-', "{}", plus4);
+    assert_ne!(plus4, "'94107-1234'");
+    assert_eq!(apply("'94107-1234'", &strategy), plus4);
+
+    let uk = apply("'SW1A 1AA'", &strategy);
+    assert_eq!(uk.len(), 10);
+    assert_eq!(&uk[5..6], " ");
+    assert!(uk[1..5].chars().zip("SW1A".chars()).all(|(a, b)| a.is_ascii_digit() == b.is_ascii_digit()));
+}
+
+#[test]
+fn zip_code_columns_are_detected() {
+    for column in ["zip", "zip_code", "postal_code", "postcode"] {
+        assert_eq!(guess_strategy(column), ColumnStrategy::ZipCode { keep_prefix: 3 });
+    }
+}
+
+#[test]
+fn digit_scramble_preserves_format_with_one_mapping() {
+    let input = "'+1 (555) 010-0199'";
+    let phone = apply(input, &ColumnStrategy::DigitScramble);
+    assert_eq!(phone.len(), input.len());
+    assert_ne!(phone, input);
+    for (a, b) in phone.chars().zip(input.chars()) {
+        assert_eq!(a.is_ascii_digit(), b.is_ascii_digit());
+        if !b.is_ascii_digit() {
+            assert_eq!(a, b);
+        }
+    }
+
+    // One permutation for the whole column: equal digits map equally.
+    let all = apply("0123456789", &ColumnStrategy::DigitScramble);
+    let mut sorted: Vec<char> = all.chars().collect();
+    sorted.sort();
+    assert_eq!(sorted.into_iter().collect::<String>(), "0123456789");
+    let five = all.as_bytes()[5] as char;
+    assert_eq!(apply("555", &ColumnStrategy::DigitScramble), five.to_string().repeat(3));
+}
+
+#[test]
+fn url_replaces_host_and_optionally_keeps_the_rest() {
+    let keep = ColumnStrategy::Url { keep_path: true };
+    let input = "'https://user:pw@shop.acme.com:8443/orders/42?email=a%40b.com&x=1#top'";
+    let out = apply(input, &keep);
+    assert!(out.starts_with("'https://"), "{}", out);
+    assert!(out.ends_with(":8443/orders/42?email=a%40b.com&x=1#top'"), "{}", out);
+    assert!(!out.contains("acme") && !out.contains("user:pw"), "{}", out);
+
+    // The same host maps to the same pseudonym, also as a plain hostname.
+    let other = apply("'http://SHOP.acme.com/?q=1'", &keep);
+    let host = out.trim_matches('\'')["https://".len()..].split(':').next().unwrap();
+    assert_eq!(other, format!("'http://{}/?q=1'", host));
+    assert_eq!(apply("'shop.acme.com'", &ColumnStrategy::Hostname), format!("'{}'", host));
+
+    let fresh = apply(input, &ColumnStrategy::Url { keep_path: false });
+    assert!(fresh.starts_with(&format!("'https://{}/", host)), "{}", fresh);
+    assert!(!fresh.contains('?') && !fresh.contains("orders"), "{}", fresh);
+}
+
+#[test]
+fn hostname_keeps_www_and_port() {
+    let out = apply("'www.example.org:5432'", &ColumnStrategy::Hostname);
+    assert!(out.starts_with("'www.") && out.ends_with(":5432'"), "{}", out);
+    assert_eq!(guess_strategy("website_url"), ColumnStrategy::Url { keep_path: false });
+    assert_eq!(guess_strategy("db_host"), ColumnStrategy::Hostname);
+    let config: ghostdb::config::AppConfig =
+        serde_yaml::from_str("tables:\n  t:\n    columns:\n      a: url\n      b: !url {keep_path: true}\n")
+            .unwrap();
+    assert_eq!(config.tables["t"].columns["a"], ColumnStrategy::Url { keep_path: false });
+    assert_eq!(config.tables["t"].columns["b"], ColumnStrategy::Url { keep_path: true });
+}
+
+#[test]
+fn binary_literals_are_kept_by_text_strategies() {
+    for value in ["0x48656C6C6F", "X'48656C6C6F'", "b'0101'", "_binary 'Hi\\'!'", "'\\x4865'"] {
+        assert_eq!(apply(value, &ColumnStrategy::Email), value);
+        assert_eq!(apply(value, &ColumnStrategy::default_mask()), value);
+    }
+    assert_ne!(apply("'Hello'", &ColumnStrategy::default_mask()), "'Hello'");
+}
+
+#[test]
+fn random_bytes_keeps_length_and_form() {
+    let hex = apply("0x48656C6C6F", &ColumnStrategy::RandomBytes);
+    assert!(hex.starts_with("0x") && hex.len() == 12, "{}", hex);
+    assert_ne!(hex, "0x48656C6C6F");
+    assert!(hex[2..].chars().all(|c| c.is_ascii_hexdigit()));
+    assert_eq!(apply("0x48656C6C6F", &ColumnStrategy::RandomBytes), hex);
+
+    let quoted = apply("X'4865'", &ColumnStrategy::RandomBytes);
+    assert!(quoted.starts_with("X'") && quoted.ends_with('\'') && quoted.len() == 7, "{}", quoted);
+
+    let bits = apply("b'0101'", &ColumnStrategy::RandomBytes);
+    assert!(bits.len() == 7 && bits[2..6].chars().all(|c| c == '0' || c == '1'), "{}", bits);
+
+    // `Hi\'!` is four bytes.
+    let introduced = apply("_binary 'Hi\\'!'", &ColumnStrategy::RandomBytes);
+    assert!(introduced.starts_with("_binary 0x") && introduced.len() == 18, "{}", introduced);
+
+    let plain = apply("'abc'", &ColumnStrategy::RandomBytes);
+    assert!(plain.starts_with("0x") && plain.len() == 8, "{}", plain);
+
+    assert_eq!(apply("NULL", &ColumnStrategy::RandomBytes), "NULL");
+}
+
+#[test]
+fn mask_reveals_configured_ends() {
+    let config: ghostdb::config::AppConfig = serde_yaml::from_str(
+        "tables:\n  t:\n    columns:\n      a: mask\n      b: !mask {char: '#', reveal_start: 2, reveal_end: 3}\n      c:\n        mask: {reveal_end: 1}\n",
+    )
+    .unwrap();
+    let columns = &config.tables["t"].columns;
+    assert_eq!(columns["a"], ColumnStrategy::default_mask());
+
+    // The plain form keeps its original output.
+    assert_eq!(apply("'alice@work.com'", &columns["a"]), "'a***@work.com'");
+    assert_eq!(apply("'Alice'", &columns["a"]), "'A***'");
+
+    assert_eq!(apply("'4111111111111111'", &columns["b"]), "'41###111'");
+    assert_eq!(apply("'alexander@work.com'", &columns["b"]), "'al###der@work.com'");
+    assert_eq!(apply("'Émilie'", &columns["c"]), "'É***e'");
+    assert_eq!(apply("'abc'", &columns["b"]), "'#'");
+    assert_eq!(apply("'bob@work.com'", &columns["b"]), "'###@work.com'");
+}
+
+#[test]
+fn date_shift_keeps_format_and_is_stable_per_value() {
+    let strategy = ColumnStrategy::DateShift {
+        max_days: 30,
+        on_error: Default::default(),
+    };
+    let date = apply("'2023-04-01'", &strategy);
+    assert!(date.len() == 12 && date.starts_with("'2023-0") && date != "'2023-04-01'", "{}", date);
+    let stamp = apply("'2023-04-01 12:30:00'", &strategy);
+    assert!(stamp.ends_with(" 12:30:00'") && stamp.len() == 21, "{}", stamp);
+    // Each value moves by its own seeded offset, the same on every run.
+    assert_eq!(apply("'2023-04-01'", &strategy), date);
+    assert_eq!(Transformer::new(42).transform("'2023-04-01 12:30:00'", &strategy).unwrap(), stamp);
+    assert_ne!(Transformer::new(7).transform("'2023-04-01'", &strategy).unwrap(), date);
+    // Anything else passes through.
+    assert_eq!(apply("'next week'", &strategy), "'next week'");
+}
+
+#[test]
+fn on_error_replaces_unparseable_dates_and_coordinates() {
+    let config = common::config(
+        "tables:\n  events:\n    columns:\n      a: !date_shift {max_days: 30}\n      b: !date_shift {max_days: 30, on_error: null}\n      c: !date_shift {max_days: 30, on_error: !fixed 1970-01-01}\n      lat: !geo_fuzz {radius_m: 100, on_error: !fixed '0.0'}\n",
+    );
+    let transformer = Transformer::new(42);
+    let mut output = Vec::new();
+    let sql = "INSERT INTO events (a, b, c, lat) VALUES ('soon', 'soon', 'soon', 'north');\n\
+               INSERT INTO events (a, b, c, lat) VALUES (NULL, NULL, NULL, NULL);\n";
+    process_stream(&transformer, sql.as_bytes(), &mut output, &config, &RunOptions::default()).unwrap();
+    let out = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines[0],
+        "INSERT INTO events (a, b, c, lat) VALUES ('soon', NULL, '1970-01-01', '0.0');"
+    );
+    // NULL is never an error.
+    assert_eq!(lines[1], sql.lines().nth(1).unwrap().trim_start());
+    assert_eq!(transformer.unparseable_values(), 4);
+}
+
+#[test]
+fn prefixed_hash_is_stable_readable_and_seeded() {
+    let strategy = ColumnStrategy::PrefixedHash {
+        prefix: "user".to_string(),
+        len: 8,
+    };
+    let key = apply("'alice@work.com'", &strategy);
+    assert!(key.starts_with("'user_") && key.len() == 15, "{}", key);
+    assert!(key[6..14].chars().all(|c| c.is_ascii_hexdigit()), "{}", key);
+    assert_eq!(apply("'alice@work.com'", &strategy), key);
+    assert_ne!(apply("'bob@work.com'", &strategy), key);
+    // Quoted and unquoted numbers join, and the result is always a string.
+    assert_eq!(apply("42", &strategy), apply("'42'", &strategy));
+    assert_eq!(apply("NULL", &strategy), "NULL");
+    let other_seed = Transformer::new(7).transform("'alice@work.com'", &strategy).unwrap();
+    assert_ne!(other_seed, key);
+
+    let bare = ColumnStrategy::PrefixedHash {
+        prefix: String::new(),
+        len: 64,
+    };
+    assert_eq!(apply("'alice@work.com'", &bare).len(), 66);
+}
+
+#[test]
+fn hash_is_a_full_seeded_digest() {
+    let digest = apply("'sess_8f2b1c9e'", &ColumnStrategy::Hash);
+    assert!(digest.starts_with('\'') && digest.ends_with('\'') && digest.len() == 66, "{}", digest);
+    assert!(digest[1..65].chars().all(|c| c.is_ascii_hexdigit()), "{}", digest);
+    assert_eq!(apply("'sess_8f2b1c9e'", &ColumnStrategy::Hash), digest);
+    assert_ne!(apply("'sess_0000'", &ColumnStrategy::Hash), digest);
+    assert_eq!(apply("42", &ColumnStrategy::Hash), apply("'42'", &ColumnStrategy::Hash));
+    assert_eq!(apply("NULL", &ColumnStrategy::Hash), "NULL");
+    let other_seed = Transformer::new(7).transform("'sess_8f2b1c9e'", &ColumnStrategy::Hash).unwrap();
+    assert_ne!(other_seed, digest);
+
+    for column in ["session_token", "api_secret", "external_key"] {
+        assert_eq!(guess_strategy(column), ColumnStrategy::Hash, "{}", column);
+    }
+    assert_eq!(guess_strategy("password"), ColumnStrategy::Fixed("REDACTED_SECRET".to_string()));
+}
+
+#[test]
+fn template_fills_placeholders_deterministically() {
+    let strategy = ColumnStrategy::Template("{firstname:lower}.{lastname:lower}-{hex:4}@corp.test".to_string());
+    let email = apply("'alice@work.com'", &strategy);
+    assert!(email.starts_with('\'') && email.ends_with("@corp.test'"), "{}", email);
+    let local = &email[1..email.len() - "@corp.test'".len()];
+    let (name, hex) = local.rsplit_once('-').unwrap();
+    assert_eq!(name, name.to_lowercase());
+    assert!(hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit()), "{}", email);
+    assert_eq!(apply("'alice@work.com'", &strategy), email);
+    assert_ne!(apply("'bob@work.com'", &strategy), email);
+    assert_eq!(apply("NULL", &strategy), "NULL");
+
+    // Digits stay numeric for unquoted values; braces can be escaped.
+    let digits = ColumnStrategy::Template("{rand:6}".to_string());
+    let number = apply("123456", &digits);
+    assert!(number.len() == 6 && number.chars().all(|c| c.is_ascii_digit()), "{}", number);
+    assert_eq!(apply("'123456'", &digits), format!("'{}'", number));
+    let braces = ColumnStrategy::Template("{{id}}-{rand:2}".to_string());
+    assert!(apply("'a'", &braces).starts_with("'{id}-"));
+}
+
+#[test]
+fn job_titles_and_name_prefixes_are_seeded_and_quoted() {
+    let title = apply("'Head of Sales'", &ColumnStrategy::JobTitle);
+    assert!(title.starts_with('\'') && title.ends_with('\'') && title.len() > 2, "{}", title);
+    assert_eq!(apply("'Head of Sales'", &ColumnStrategy::JobTitle), title);
+
+    let prefix = apply("'Mrs.'", &ColumnStrategy::NamePrefix);
+    let bare = prefix.trim_matches('\'');
+    assert!(bare == "Miss" || bare.ends_with('.'), "{}", prefix);
+    for original in ["'Mr'", "'Dr'", "'Ms'"] {
+        let prefix = apply(original, &ColumnStrategy::NamePrefix);
+        assert!(["'Mr'", "'Mrs'", "'Ms'", "'Miss'", "'Mx'", "'Dr'", "'Prof'"].contains(&prefix.as_str()), "{}", prefix);
+    }
+    assert_eq!(apply("NULL", &ColumnStrategy::NamePrefix), "NULL");
+}
+
+#[test]
+fn title_columns_are_told_apart_by_their_neighbours() {
+    let columns = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let staff = columns(&["first_name", "last_name", "title", "department", "role"]);
+    assert_eq!(guess_strategy_among("title", &staff), ColumnStrategy::JobTitle);
+    assert_eq!(guess_strategy_among("role", &staff), ColumnStrategy::JobTitle);
+    let customers = columns(&["title", "first_name", "last_name"]);
+    assert_eq!(guess_strategy_among("title", &customers), ColumnStrategy::NamePrefix);
+    let documents = columns(&["title", "file_name", "role"]);
+    assert_eq!(guess_strategy_among("title", &documents), ColumnStrategy::Keep);
+    assert_eq!(guess_strategy_among("role", &documents), ColumnStrategy::Keep);
+    assert_eq!(guess_strategy("occupation"), ColumnStrategy::JobTitle);
+    assert_eq!(guess_strategy("salutation"), ColumnStrategy::NamePrefix);
+}
+
+#[test]
+fn json_pii_fakes_pii_leaves_and_keeps_the_document() {
+    let transformer = Transformer::new(42);
+    let email = transformer.transform("'alice@work.com'", &ColumnStrategy::Email).unwrap();
+    let phone = transformer.transform("'555-123-4567'", &ColumnStrategy::Phone).unwrap();
+    let doc = "'{\"alice@work.com\": 1, \"contact\": {\"email\": \"alice@work.com\", \"phone\": \"555-123-4567\"}, \"plan\": \"pro\", \"n\": 1.50, \"note\": \"it''s\"}'";
+    let out = transformer.transform(doc, &ColumnStrategy::JsonPii).unwrap();
+    assert_eq!(
+        out,
+        format!(
+            "'{{\"alice@work.com\": 1, \"contact\": {{\"email\": \"{}\", \"phone\": \"{}\"}}, \"plan\": \"pro\", \"n\": 1.50, \"note\": \"it''s\"}}'",
+            email.trim_matches('\''),
+            phone.trim_matches('\'')
+        )
+    );
+
+    let names = transformer
+        .transform("'[\"Alice Smith\", \"Berlin\", 7, null]'", &ColumnStrategy::JsonPii)
+        .unwrap();
+    assert!(names.ends_with(", \"Berlin\", 7, null]'"), "{}", names);
+    assert!(!names.contains("Alice Smith"), "{}", names);
+}
+
+#[test]
+fn json_pii_keeps_mysqldump_escaping() {
+    let transformer = Transformer::new(42);
+    let email = transformer.transform("'bob@x.org'", &ColumnStrategy::Email).unwrap();
+    let out = transformer
+        .transform(
+            "'{\\\"email\\\": \\\"bob@x.org\\\", \\\"bio\\\": \\\"O\\'Neil said \\\\\\\"hi\\\\\\\"\\\"}'",
+            &ColumnStrategy::JsonPii,
+        )
+        .unwrap();
+    assert_eq!(
+        out,
+        format!(
+            "'{{\\\"email\\\": \\\"{}\\\", \\\"bio\\\": \\\"O\\'Neil said \\\\\\\"hi\\\\\\\"\\\"}}'",
+            email.trim_matches('\'')
+        )
+    );
+}
+
+#[test]
+fn json_pii_keeps_values_that_are_not_json() {
+    let transformer = Transformer::new(42);
+    for value in ["'not json'", "'{\"a\": }'", "42", "NULL"] {
+        assert_eq!(transformer.transform(value, &ColumnStrategy::JsonPii).unwrap(), value);
+    }
+    assert_eq!(transformer.unparseable_values(), 3);
+}
+
+#[test]
+fn scrub_text_replaces_only_the_pii_in_prose() {
+    let transformer = Transformer::new(42);
+    let fake = |value: &str, strategy: &ColumnStrategy| {
+        transformer.transform(value, strategy).unwrap().trim_matches('\'').to_string()
+    };
+    let out = transformer
+        .transform(
+            "'Contact: john@x.com, phone (555) 123-4567; SSN 123-45-6789. Order 2024-01-15, ref ab555-123-4567.'",
+            &ColumnStrategy::ScrubText,
+        )
+        .unwrap();
+    assert_eq!(
+        out,
+        format!(
+            "'Contact: {}, phone {}; SSN {}. Order 2024-01-15, ref ab555-123-4567.'",
+            fake("john@x.com", &ColumnStrategy::Email),
+            fake("(555) 123-4567", &ColumnStrategy::Phone),
+            fake("123-45-6789", &ColumnStrategy::Ssn)
+        )
+    );
+}
+
+#[test]
+fn scrub_text_keeps_quoting_and_escapes() {
+    let transformer = Transformer::new(42);
+    let email = transformer.transform("'ann@b.org'", &ColumnStrategy::Email).unwrap();
+    let email = email.trim_matches('\'');
+    assert_eq!(
+        transformer.transform("'it''s ann@b.org'", &ColumnStrategy::ScrubText).unwrap(),
+        format!("'it''s {}'", email)
+    );
+    assert_eq!(
+        transformer
+            .transform("'it\\'s\\nann@b.org'", &ColumnStrategy::ScrubText)
+            .unwrap(),
+        format!("'it\\'s\\n{}'", email)
+    );
+    for value in ["'nothing to see'", "42", "NULL"] {
+        assert_eq!(transformer.transform(value, &ColumnStrategy::ScrubText).unwrap(), value);
+    }
+}
+
+#[test]
+fn random_int_stays_in_range_and_unquoted() {
+    let strategy = ColumnStrategy::RandomInt { min: 18, max: 90 };
+    for value in ["42", "'42'", "7", "1000"] {
+        let out = apply(value, &strategy);
+        let n: i64 = out.parse().unwrap_or_else(|_| panic!("not a bare integer: {}", out));
+        assert!((18..=90).contains(&n), "{}", out);
+        assert_eq!(apply(value, &strategy), out);
+    }
+    assert_eq!(apply("NULL", &strategy), "NULL");
+    assert_eq!(apply("5", &ColumnStrategy::RandomInt { min: -3, max: -3 }), "-3");
+
+    let err = common::config("tables:\n  t:\n    columns:\n      age: !random_int {min: 9, max: 1}\n")
+        .validate()
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("t.age: `random_int` `min` 9 is greater than `max` 1"), "{:#}", err);
+}
+
+#[test]
+fn pseudonyms_agree_across_tables_sharing_a_namespace() {
+    let config = common::config(
+        "tables:\n  users:\n    seed: 7\n    columns:\n      id: !pseudonym {namespace: users}\n      ref: !pseudonym {namespace: users}\n  orders:\n    columns:\n      user_id: !pseudonym {namespace: users}\n      code: !pseudonym {namespace: orders}\n",
+    );
+    let mut sql = String::new();
+    for id in 100..300 {
+        sql.push_str(&format!("INSERT INTO users (id, ref) VALUES ({}, 'a1b2-C3D4');\n", id));
+        sql.push_str(&format!("INSERT INTO orders (user_id, code) VALUES ('{}', {});\n", id, id));
+    }
+    let out = common::run_sql(&sql, &config);
+    let lines: Vec<&str> = out.lines().collect();
+    let value = |line: &str, i: usize| {
+        let values = line.rsplit_once("VALUES (").unwrap().1.trim_end_matches(");");
+        values.split(", ").nth(i).unwrap().to_string()
+    };
+    let mut ids = std::collections::HashSet::new();
+    for pair in lines.chunks(2) {
+        let id = value(pair[0], 0);
+        // Same pseudonym for the key and the foreign key, despite the
+        // table seed and the quotes.
+        assert_eq!(format!("'{}'", id), value(pair[1], 0), "{:?}", pair);
+        assert!(id.len() == 3 && !id.starts_with('0'), "{}", id);
+        // Another namespace maps the same value elsewhere.
+        assert_ne!(value(pair[1], 1), id);
+        ids.insert(id);
+        let reference = value(pair[0], 1);
+        assert_ne!(reference, "'a1b2-C3D4'");
+        assert!(reference.chars().all(|c| c.is_ascii_hexdigit() || "'-".contains(c)), "{}", reference);
+    }
+    // Distinct ids stay distinct.
+    assert_eq!(ids.len(), 200);
+    assert_eq!(common::run_sql("INSERT INTO users (id) VALUES (NULL);\n", &config), "INSERT INTO users (id) VALUES (NULL);\n");
+}
+
+#[test]
+fn null_out_nulls_a_stable_fraction_of_values() {
+    let config = common::config("tables:\n  t:\n    columns:\n      notes: !null_out {probability: 0.3}\n");
+    assert_eq!(config.tables["t"].columns["notes"], ColumnStrategy::RandomNull { probability: 0.3 });
+    let sql: String = (0..1000)
+        .map(|i| format!("INSERT INTO t (notes) VALUES ('note {}');\n", i))
+        .collect();
+    let out = common::run_sql(&sql, &config);
+    assert_eq!(out, common::run_sql(&sql, &config));
+    let nulled = out.lines().filter(|l| l.contains("(NULL)")).count();
+    assert!((200..400).contains(&nulled), "{}", nulled);
+    // Kept values are untouched.
+    for (before, after) in sql.lines().zip(out.lines()) {
+        assert!(after == before || after.contains("(NULL)"), "{}", after);
+    }
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{config, process_sql, run_sql};
+use ghostdb::processor::RunOptions;
+use ghostdb::schema::parse_create_table;
+
+const SCHEMA: &str = "\
+CREATE TABLE `users` (
+  `id` int NOT NULL AUTO_INCREMENT,
+  `bio` TEXT CHARACTER SET utf8mb4,
+  `nickname` text,
+  `born` date DEFAULT NULL,
+  `status` enum('active','it''s off') NOT NULL,
+  PRIMARY KEY (`id`)
+);
+";
+
+const TYPE_RULES: &str = "\
+type_rules:
+  text: !fixed REDACTED
+  date: !fixed 1970-01-01
+tables:
+  users:
+    columns:
+      nickname: keep
+";
+
+#[test]
+fn create_table_columns_and_types_are_parsed() {
+    let schema = parse_create_table(SCHEMA).unwrap();
+    assert_eq!(schema.name, "users");
+    let types: Vec<(&str, &str)> = schema
+        .columns
+        .iter()
+        .map(|c| (c.name.as_str(), c.sql_type.as_str()))
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            ("id", "int"),
+            ("bio", "TEXT"),
+            ("nickname", "text"),
+            ("born", "date"),
+            ("status", "enum('active','it''s off')"),
+        ]
+    );
+    assert_eq!(schema.columns[4].enum_values, vec!["active", "it's off"]);
+}
+
+#[test]
+fn columns_without_a_name_rule_fall_back_to_their_type() {
+    let config = config(TYPE_RULES);
+    let sql = format!(
+        "{}INSERT INTO users (id, bio, nickname, born) VALUES (1, 'about me', 'nick', '1990-05-01');\n",
+        SCHEMA
+    );
+    let out = run_sql(&sql, &config);
+    assert!(
+        out.ends_with("VALUES (1, 'REDACTED', 'nick', '1970-01-01');\n"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn type_rules_cover_tables_missing_from_the_config() {
+    let config = config(&TYPE_RULES.replace("  text:", "  varchar:"));
+    let sql = "CREATE TABLE notes (body varchar(20), id bigint);\nINSERT INTO notes (body, id) VALUES ('x', 1);\n";
+    let (out, report) = process_sql(sql, &config, &RunOptions::default());
+    assert!(out.ends_with("INSERT INTO notes (body, id) VALUES ('REDACTED', 1);\n"), "{}", out);
+    assert!(report.unconfigured_columns.contains("notes.id"));
+    assert!(!report.unconfigured_columns.contains("notes.body"));
+}
+
+#[test]
+fn columns_without_schema_are_kept() {
+    let config = config(TYPE_RULES);
+    let sql = "INSERT INTO users (bio) VALUES ('about me');\n";
+    assert_eq!(run_sql(sql, &config), sql);
+}
+
+#[test]
+fn enum_columns_get_another_member_of_their_enum() {
+    let config = config("tables:\n  users:\n    columns:\n      status: enum\n");
+    let rows = "INSERT INTO users (id, status) VALUES (1, 'active');\n\
+                INSERT INTO users (id, status) VALUES (2, 'it''s off');\n\
+                INSERT INTO users (id, status) VALUES (3, 'active');\n\
+                INSERT INTO users (id, status) VALUES (4, 'gone');\n";
+    let (out, report) = process_sql(&format!("{}{}", SCHEMA, rows), &config, &RunOptions::default());
+    let out: Vec<&str> = out.lines().skip(SCHEMA.lines().count()).collect();
+    assert_eq!(out[0], "INSERT INTO users (id, status) VALUES (1, 'it''s off');");
+    assert_eq!(out[1], "INSERT INTO users (id, status) VALUES (2, 'active');");
+    assert_eq!(out[2], "INSERT INTO users (id, status) VALUES (3, 'it''s off');");
+    assert_eq!(out[3], "INSERT INTO users (id, status) VALUES (4, 'gone');");
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.warnings[0].contains("'gone'"), "{:?}", report.warnings);
+}
+
+#[test]
+fn enum_columns_without_schema_are_kept_with_a_warning() {
+    let config = config("tables:\n  users:\n    columns:\n      status: enum\n");
+    let sql = "INSERT INTO users (status) VALUES ('active');\nINSERT INTO users (status) VALUES ('x');\n";
+    let (out, report) = process_sql(sql, &config, &RunOptions::default());
+    assert_eq!(out, sql);
+    assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{config, temp_path};
+use ghostdb::config::{AppConfig, ConfigFormat};
+use ghostdb::failure::Failure;
+
+#[test]
+fn load_rejects_parameters_that_cannot_work() {
+    let path = temp_path("invalid_config.yaml");
+    std::fs::write(
+        &path,
+        "tables:\n  users:\n    columns:\n      zip: !zip_code {keep_prefix: 7}\n      tags: !array_of {mask: {char: \"'\"}}\n      loc: !geo_fuzz {radius_m: -5}\n      email: !company_email {domain: ''}\n      id: !tokenize {prefix: \"it's\"}\n      key: !prefixed_hash {prefix: k, len: 0}\n      born: !date_shift {max_days: 30, on_error: !fixed \"'\"}\n      code: !template \"EMP-{bogus}\"\n      '/[a-z/': email\n      notes: !null_out {probability: 1.5}\n    options:\n      name:\n        max_len: 0\n      email:\n        must_match: '[a-z'\ntype_rules:\n  date: !date_shift {max_days: 99999999}\n",
+    )
+    .unwrap();
+    let err = AppConfig::load(&path).unwrap_err();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(Failure::of(&err), Some(Failure::Config));
+    let message = format!("{:#}", err);
+    for problem in [
+        "users./[a-z/: not a valid column pattern",
+        "type_rules.date: `max_days` is 99999999",
+        "users.born: `on_error` `fixed` value contains a quote",
+        "users.code: `template` \"EMP-{bogus}\" has an unknown placeholder `{bogus}`",
+        "users.email: `company_email` `domain` \"\" is not a domain name",
+        "users.email: `must_match` \"[a-z\" is not a valid regex",
+        "users.id: `tokenize` `prefix` contains a quote",
+        "users.key: `len` is 0",
+        "users.loc: `radius_m` is -5",
+        "users.name: `max_len` must be at least 1",
+        "users.notes: `probability` is 1.5, but must be from 0.0 to 1.0",
+        "users.tags: `mask` `char` is a quote or backslash",
+        "users.zip: `keep_prefix` is 7",
+    ] {
+        assert!(message.contains(problem), "{} not in {}", problem, message);
+    }
+}
+
+#[test]
+fn sensible_parameters_pass_validation() {
+    let config = config(
+        "tables:\n  users:\n    columns:\n      zip: !zip_code {keep_prefix: 5}\n      email: !company_email {domain: acme.test}\n      loc: !geo_fuzz {radius_m: 0}\n      seen: !random_null {probability: 0.5}\n",
+    );
+    config.validate().unwrap();
+}
+
+#[test]
+fn yaml_json_and_toml_configs_load_the_same() {
+    let yaml = "tables:\n  users:\n    columns:\n      id: keep\n      email: email\n      notes: mask\n      plan: !fixed basic\n      customer: !tokenize {prefix: customer}\n    options:\n      email:\n        unique: true\ncase_insensitive_tables: true\ntype_rules:\n  date: !date_shift {max_days: 30}\n";
+    let json = r#"{
+  "tables": {
+    "users": {
+      "columns": {
+        "id": "keep",
+        "email": "email",
+        "notes": "mask",
+        "plan": {"fixed": "basic"},
+        "customer": {"tokenize": {"prefix": "customer"}}
+      },
+      "options": {"email": {"unique": true}}
+    }
+  },
+  "case_insensitive_tables": true,
+  "type_rules": {"date": {"date_shift": {"max_days": 30}}}
+}"#;
+    let toml = "case_insensitive_tables = true\n\n[tables.users.columns]\nid = \"keep\"\nemail = \"email\"\nnotes = \"mask\"\nplan = { fixed = \"basic\" }\ncustomer = { tokenize = { prefix = \"customer\" } }\n\n[tables.users.options.email]\nunique = true\n\n[type_rules]\ndate = { date_shift = { max_days = 30 } }\n";
+    let mut loaded = Vec::new();
+    for (name, text) in [("parity.yaml", yaml), ("parity.json", json), ("parity.toml", toml)] {
+        let path = temp_path(name);
+        std::fs::write(&path, text).unwrap();
+        let config = AppConfig::load(&path);
+        let _ = std::fs::remove_file(&path);
+        // Through `serde_json::Value`, whose maps are sorted.
+        loaded.push(serde_json::to_value(config.unwrap()).unwrap());
+    }
+    assert_eq!(loaded[0], loaded[1]);
+    assert_eq!(loaded[0], loaded[2]);
+
+    // Written back in each format, the config loads unchanged.
+    let config: AppConfig = serde_json::from_value(loaded[0].clone()).unwrap();
+    for (name, format) in [("back.yml", ConfigFormat::Yaml), ("back.json", ConfigFormat::Json), ("back.toml", ConfigFormat::Toml)] {
+        let path = temp_path(name);
+        std::fs::write(&path, config.to_string_as(format).unwrap()).unwrap();
+        let reloaded = AppConfig::load(&path);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(serde_json::to_value(reloaded.unwrap()).unwrap(), loaded[0], "{:?}", format);
+    }
+}
+
+#[test]
+fn configs_with_unknown_extensions_are_refused() {
+    let path = temp_path("config.ini");
+    std::fs::write(&path, "tables: {}\n").unwrap();
+    let err = AppConfig::load(&path).unwrap_err();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(Failure::of(&err), Some(Failure::Config));
+    assert!(format!("{:#}", err).contains("use a .yaml, .yml, .json or .toml file"), "{:#}", err);
+}
+
+#[test]
+fn commands_inside_array_of_need_allow_commands() {
+    let config = config("tables:\n  posts:\n    columns:\n      tags: !array_of\n        command:\n          program: sh\n          args: [-c, 'echo X']\n");
+    assert!(config.has_command_strategies());
+    let err = config.ensure_commands_allowed(false).unwrap_err();
+    assert_eq!(Failure::of(&err), Some(Failure::Config));
+    assert!(format!("{:#}", err).contains("--allow-commands"), "{:#}", err);
+    assert!(config.ensure_commands_allowed(true).is_ok());
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{config, run_sql};
+use ghostdb::config::ColumnStrategy;
+use ghostdb::scanner::guess_strategy;
+use ghostdb::transformer::Transformer;
+use ghostdb::vin;
+
+#[test]
+fn check_digit_matches_known_vins() {
+    assert!(vin::is_valid("1M8GDM9AXKP042788"));
+    assert!(vin::is_valid("11111111111111111"));
+    assert_eq!(vin::check_digit("1M8GDM9A_KP042788"), Some('X'));
+    assert!(!vin::is_valid("1M8GDM9A1KP042788"));
+    assert!(!vin::is_valid("1M8GDM9AXKP04278"));
+    assert!(!vin::is_valid("IM8GDM9AXKP042788"));
+}
+
+#[test]
+fn generated_vins_are_valid() {
+    let transformer = Transformer::new(7);
+    for i in 0..500 {
+        let original = format!("'1HGCM82633A{:06}'", i);
+        let fake = transformer.transform(&original, &ColumnStrategy::Vin).unwrap();
+        let fake = fake.trim_matches('\'');
+        assert!(vin::is_valid(fake), "{}", fake);
+        assert!(!fake.contains(['I', 'O', 'Q']), "{}", fake);
+    }
+}
+
+#[test]
+fn non_vin_values_keep_their_length() {
+    let config = config("tables:\n  cars:\n    columns:\n      vin: vin\n");
+    let out = run_sql("INSERT INTO cars (vin) VALUES ('ABC123');\n", &config);
+    let value = out.split('\'').nth(1).unwrap();
+    assert_eq!(value.len(), 6);
+    assert_ne!(value, "ABC123");
+}
+
+#[test]
+fn vin_columns_are_detected() {
+    assert_eq!(guess_strategy("vin"), ColumnStrategy::Vin);
+    assert_eq!(guess_strategy("vehicle_vin"), ColumnStrategy::Vin);
+    assert_eq!(guess_strategy("province"), ColumnStrategy::Keep);
+}